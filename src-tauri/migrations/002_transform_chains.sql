@@ -0,0 +1,7 @@
+-- Saved paste-time transform chains
+
+CREATE TABLE IF NOT EXISTS transform_chains (
+    name TEXT PRIMARY KEY,
+    transforms TEXT NOT NULL,                -- JSON array of transform names, applied in order
+    updated_at INTEGER NOT NULL              -- unix timestamp (seconds)
+);
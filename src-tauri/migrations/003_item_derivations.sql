@@ -0,0 +1,10 @@
+-- Links items built from other items (e.g. joined pastes) back to their originals
+
+CREATE TABLE IF NOT EXISTS item_derivations (
+    item_id INTEGER NOT NULL REFERENCES clipboard_items(id) ON DELETE CASCADE,
+    source_id INTEGER NOT NULL REFERENCES clipboard_items(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,               -- order of the source in the derived item
+    PRIMARY KEY (item_id, source_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_derivations_source ON item_derivations(source_id);
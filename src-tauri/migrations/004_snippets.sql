@@ -0,0 +1,14 @@
+-- Snippet library: named, permanent items kept outside clipboard history.
+-- Retention and max_items cleanup only touch clipboard_items, never this table.
+
+CREATE TABLE IF NOT EXISTS snippets (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    abbreviation TEXT UNIQUE,                -- optional short trigger, e.g. "sig"
+    folder TEXT,                             -- optional grouping, NULL = top level
+    content TEXT NOT NULL,
+    created_at INTEGER NOT NULL,             -- unix timestamp (seconds)
+    updated_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_snippets_folder ON snippets(folder);
@@ -0,0 +1,40 @@
+-- Team snippet packs: read-only snippets loaded from a watched directory,
+-- plus FTS over all snippets so `search` can find them.
+
+ALTER TABLE snippets ADD COLUMN tags TEXT NOT NULL DEFAULT '[]';   -- JSON array of tag names
+ALTER TABLE snippets ADD COLUMN pack_path TEXT;                    -- source pack file, NULL for user snippets
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_snippets_pack_name
+    ON snippets(pack_path, name) WHERE pack_path IS NOT NULL;
+
+CREATE VIRTUAL TABLE IF NOT EXISTS snippets_fts USING fts5(
+    name,
+    abbreviation,
+    folder,
+    tags,
+    content,
+    content='snippets',
+    content_rowid='id'
+);
+
+CREATE TRIGGER IF NOT EXISTS snippets_fts_insert AFTER INSERT ON snippets BEGIN
+    INSERT INTO snippets_fts(rowid, name, abbreviation, folder, tags, content)
+    VALUES (NEW.id, NEW.name, NEW.abbreviation, NEW.folder, NEW.tags, NEW.content);
+END;
+
+CREATE TRIGGER IF NOT EXISTS snippets_fts_delete AFTER DELETE ON snippets BEGIN
+    INSERT INTO snippets_fts(snippets_fts, rowid, name, abbreviation, folder, tags, content)
+    VALUES ('delete', OLD.id, OLD.name, OLD.abbreviation, OLD.folder, OLD.tags, OLD.content);
+END;
+
+CREATE TRIGGER IF NOT EXISTS snippets_fts_update AFTER UPDATE ON snippets BEGIN
+    INSERT INTO snippets_fts(snippets_fts, rowid, name, abbreviation, folder, tags, content)
+    VALUES ('delete', OLD.id, OLD.name, OLD.abbreviation, OLD.folder, OLD.tags, OLD.content);
+    INSERT INTO snippets_fts(rowid, name, abbreviation, folder, tags, content)
+    VALUES (NEW.id, NEW.name, NEW.abbreviation, NEW.folder, NEW.tags, NEW.content);
+END;
+
+-- Index snippets created before this migration
+INSERT INTO snippets_fts(snippets_fts) VALUES ('rebuild');
+
+INSERT OR IGNORE INTO settings (key, value) VALUES ('snippet_pack_dir', '');
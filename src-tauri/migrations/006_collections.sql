@@ -0,0 +1,19 @@
+-- Named collections (boards) with a manual order. Items in any collection
+-- are protected from retention and max_items cleanup.
+
+CREATE TABLE IF NOT EXISTS collections (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    position INTEGER NOT NULL,               -- manual order of collections
+    created_at INTEGER NOT NULL              -- unix timestamp (seconds)
+);
+
+CREATE TABLE IF NOT EXISTS collection_items (
+    collection_id INTEGER NOT NULL REFERENCES collections(id) ON DELETE CASCADE,
+    item_id INTEGER NOT NULL REFERENCES clipboard_items(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,               -- manual order within the collection
+    added_at INTEGER NOT NULL,
+    PRIMARY KEY (collection_id, item_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_collection_items_item ON collection_items(item_id);
@@ -0,0 +1,41 @@
+-- Optional title and note on clipboard items, indexed for search.
+-- Replaces the clipboard_fts table and triggers from 001_init.sql so the
+-- index covers the new columns.
+
+ALTER TABLE clipboard_items ADD COLUMN title TEXT;
+ALTER TABLE clipboard_items ADD COLUMN note TEXT;
+
+DROP TRIGGER IF EXISTS fts_insert;
+DROP TRIGGER IF EXISTS fts_delete;
+DROP TRIGGER IF EXISTS fts_update;
+DROP TABLE IF EXISTS clipboard_fts;
+
+CREATE VIRTUAL TABLE IF NOT EXISTS clipboard_fts USING fts5(
+    content,
+    category,
+    source_app,
+    title,
+    note,
+    content='clipboard_items',
+    content_rowid='id'
+);
+
+CREATE TRIGGER IF NOT EXISTS fts_insert AFTER INSERT ON clipboard_items BEGIN
+    INSERT INTO clipboard_fts(rowid, content, category, source_app, title, note)
+    VALUES (NEW.id, NEW.content, NEW.category, NEW.source_app, NEW.title, NEW.note);
+END;
+
+CREATE TRIGGER IF NOT EXISTS fts_delete AFTER DELETE ON clipboard_items BEGIN
+    INSERT INTO clipboard_fts(clipboard_fts, rowid, content, category, source_app, title, note)
+    VALUES ('delete', OLD.id, OLD.content, OLD.category, OLD.source_app, OLD.title, OLD.note);
+END;
+
+CREATE TRIGGER IF NOT EXISTS fts_update AFTER UPDATE ON clipboard_items BEGIN
+    INSERT INTO clipboard_fts(clipboard_fts, rowid, content, category, source_app, title, note)
+    VALUES ('delete', OLD.id, OLD.content, OLD.category, OLD.source_app, OLD.title, OLD.note);
+    INSERT INTO clipboard_fts(rowid, content, category, source_app, title, note)
+    VALUES (NEW.id, NEW.content, NEW.category, NEW.source_app, NEW.title, NEW.note);
+END;
+
+-- Re-index existing rows into the new table
+INSERT INTO clipboard_fts(clipboard_fts) VALUES ('rebuild');
@@ -0,0 +1,11 @@
+-- Previous versions of edited clipboard items
+
+CREATE TABLE IF NOT EXISTS item_revisions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    item_id INTEGER NOT NULL REFERENCES clipboard_items(id) ON DELETE CASCADE,
+    content TEXT NOT NULL,                   -- content before the edit
+    hash TEXT NOT NULL,
+    replaced_at INTEGER NOT NULL             -- unix timestamp (seconds) of the edit
+);
+
+CREATE INDEX IF NOT EXISTS idx_revisions_item ON item_revisions(item_id, replaced_at DESC);
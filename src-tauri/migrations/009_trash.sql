@@ -0,0 +1,7 @@
+-- Soft delete: deleted items stay in the trash until purged
+
+ALTER TABLE clipboard_items ADD COLUMN deleted_at INTEGER;   -- unix timestamp (seconds), NULL = not deleted
+
+CREATE INDEX IF NOT EXISTS idx_deleted_at ON clipboard_items(deleted_at) WHERE deleted_at IS NOT NULL;
+
+INSERT OR IGNORE INTO settings (key, value) VALUES ('trash_retention_days', '7');
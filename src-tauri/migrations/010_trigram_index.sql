@@ -0,0 +1,31 @@
+-- Substring index over item text. The trigram tokenizer matches any run of
+-- three or more characters, so partial words, hashes and CJK text are searchable.
+
+CREATE VIRTUAL TABLE IF NOT EXISTS clipboard_trigram USING fts5(
+    content,
+    title,
+    note,
+    content='clipboard_items',
+    content_rowid='id',
+    tokenize='trigram'
+);
+
+CREATE TRIGGER IF NOT EXISTS trigram_insert AFTER INSERT ON clipboard_items BEGIN
+    INSERT INTO clipboard_trigram(rowid, content, title, note)
+    VALUES (NEW.id, NEW.content, NEW.title, NEW.note);
+END;
+
+CREATE TRIGGER IF NOT EXISTS trigram_delete AFTER DELETE ON clipboard_items BEGIN
+    INSERT INTO clipboard_trigram(clipboard_trigram, rowid, content, title, note)
+    VALUES ('delete', OLD.id, OLD.content, OLD.title, OLD.note);
+END;
+
+CREATE TRIGGER IF NOT EXISTS trigram_update AFTER UPDATE OF content, title, note ON clipboard_items BEGIN
+    INSERT INTO clipboard_trigram(clipboard_trigram, rowid, content, title, note)
+    VALUES ('delete', OLD.id, OLD.content, OLD.title, OLD.note);
+    INSERT INTO clipboard_trigram(rowid, content, title, note)
+    VALUES (NEW.id, NEW.content, NEW.title, NEW.note);
+END;
+
+-- Index existing rows
+INSERT INTO clipboard_trigram(clipboard_trigram) VALUES ('rebuild');
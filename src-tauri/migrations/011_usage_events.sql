@@ -0,0 +1,19 @@
+-- Usage history: every capture and every paste-back of an item
+
+CREATE TABLE IF NOT EXISTS usage_events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    item_id INTEGER NOT NULL REFERENCES clipboard_items(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL CHECK (kind IN ('capture', 'paste')),
+    app TEXT,                                -- source app for captures, target app for pastes
+    occurred_at INTEGER NOT NULL             -- unix timestamp (seconds)
+);
+
+CREATE INDEX IF NOT EXISTS idx_usage_events_item ON usage_events(item_id, occurred_at DESC);
+CREATE INDEX IF NOT EXISTS idx_usage_events_time ON usage_events(occurred_at);
+
+ALTER TABLE clipboard_items ADD COLUMN use_count INTEGER NOT NULL DEFAULT 0;  -- re-captures and paste-backs
+ALTER TABLE clipboard_items ADD COLUMN last_used_at INTEGER;   -- last capture or paste-back
+
+CREATE INDEX IF NOT EXISTS idx_use_count ON clipboard_items(use_count DESC);
+
+UPDATE clipboard_items SET last_used_at = copied_at;
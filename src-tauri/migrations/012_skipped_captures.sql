@@ -0,0 +1,10 @@
+-- Clipboard content the monitor saw but did not store, kept for statistics
+
+CREATE TABLE IF NOT EXISTS skipped_captures (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    reason TEXT NOT NULL CHECK (reason IN ('sensitive')),
+    source_app TEXT,
+    skipped_at INTEGER NOT NULL              -- unix timestamp (seconds)
+);
+
+CREATE INDEX IF NOT EXISTS idx_skipped_captures_time ON skipped_captures(skipped_at);
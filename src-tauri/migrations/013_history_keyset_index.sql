@@ -0,0 +1,5 @@
+-- Keyset pagination: matches the history order (favorites first, newest first)
+
+CREATE INDEX IF NOT EXISTS idx_history_order
+    ON clipboard_items(is_favorite DESC, copied_at DESC, id DESC)
+    WHERE deleted_at IS NULL;
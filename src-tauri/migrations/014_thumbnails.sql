@@ -0,0 +1,4 @@
+-- Cached thumbnails for image items (files under images/thumbs)
+
+ALTER TABLE clipboard_items ADD COLUMN thumb_small_path TEXT;
+ALTER TABLE clipboard_items ADD COLUMN thumb_medium_path TEXT;
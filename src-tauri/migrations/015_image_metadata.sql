@@ -0,0 +1,12 @@
+-- How each image item was stored (possibly downscaled or recompressed) and
+-- the dimensions it was copied at
+
+CREATE TABLE IF NOT EXISTS image_metadata (
+    item_id INTEGER PRIMARY KEY REFERENCES clipboard_items(id) ON DELETE CASCADE,
+    original_width INTEGER NOT NULL,
+    original_height INTEGER NOT NULL,
+    width INTEGER NOT NULL,
+    height INTEGER NOT NULL,
+    format TEXT NOT NULL CHECK (format IN ('png', 'webp', 'jpeg')),
+    byte_size INTEGER NOT NULL
+);
@@ -0,0 +1,6 @@
+-- Perceptual (difference) hash of image items, for near-duplicate detection
+
+ALTER TABLE clipboard_items ADD COLUMN image_dhash INTEGER;
+ALTER TABLE clipboard_items ADD COLUMN near_duplicate_of INTEGER REFERENCES clipboard_items(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_items_image_dhash ON clipboard_items(image_dhash) WHERE image_dhash IS NOT NULL;
@@ -0,0 +1,22 @@
+-- Snippet expansions are pastes too: a usage event belongs to an item or a snippet.
+-- SQLite cannot drop NOT NULL in place, so the table is rebuilt.
+
+CREATE TABLE usage_events_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    item_id INTEGER REFERENCES clipboard_items(id) ON DELETE CASCADE,
+    snippet_id INTEGER REFERENCES snippets(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL CHECK (kind IN ('capture', 'paste')),
+    app TEXT,                                -- source app for captures, target app for pastes
+    occurred_at INTEGER NOT NULL,            -- unix timestamp (seconds)
+    CHECK ((item_id IS NULL) <> (snippet_id IS NULL))
+);
+
+INSERT INTO usage_events_new (id, item_id, kind, app, occurred_at)
+    SELECT id, item_id, kind, app, occurred_at FROM usage_events;
+
+DROP TABLE usage_events;
+ALTER TABLE usage_events_new RENAME TO usage_events;
+
+CREATE INDEX IF NOT EXISTS idx_usage_events_item ON usage_events(item_id, occurred_at DESC);
+CREATE INDEX IF NOT EXISTS idx_usage_events_snippet ON usage_events(snippet_id, occurred_at DESC) WHERE snippet_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_usage_events_time ON usage_events(occurred_at);
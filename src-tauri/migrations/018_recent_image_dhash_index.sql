@@ -0,0 +1,5 @@
+-- Near-duplicate detection compares a new image with the most recent hashed images
+
+CREATE INDEX IF NOT EXISTS idx_items_recent_dhash
+    ON clipboard_items(copied_at DESC, id DESC)
+    WHERE image_dhash IS NOT NULL AND deleted_at IS NULL;
@@ -0,0 +1,3 @@
+-- Context ordering reads the usage events of the app the window was opened from
+
+CREATE INDEX IF NOT EXISTS idx_usage_events_app ON usage_events(app, kind, item_id);
@@ -0,0 +1,4 @@
+-- Near-duplicate lookups scan recent hashed images (idx_items_recent_dhash);
+-- nothing filters on the hash value itself
+
+DROP INDEX IF EXISTS idx_items_image_dhash;
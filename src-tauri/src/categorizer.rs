@@ -29,6 +29,11 @@ static CODE_KEYWORDS: &[&str] = &[
     "function ", "async ", "await ", "return ", "if (", "for ("
 ];
 
+/// Every category `detect_category` can return
+pub static CATEGORIES: &[&str] = &[
+    "url", "email", "error", "code", "command", "ip", "path", "misc"
+];
+
 pub fn detect_category(content: &str) -> String {
     // Priority order: URL > Email > IP > Path > Command > Error > Code > Misc
 
@@ -1,8 +1,15 @@
 use crate::categorizer::detect_category;
+use crate::models::{
+    ImageMetadata, OversizedImageAction, OversizedImagePolicy, RecompressFormat, StoredImageFormat,
+};
 use crate::platform::get_frontmost_app;
+use crate::qr;
 use crate::sensitive::is_sensitive;
+use crate::thumbnails::{self, ThumbnailPaths};
 use arboard::Clipboard;
-use image::DynamicImage;
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::{DynamicImage, RgbaImage};
 use sha2::{Digest, Sha256};
 use std::path::Path;
 use std::sync::mpsc::{Sender, channel};
@@ -12,10 +19,10 @@ use std::time::Duration;
 
 const IMAGE_HASH_PREFIX_LEN: usize = 12;
 
-fn build_image_filename(timestamp_nanos: i64, hash: &str) -> String {
+fn build_image_filename(timestamp_nanos: i64, hash: &str, format: StoredImageFormat) -> String {
     let hash_prefix_len = std::cmp::min(IMAGE_HASH_PREFIX_LEN, hash.len());
     let hash_prefix = &hash[..hash_prefix_len];
-    format!("{}_{}.png", timestamp_nanos, hash_prefix)
+    format!("{}_{}.{}", timestamp_nanos, hash_prefix, format.extension())
 }
 
 fn encode_rgba_to_png(bytes: &[u8], width: usize, height: usize) -> Option<Vec<u8>> {
@@ -38,11 +45,124 @@ fn encode_rgba_to_png(bytes: &[u8], width: usize, height: usize) -> Option<Vec<u
     Some(png_bytes)
 }
 
+/// Image bytes as they will be written to disk
+#[derive(Debug)]
+struct EncodedImage {
+    bytes: Vec<u8>,
+    format: StoredImageFormat,
+    width: u32,
+    height: u32,
+}
+
+fn encode_lossy(image: &RgbaImage, format: RecompressFormat, quality: u8) -> Option<Vec<u8>> {
+    match format {
+        RecompressFormat::Webp => webp::Encoder::from_rgba(image.as_raw(), image.width(), image.height())
+            .encode_simple(false, f32::from(quality))
+            .ok()
+            .map(|encoded| encoded.to_vec()),
+        RecompressFormat::Jpeg => {
+            // JPEG has no alpha channel
+            let rgb = DynamicImage::ImageRgba8(image.clone()).to_rgb8();
+            let mut bytes = Vec::new();
+            JpegEncoder::new_with_quality(&mut bytes, quality)
+                .encode_image(&rgb)
+                .ok()?;
+            Some(bytes)
+        }
+    }
+}
+
+/// Keep a captured image within `max_bytes` of encoded output. The PNG is
+/// used as is when it fits; otherwise `policy` decides whether the image is
+/// skipped, downscaled or recompressed. Err carries the reason it was dropped.
+fn fit_image_to_limit(
+    png: Vec<u8>,
+    image: &RgbaImage,
+    max_bytes: usize,
+    policy: &OversizedImagePolicy,
+) -> std::result::Result<EncodedImage, String> {
+    let (width, height) = image.dimensions();
+    if png.len() <= max_bytes {
+        return Ok(EncodedImage { bytes: png, format: StoredImageFormat::Png, width, height });
+    }
+
+    let too_large = |len: usize| format!("{:.2}MB exceeds limit", len as f64 / (1024.0 * 1024.0));
+    let encoded = match policy.action {
+        OversizedImageAction::Skip => return Err(too_large(png.len())),
+        OversizedImageAction::Downscale => {
+            if width <= policy.max_dimension && height <= policy.max_dimension {
+                return Err(format!("{} and already within {}px", too_large(png.len()), policy.max_dimension));
+            }
+            let scaled = DynamicImage::ImageRgba8(image.clone())
+                .resize(policy.max_dimension, policy.max_dimension, FilterType::Triangle)
+                .to_rgba8();
+            let bytes = encode_rgba_to_png(scaled.as_raw(), scaled.width() as usize, scaled.height() as usize)
+                .ok_or_else(|| "failed to encode downscaled PNG".to_string())?;
+            EncodedImage { bytes, format: StoredImageFormat::Png, width: scaled.width(), height: scaled.height() }
+        }
+        OversizedImageAction::Recompress => {
+            let bytes = encode_lossy(image, policy.format, policy.quality)
+                .ok_or_else(|| format!("failed to encode {}", StoredImageFormat::from(policy.format).as_str()))?;
+            EncodedImage { bytes, format: policy.format.into(), width, height }
+        }
+    };
+
+    if encoded.bytes.len() > max_bytes {
+        return Err(format!("{} after {}", too_large(encoded.bytes.len()), encoded.format.as_str()));
+    }
+    Ok(encoded)
+}
+
+/// 64-bit difference hash: each bit says whether a pixel of a 9×8 grayscale
+/// copy is brighter than its right neighbour. Survives small edits, rescaling
+/// and color profile changes that alter every byte of the RGBA data.
+pub fn dhash(image: &DynamicImage) -> u64 {
+    let small = image.resize_exact(9, 8, FilterType::Triangle).to_luma8();
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            hash <<= 1;
+            if small.get_pixel(x, y)[0] > small.get_pixel(x + 1, y)[0] {
+                hash |= 1;
+            }
+        }
+    }
+    hash
+}
+
+/// SHA-256 of text content, as used for dedup and re-capture suppression.
+pub fn compute_text_hash(content: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(content.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// SHA-256 of raw RGBA pixels, as used for image dedup and re-capture suppression.
+pub fn compute_image_hash(rgba: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(rgba);
+    hex::encode(hasher.finalize())
+}
+
+/// Preview shown in the list: first 80 chars, UTF-8 safe.
+pub fn build_preview(text: &str) -> String {
+    if text.chars().count() > 80 {
+        let preview_text: String = text.chars().take(80).collect();
+        format!("{}...", preview_text)
+    } else {
+        text.to_string()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct NewClipboardItem {
     pub content: String,
     pub content_type: String,
     pub image_path: Option<String>,
+    pub thumbnails: Option<ThumbnailPaths>,
+    pub image_metadata: Option<ImageMetadata>,
+    pub image_dhash: Option<u64>,
+    pub decoded_text: Vec<NewClipboardItem>, // QR code contents, stored as items derived from the image
     pub category: String,
     pub source_app: String,
     pub is_sensitive: bool,
@@ -51,19 +171,49 @@ pub struct NewClipboardItem {
     pub copied_at: i64,
 }
 
+impl NewClipboardItem {
+    /// A text capture, categorized
+    pub fn text(text: String, source_app: String, is_sensitive: bool, hash: String) -> Self {
+        NewClipboardItem {
+            category: detect_category(&text),
+            preview: build_preview(&text),
+            content: text,
+            content_type: "text".to_string(),
+            image_path: None,
+            thumbnails: None,
+            image_metadata: None,
+            image_dhash: None,
+            decoded_text: Vec::new(),
+            source_app,
+            is_sensitive,
+            hash,
+            copied_at: chrono::Utc::now().timestamp(),
+        }
+    }
+}
+
+/// What the monitor reports to the main thread
+#[derive(Debug, Clone)]
+pub enum MonitorEvent {
+    Captured(Box<NewClipboardItem>),
+    /// Sensitive content dropped because auto-exclusion is on; nothing of it is kept
+    SkippedSensitive { source_app: String, skipped_at: i64 },
+}
+
 #[derive(Clone)]
 pub struct ClipboardMonitor {
     last_hash: Arc<Mutex<Option<String>>>,
     last_copied_hash: Arc<Mutex<Option<String>>>, // For preventing re-capture loop
-    sender: Arc<Mutex<Option<Sender<NewClipboardItem>>>>, // Wrapped for Clone
+    sender: Arc<Mutex<Option<Sender<MonitorEvent>>>>, // Wrapped for Clone
     auto_exclude_sensitive: Arc<Mutex<bool>>,
     exclusions: Arc<Mutex<Vec<String>>>,
     max_image_size_mb: Arc<Mutex<u32>>,
+    oversized_images: Arc<Mutex<OversizedImagePolicy>>,
     images_dir: Arc<std::path::PathBuf>,
 }
 
 impl ClipboardMonitor {
-    pub fn new(app_data_dir: &Path) -> (Self, std::sync::mpsc::Receiver<NewClipboardItem>) {
+    pub fn new(app_data_dir: &Path) -> (Self, std::sync::mpsc::Receiver<MonitorEvent>) {
         let (sender, receiver) = channel();
         let images_dir = app_data_dir.join("images");
 
@@ -78,6 +228,7 @@ impl ClipboardMonitor {
                 auto_exclude_sensitive: Arc::new(Mutex::new(true)),
                 exclusions: Arc::new(Mutex::new(Vec::new())),
                 max_image_size_mb: Arc::new(Mutex::new(5)),
+                oversized_images: Arc::new(Mutex::new(OversizedImagePolicy::default())),
                 images_dir: Arc::new(images_dir),
             },
             receiver,
@@ -89,6 +240,11 @@ impl ClipboardMonitor {
         *self.last_copied_hash.lock().unwrap() = Some(hash);
     }
 
+    /// Hash of the content last placed on the clipboard by the app itself
+    pub fn last_copied_hash(&self) -> Option<String> {
+        self.last_copied_hash.lock().unwrap().clone()
+    }
+
     /// Update auto exclude sensitive setting
     pub fn set_auto_exclude_sensitive(&self, enabled: bool) {
         *self.auto_exclude_sensitive.lock().unwrap() = enabled;
@@ -104,6 +260,160 @@ impl ClipboardMonitor {
         *self.max_image_size_mb.lock().unwrap() = size_mb;
     }
 
+    /// Update what happens to images over the size limit
+    pub fn set_oversized_image_policy(&self, policy: OversizedImagePolicy) {
+        *self.oversized_images.lock().unwrap() = policy;
+    }
+
+    /// Tell the main thread sensitive content was dropped. False if the channel is closed.
+    fn report_skipped_sensitive(&self, source_app: String) -> bool {
+        let event = MonitorEvent::SkippedSensitive {
+            source_app,
+            skipped_at: chrono::Utc::now().timestamp(),
+        };
+        if let Some(sender) = self.sender.lock().unwrap().as_ref() {
+            if sender.send(event).is_err() {
+                log::error!("Failed to send skipped capture to main thread");
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Text items for QR codes in a captured image, and whether any of them is
+    /// sensitive. Each goes through the same check as copied text; None when a
+    /// sensitive code was found with auto-exclusion on, so the image is dropped
+    /// along with it and counted as a skipped capture.
+    fn decode_image_text(&self, image: &RgbaImage, source_app: &str) -> Option<(Vec<NewClipboardItem>, bool)> {
+        let auto_exclude = *self.auto_exclude_sensitive.lock().unwrap();
+        let mut items = Vec::new();
+        let mut any_sensitive = false;
+        for text in qr::decode_qr_codes(&image::imageops::grayscale(image)) {
+            let is_sens = is_sensitive(&text);
+            if is_sens && auto_exclude {
+                log::warn!("Skipping image with sensitive QR code content");
+                self.report_skipped_sensitive(source_app.to_string());
+                return None;
+            }
+            any_sensitive |= is_sens;
+            let hash = compute_text_hash(&text);
+            items.push(NewClipboardItem::text(text, source_app.to_string(), is_sens, hash));
+        }
+        Some((items, any_sensitive))
+    }
+
+    /// Encode, store and describe a captured image. None when it is skipped:
+    /// invalid data, too large for the policy, or a sensitive QR code.
+    fn capture_image(
+        &self,
+        image_data: &arboard::ImageData,
+        hash: String,
+        source_app: String,
+    ) -> Option<NewClipboardItem> {
+        let (png_bytes, rgba) = match encode_rgba_to_png(
+            &image_data.bytes,
+            image_data.width,
+            image_data.height,
+        )
+        .zip(RgbaImage::from_raw(
+            image_data.width as u32,
+            image_data.height as u32,
+            image_data.bytes.to_vec(),
+        )) {
+            Some(encoded) => encoded,
+            None => {
+                log::warn!(
+                    "Skipping image with invalid dimensions/bytes: {}x{}, {} bytes",
+                    image_data.width,
+                    image_data.height,
+                    image_data.bytes.len()
+                );
+                return None;
+            }
+        };
+
+        // Check the encoded size against the limit
+        let max_size_mb = *self.max_image_size_mb.lock().unwrap();
+        let policy = self.oversized_images.lock().unwrap().clone();
+        let max_bytes = max_size_mb as usize * 1024 * 1024;
+        let encoded = match fit_image_to_limit(png_bytes, &rgba, max_bytes, &policy) {
+            Ok(encoded) => encoded,
+            Err(reason) => {
+                log::warn!("Skipping image ({}: {}MB)", reason, max_size_mb);
+                return None;
+            }
+        };
+
+        // A secret in a QR code makes the whole screenshot sensitive. Decoded
+        // only once the image is known to be kept.
+        let (decoded_text, is_sens) = self.decode_image_text(&rgba, &source_app)?;
+
+        // Save image to disk
+        let timestamp_nanos = chrono::Utc::now()
+            .timestamp_nanos_opt()
+            .unwrap_or_else(|| chrono::Utc::now().timestamp_micros() * 1000);
+        let filename = build_image_filename(timestamp_nanos, &hash, encoded.format);
+        let image_path = self.images_dir.join(&filename);
+
+        if let Err(e) = std::fs::write(&image_path, &encoded.bytes) {
+            log::error!("Failed to save image: {}", e);
+            return None;
+        }
+
+        let stored_path = image_path
+            .canonicalize()
+            .unwrap_or(image_path.clone())
+            .to_string_lossy()
+            .to_string();
+
+        let rgba = DynamicImage::ImageRgba8(rgba);
+        let image_dhash = dhash(&rgba);
+
+        // Thumbnails are an optimization; the item is kept without them on failure
+        let thumbnails = thumbnails::generate(
+            &rgba,
+            &image_path,
+            &thumbnails::thumbs_dir(&self.images_dir),
+        )
+        .map_err(|e| log::warn!("Failed to generate thumbnails: {}", e))
+        .ok();
+
+        let image_metadata = ImageMetadata {
+            original_width: image_data.width as u32,
+            original_height: image_data.height as u32,
+            width: encoded.width,
+            height: encoded.height,
+            format: encoded.format,
+            byte_size: encoded.bytes.len() as u64,
+        };
+
+        // Generate preview text with dimensions
+        // Note: arboard may not provide dimensions for all formats
+        let width = image_data.width;
+        let height = image_data.height;
+        let preview = if width > 0 && height > 0 {
+            format!("Image {}×{}", width, height)
+        } else {
+            "Image".to_string()
+        };
+
+        Some(NewClipboardItem {
+            content: preview.clone(), // Store dimensions as content
+            content_type: "image".to_string(),
+            image_path: Some(stored_path),
+            thumbnails,
+            image_metadata: Some(image_metadata),
+            image_dhash: Some(image_dhash),
+            decoded_text,
+            category: "misc".to_string(), // Images don't get categorized
+            source_app,
+            is_sensitive: is_sens,
+            hash,
+            preview,
+            copied_at: chrono::Utc::now().timestamp(),
+        })
+    }
+
     /// Get the canonical image storage directory used by the monitor.
     pub fn images_dir(&self) -> std::path::PathBuf {
         self.images_dir.as_ref().clone()
@@ -123,7 +433,7 @@ impl ClipboardMonitor {
 
                 // Try to read text content
                 if let Ok(text) = clipboard.get_text() {
-                    let hash = monitor_clone.compute_hash(&text);
+                    let hash = compute_text_hash(&text);
 
                     // Check if this is new content
                     let last_hash = monitor_clone.last_hash.lock().unwrap().clone();
@@ -150,35 +460,17 @@ impl ClipboardMonitor {
                     let is_sens = is_sensitive(&text);
                     if is_sens && *monitor_clone.auto_exclude_sensitive.lock().unwrap() {
                         log::warn!("Skipping sensitive clipboard content");
+                        if !monitor_clone.report_skipped_sensitive(source_app) {
+                            break;
+                        }
                         continue;
                     }
 
-                    // Categorize
-                    let category = detect_category(&text);
-
-                    // Generate preview (first 80 chars, UTF-8 safe)
-                    let preview = if text.chars().count() > 80 {
-                        let preview_text: String = text.chars().take(80).collect();
-                        format!("{}...", preview_text)
-                    } else {
-                        text.clone()
-                    };
-
-                    let item = NewClipboardItem {
-                        content: text,
-                        content_type: "text".to_string(),
-                        image_path: None,
-                        category,
-                        source_app,
-                        is_sensitive: is_sens,
-                        hash,
-                        preview,
-                        copied_at: chrono::Utc::now().timestamp(),
-                    };
+                    let item = NewClipboardItem::text(text, source_app, is_sens, hash);
 
                     // Send item through the channel
                     if let Some(sender) = monitor_clone.sender.lock().unwrap().as_ref() {
-                        if sender.send(item).is_err() {
+                        if sender.send(MonitorEvent::Captured(Box::new(item))).is_err() {
                             log::error!("Failed to send clipboard item to main thread");
                             break;
                         }
@@ -187,11 +479,7 @@ impl ClipboardMonitor {
 
                 // Handle image clipboard content
                 if let Ok(image_data) = clipboard.get_image() {
-                    let hash = {
-                        let mut hasher = Sha256::new();
-                        hasher.update(&image_data.bytes);
-                        hex::encode(hasher.finalize())
-                    };
+                    let hash = compute_image_hash(&image_data.bytes);
 
                     // Check if this is new content
                     let last_hash = monitor_clone.last_hash.lock().unwrap().clone();
@@ -201,19 +489,7 @@ impl ClipboardMonitor {
                         continue;
                     }
 
-                    // Check image size against limit
-                    let image_size_mb = image_data.bytes.len() as f64 / (1024.0 * 1024.0);
-                    let max_size_mb = *monitor_clone.max_image_size_mb.lock().unwrap();
-
-                    if image_size_mb > max_size_mb as f64 {
-                        log::warn!(
-                            "Skipping image (size: {:.2}MB exceeds limit: {}MB)",
-                            image_size_mb, max_size_mb
-                        );
-                        continue;
-                    }
-
-                    // Update last hash
+                    // Update last hash, so an oversized image is only encoded once
                     *monitor_clone.last_hash.lock().unwrap() = Some(hash.clone());
 
                     // Get source app
@@ -225,65 +501,12 @@ impl ClipboardMonitor {
                         continue;
                     }
 
-                    // Save image to disk
-                    let timestamp_nanos = chrono::Utc::now()
-                        .timestamp_nanos_opt()
-                        .unwrap_or_else(|| chrono::Utc::now().timestamp_micros() * 1000);
-                    let filename = build_image_filename(timestamp_nanos, &hash);
-                    let image_path = monitor_clone.images_dir.join(&filename);
-
-                    let png_bytes = match encode_rgba_to_png(
-                        &image_data.bytes,
-                        image_data.width,
-                        image_data.height,
-                    ) {
-                        Some(bytes) => bytes,
-                        None => {
-                            log::warn!(
-                                "Skipping image with invalid dimensions/bytes: {}x{}, {} bytes",
-                                image_data.width,
-                                image_data.height,
-                                image_data.bytes.len()
-                            );
-                            continue;
-                        }
-                    };
-
-                    if let Err(e) = std::fs::write(&image_path, &png_bytes) {
-                        log::error!("Failed to save image PNG: {}", e);
+                    let Some(item) = monitor_clone.capture_image(&image_data, hash, source_app) else {
                         continue;
-                    }
-
-                    let stored_path = image_path
-                        .canonicalize()
-                        .unwrap_or(image_path.clone())
-                        .to_string_lossy()
-                        .to_string();
-
-                    // Generate preview text with dimensions
-                    // Note: arboard may not provide dimensions for all formats
-                    let width = image_data.width;
-                    let height = image_data.height;
-                    let preview = if width > 0 && height > 0 {
-                        format!("Image {}×{}", width, height)
-                    } else {
-                        "Image".to_string()
-                    };
-
-                    let item = NewClipboardItem {
-                        content: preview.clone(), // Store dimensions as content
-                        content_type: "image".to_string(),
-                        image_path: Some(stored_path),
-                        category: "misc".to_string(), // Images don't get categorized
-                        source_app,
-                        is_sensitive: false,
-                        hash,
-                        preview,
-                        copied_at: chrono::Utc::now().timestamp(),
                     };
 
                     if let Some(sender) = monitor_clone.sender.lock().unwrap().as_ref() {
-                        if sender.send(item).is_err() {
+                        if sender.send(MonitorEvent::Captured(Box::new(item))).is_err() {
                             log::error!("Failed to send clipboard image to main thread");
                             break;
                         }
@@ -294,22 +517,68 @@ impl ClipboardMonitor {
             log::warn!("Clipboard monitor stopped");
         });
     }
-
-    fn compute_hash(&self, content: &str) -> String {
-        let mut hasher = Sha256::new();
-        hasher.update(content.as_bytes());
-        hex::encode(hasher.finalize())
-    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{build_image_filename, encode_rgba_to_png};
+    use super::{
+        build_image_filename, build_preview, compute_image_hash, dhash, encode_rgba_to_png,
+        fit_image_to_limit, ClipboardMonitor, MonitorEvent,
+    };
+    use crate::models::{
+        OversizedImageAction, OversizedImagePolicy, RecompressFormat, StoredImageFormat,
+    };
 
     #[test]
     fn test_build_image_filename_uses_hash_prefix() {
-        let filename = build_image_filename(1_700_000_000_000_000_000, "abcdef1234567890");
+        let filename = build_image_filename(1_700_000_000_000_000_000, "abcdef1234567890", StoredImageFormat::Png);
         assert_eq!(filename, "1700000000000000000_abcdef123456.png");
+        let filename = build_image_filename(1, "abcdef1234567890", StoredImageFormat::Jpeg);
+        assert_eq!(filename, "1_abcdef123456.jpg");
+    }
+
+    /// Deterministic noise, so PNG cannot compress it much
+    fn noisy_image(width: u32, height: u32) -> image::RgbaImage {
+        let mut state = 0x2545_f491_u32;
+        image::RgbaImage::from_fn(width, height, |_, _| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            let [r, g, b, _] = state.to_le_bytes();
+            image::Rgba([r, g, b, 255])
+        })
+    }
+
+    #[test]
+    fn test_fit_image_to_limit_applies_policy_to_encoded_size() {
+        let image = noisy_image(400, 200);
+        let png = encode_rgba_to_png(image.as_raw(), 400, 200).expect("png");
+        let policy = |action| OversizedImagePolicy { action, max_dimension: 100, ..Default::default() };
+
+        let kept = fit_image_to_limit(png.clone(), &image, png.len(), &policy(OversizedImageAction::Skip))
+            .expect("fits");
+        assert_eq!((kept.format, kept.width, kept.height), (StoredImageFormat::Png, 400, 200));
+
+        let limit = png.len() / 2;
+        assert!(fit_image_to_limit(png.clone(), &image, limit, &policy(OversizedImageAction::Skip)).is_err());
+
+        let scaled = fit_image_to_limit(png.clone(), &image, limit, &policy(OversizedImageAction::Downscale))
+            .expect("downscaled");
+        assert_eq!((scaled.format, scaled.width, scaled.height), (StoredImageFormat::Png, 100, 50));
+        assert!(scaled.bytes.len() <= limit);
+
+        for (format, stored) in [(RecompressFormat::Jpeg, StoredImageFormat::Jpeg), (RecompressFormat::Webp, StoredImageFormat::Webp)] {
+            let recompress = OversizedImagePolicy { format, quality: 50, ..policy(OversizedImageAction::Recompress) };
+            let encoded = fit_image_to_limit(png.clone(), &image, limit, &recompress).expect("recompressed");
+            assert_eq!((encoded.format, encoded.width, encoded.height), (stored, 400, 200));
+            assert!(encoded.bytes.len() <= limit);
+            let decoded = image::load_from_memory(&encoded.bytes).expect("decodable");
+            assert_eq!((decoded.width(), decoded.height()), (400, 200));
+        }
+
+        // The limit applies to what would be written, not to the raw pixels
+        let tiny = fit_image_to_limit(png.clone(), &image, 10, &policy(OversizedImageAction::Downscale));
+        assert!(tiny.is_err());
     }
 
     #[test]
@@ -335,4 +604,75 @@ mod tests {
         let invalid = vec![0_u8; 3];
         assert!(encode_rgba_to_png(&invalid, 1, 1).is_none());
     }
+
+    #[test]
+    fn test_dhash_tolerates_small_changes_but_not_different_images() {
+        let gradient = |shift: u8| {
+            image::RgbaImage::from_fn(320, 200, |x, y| {
+                let v = ((x + y) % 256) as u8;
+                image::Rgba([v.saturating_add(shift), v, 255 - v, 255])
+            })
+        };
+        let original = gradient(0);
+        let mut edited = original.clone();
+        edited.put_pixel(10, 10, image::Rgba([0, 0, 0, 255]));
+        let tinted = gradient(12);
+        let other = image::DynamicImage::ImageRgba8(noisy_image(320, 200));
+
+        let hash = dhash(&image::DynamicImage::ImageRgba8(original.clone()));
+        let distance = |image: &image::DynamicImage| (hash ^ dhash(image)).count_ones();
+        assert_eq!(distance(&image::DynamicImage::ImageRgba8(edited)), 0);
+        assert!(distance(&image::DynamicImage::ImageRgba8(tinted)) <= 2);
+        assert!(distance(&image::DynamicImage::ImageRgba8(original).resize(160, 100, image::imageops::FilterType::Triangle)) <= 2);
+        assert!(distance(&other) > 10);
+    }
+
+    #[test]
+    fn test_build_preview_truncates_on_char_boundary() {
+        assert_eq!(build_preview("short"), "short");
+
+        let long = "é".repeat(81);
+        let preview = build_preview(&long);
+        assert_eq!(preview.chars().count(), 83);
+        assert!(preview.ends_with("..."));
+    }
+
+    /// A white screenshot with a QR code of `data` in its corner
+    fn qr_screenshot(data: &str) -> arboard::ImageData<'static> {
+        let code = qrcode::QrCode::new(data).expect("qr");
+        let width = code.width() as u32;
+        let colors = code.to_colors();
+        let module = |offset: u32| offset.checked_sub(20).map(|o| o / 4).filter(|m| *m < width);
+        let image = image::RgbaImage::from_fn(320, 240, |x, y| match (module(x), module(y)) {
+            (Some(mx), Some(my)) if colors[(my * width + mx) as usize] == qrcode::Color::Dark => {
+                image::Rgba([0, 0, 0, 255])
+            }
+            _ => image::Rgba([255, 255, 255, 255]),
+        });
+        arboard::ImageData { width: 320, height: 240, bytes: image.into_raw().into() }
+    }
+
+    #[test]
+    fn test_image_with_a_secret_qr_code_is_sensitive_or_skipped() {
+        let temp_dir = tempfile::tempdir().expect("tempdir");
+        let (monitor, events) = ClipboardMonitor::new(temp_dir.path());
+        let screenshot = qr_screenshot("otpauth://totp/Example:alice?secret=JBSWY3DPEHPK3PXP&issuer=Example");
+        let hash = compute_image_hash(&screenshot.bytes);
+
+        // Auto-exclusion drops the image with its secret and counts the skip
+        monitor.set_auto_exclude_sensitive(true);
+        assert!(monitor.capture_image(&screenshot, hash.clone(), "Browser".to_string()).is_none());
+        assert!(matches!(
+            events.try_recv(),
+            Ok(MonitorEvent::SkippedSensitive { source_app, .. }) if source_app == "Browser"
+        ));
+        assert_eq!(std::fs::read_dir(monitor.images_dir()).expect("images dir").count(), 0, "nothing is written");
+
+        // Otherwise the image is kept but flagged like the secret itself
+        monitor.set_auto_exclude_sensitive(false);
+        let item = monitor.capture_image(&screenshot, hash, "Browser".to_string()).expect("captured");
+        assert!(item.is_sensitive);
+        assert_eq!(item.decoded_text.len(), 1);
+        assert!(item.decoded_text[0].is_sensitive);
+    }
 }
@@ -1,10 +1,452 @@
 use crate::error::{AppError, Result};
-use crate::models::{ClipboardItem, SearchFilters, Settings};
+use crate::fuzzy::fuzzy_score;
+use crate::models::{
+    BulkAction, BulkSelection, ClipboardItem, ClipboardListItem, ClipboardStats, Collection, ContentChunk,
+    ContextCursor, ContextReason, HistoryEntry, ImageMetadata, ItemRevision, KeyCount, MatchRange,
+    PageCursor, PageRequest, RankingWeights, SearchFilters, SearchHit, SearchMode, SearchSort,
+    Settings, SimilarImage, Snippet, SnippetInput, StatsBucket, StoredImageFormat, TimeCount,
+    TransformChain, UsageEvent,
+};
+use crate::query::{self, ParsedQuery};
+use crate::thumbnails::ThumbnailPaths;
 use rusqlite::{Connection, params};
 use std::path::Path;
 use std::sync::Mutex;
 use std::time::Duration;
 
+/// Rows that retention and max_items cleanup may delete: not favorited and not in any collection
+const CLEANUP_ELIGIBLE: &str =
+    "is_favorite = 0 AND id NOT IN (SELECT item_id FROM collection_items)";
+
+/// Column list matching `row_to_item`, for `clipboard_items` aliased as `ci`
+const ITEM_COLUMNS: &str = "ci.id, ci.content, ci.content_type, ci.image_path, ci.category, ci.source_app, ci.preview, ci.copied_at, ci.is_favorite, ci.is_sensitive, ci.hash, ci.title, ci.note, ci.deleted_at, ci.use_count, ci.last_used_at";
+
+/// Index of the first column selected after `ITEM_COLUMNS`
+fn item_columns_end() -> usize {
+    ITEM_COLUMNS.split(", ").count()
+}
+
+/// Column list matching `row_to_list_item`: `ITEM_COLUMNS` with the content
+/// replaced by its length in characters and its line count, so lists never
+/// read megabytes of content into memory
+const LIST_COLUMNS: &str = "ci.id, LENGTH(ci.content), LENGTH(ci.content) - LENGTH(REPLACE(ci.content, char(10), '')) + 1, ci.content_type, ci.image_path, ci.category, ci.source_app, ci.preview, ci.copied_at, ci.is_favorite, ci.is_sensitive, ci.hash, ci.title, ci.note, ci.deleted_at, ci.use_count, ci.last_used_at";
+
+/// Index of the first column selected after `LIST_COLUMNS`
+fn list_columns_end() -> usize {
+    item_columns_end() + 1
+}
+
+fn row_to_item(row: &rusqlite::Row) -> rusqlite::Result<ClipboardItem> {
+    Ok(ClipboardItem {
+        id: row.get(0)?,
+        content: row.get(1)?,
+        content_type: row.get(2)?,
+        image_path: row.get(3)?,
+        category: row.get(4)?,
+        source_app: row.get(5)?,
+        preview: row.get(6)?,
+        copied_at: row.get(7)?,
+        is_favorite: row.get::<_, i32>(8)? != 0,
+        is_sensitive: row.get::<_, i32>(9)? != 0,
+        hash: row.get(10)?,
+        title: row.get(11)?,
+        note: row.get(12)?,
+        deleted_at: row.get(13)?,
+        use_count: row.get(14)?,
+        last_used_at: row.get(15)?,
+    })
+}
+
+fn row_to_list_item(row: &rusqlite::Row) -> rusqlite::Result<ClipboardListItem> {
+    Ok(ClipboardListItem {
+        id: row.get(0)?,
+        content_length: row.get::<_, i64>(1)? as usize,
+        line_count: row.get::<_, i64>(2)? as usize,
+        content_type: row.get(3)?,
+        image_path: row.get(4)?,
+        category: row.get(5)?,
+        source_app: row.get(6)?,
+        preview: row.get(7)?,
+        copied_at: row.get(8)?,
+        is_favorite: row.get::<_, i32>(9)? != 0,
+        is_sensitive: row.get::<_, i32>(10)? != 0,
+        hash: row.get(11)?,
+        title: row.get(12)?,
+        note: row.get(13)?,
+        deleted_at: row.get(14)?,
+        use_count: row.get(15)?,
+        last_used_at: row.get(16)?,
+    })
+}
+
+const SNIPPET_COLUMNS: &str =
+    "id, name, abbreviation, folder, content, tags, pack_path, created_at, updated_at";
+
+fn row_to_snippet(row: &rusqlite::Row) -> rusqlite::Result<Snippet> {
+    let tags_json: String = row.get(5)?;
+    Ok(Snippet {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        abbreviation: row.get(2)?,
+        folder: row.get(3)?,
+        content: row.get(4)?,
+        tags: serde_json::from_str(&tags_json).unwrap_or_default(),
+        pack: row.get(6)?,
+        created_at: row.get(7)?,
+        updated_at: row.get(8)?,
+    })
+}
+
+fn tags_to_json(tags: &[String]) -> String {
+    serde_json::to_string(tags).unwrap_or_else(|_| "[]".to_string())
+}
+
+fn is_constraint_violation(e: &rusqlite::Error) -> bool {
+    matches!(e, rusqlite::Error::SqliteFailure(err, _) if err.code == rusqlite::ErrorCode::ConstraintViolation)
+}
+
+/// Map a UNIQUE violation on `collections.name` to a user-facing error
+fn map_collection_write_error(e: rusqlite::Error) -> AppError {
+    if is_constraint_violation(&e) {
+        AppError::InvalidInput("A collection with that name already exists".to_string())
+    } else {
+        e.into()
+    }
+}
+
+/// Pack snippets are owned by their files; reject edits through the app
+fn ensure_user_snippet(conn: &Connection, id: i64) -> Result<()> {
+    let pack_path: Option<String> = conn
+        .query_row("SELECT pack_path FROM snippets WHERE id = ?1", params![id], |row| row.get(0))
+        .map_err(|_| AppError::NotFound(id))?;
+
+    if pack_path.is_some() {
+        return Err(AppError::InvalidInput(
+            "Snippets from team packs are read-only".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Map a UNIQUE violation on `snippets.abbreviation` to a user-facing error
+fn map_snippet_write_error(e: rusqlite::Error) -> AppError {
+    if is_constraint_violation(&e) {
+        AppError::InvalidInput("Snippet abbreviation is already in use".to_string())
+    } else {
+        e.into()
+    }
+}
+
+type SqlParams = Vec<Box<dyn rusqlite::ToSql>>;
+
+/// Word-level FTS5 index (unicode61 tokenizer)
+const TOKEN_INDEX: &str = "clipboard_fts";
+/// Substring FTS5 index (trigram tokenizer)
+const TRIGRAM_INDEX: &str = "clipboard_trigram";
+/// Rows fetched from the trigram index before checking them for typo matches
+const TYPO_CANDIDATE_LIMIT: u32 = 200;
+/// Approximate number of tokens of context in a search snippet
+const SNIPPET_TOKENS: u32 = 16;
+/// Most recent image hashes a new capture is compared with for near-duplicates
+const NEAR_DUPLICATE_CANDIDATES: u32 = 500;
+
+/// Context ordering boost for items pasted into the app before. Boosts are on the
+/// scale of the recency term, which is 1.0 for a new item and 0.5 a day later.
+const CONTEXT_PASTED_BOOST: f64 = 1.0;
+/// Maximum boost for a category, scaled by its share of the app's usage events
+const CONTEXT_CATEGORY_BOOST: f64 = 0.5;
+/// Context ordering boost for items copied from the app
+const CONTEXT_COPIED_FROM_BOOST: f64 = 0.25;
+/// Most boosted items placed ahead of plain history when the window is opened from an app
+const CONTEXT_SECTION_SIZE: u32 = 10;
+/// Minimum share of an app's usage events for a category to count as favoured
+const CONTEXT_MIN_CATEGORY_SHARE: f64 = 0.25;
+
+/// `FROM ... WHERE ...` selecting visible items for a parsed search (see `query::parse`).
+/// `index` is the FTS5 table and expression to match; `None` applies only filters.
+fn search_clause(parsed: &ParsedQuery, filters: SearchFilters, index: Option<(&str, &str)>) -> (String, SqlParams) {
+    let mut sql = String::from("FROM clipboard_items ci");
+    let mut params: SqlParams = Vec::new();
+
+    match index {
+        Some((table, match_expr)) => {
+            sql.push_str(&format!(
+                " JOIN {table} fts ON ci.id = fts.rowid
+                 WHERE {table} MATCH ? AND ci.is_sensitive = 0 AND ci.deleted_at IS NULL"
+            ));
+            params.push(Box::new(match_expr.to_string()));
+        }
+        None => sql.push_str(" WHERE ci.is_sensitive = 0 AND ci.deleted_at IS NULL"),
+    }
+
+    if let Some(exclude_expr) = &parsed.exclude_expr {
+        sql.push_str(" AND ci.id NOT IN (SELECT rowid FROM clipboard_fts WHERE clipboard_fts MATCH ?)");
+        params.push(Box::new(exclude_expr.clone()));
+    }
+
+    if let Some(category) = filters.category {
+        sql.push_str(" AND ci.category = ?");
+        params.push(Box::new(category));
+    }
+
+    if let Some(source_app) = filters.source_app {
+        sql.push_str(" AND ci.source_app = ?");
+        params.push(Box::new(source_app));
+    }
+
+    if let Some(content_type) = filters.content_type {
+        sql.push_str(" AND ci.content_type = ?");
+        params.push(Box::new(content_type));
+    }
+
+    if let Some(date_from) = filters.date_from {
+        sql.push_str(" AND ci.copied_at >= ?");
+        params.push(Box::new(date_from));
+    }
+
+    if let Some(date_to) = filters.date_to {
+        sql.push_str(" AND ci.copied_at <= ?");
+        params.push(Box::new(date_to));
+    }
+
+    if let Some(collection_id) = filters.collection_id {
+        sql.push_str(" AND ci.id IN (SELECT item_id FROM collection_items WHERE collection_id = ?)");
+        params.push(Box::new(collection_id));
+    }
+
+    if let Some(is_favorite) = filters.is_favorite {
+        sql.push_str(" AND ci.is_favorite = ?");
+        params.push(Box::new(is_favorite));
+    }
+
+    (sql, params)
+}
+
+/// History keyset bound for a page: rows sorting after `after`, or every row
+/// for the first page (`is_favorite` is never above 1)
+fn keyset_bound(after: Option<PageCursor>) -> (i64, i64, i64) {
+    match after {
+        Some(cursor) => (cursor.is_favorite as i64, cursor.copied_at, cursor.id),
+        None => (2, i64::MAX, i64::MAX),
+    }
+}
+
+/// One page of plain history (favorites first, newest first), without `exclude`
+fn query_history(conn: &Connection, page: PageRequest, exclude: &[i64]) -> Result<Vec<ClipboardListItem>> {
+    if page.limit == 0 {
+        return Ok(Vec::new());
+    }
+    let (favorite, copied_at, id) = keyset_bound(page.after);
+    let placeholders = vec!["?"; exclude.len()].join(", ");
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}
+         FROM clipboard_items ci
+         WHERE ci.is_sensitive = 0 AND ci.deleted_at IS NULL
+           AND (ci.is_favorite, ci.copied_at, ci.id) < (?, ?, ?)
+           AND ci.id NOT IN ({})
+         ORDER BY ci.is_favorite DESC, ci.copied_at DESC, ci.id DESC
+         LIMIT ?",
+        LIST_COLUMNS, placeholders
+    ))?;
+
+    let mut params: Vec<&dyn rusqlite::ToSql> = vec![&favorite, &copied_at, &id];
+    params.extend(exclude.iter().map(|id| id as &dyn rusqlite::ToSql));
+    params.push(&page.limit);
+
+    let items = stmt.query_map(params.as_slice(), row_to_list_item)?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(items)
+}
+
+/// Items from the whole history that `app` has used before, or that match
+/// its habits, ordered by boost plus recency, each with the reason it was boosted
+fn query_context_section(conn: &Connection, app: &str, limit: u32) -> Result<Vec<HistoryEntry>> {
+    let mut stmt = conn.prepare(&format!(
+        "WITH app_events AS (
+             SELECT ue.kind, ue.item_id, ci.category
+             FROM usage_events ue JOIN clipboard_items ci ON ci.id = ue.item_id
+             WHERE ue.app = ?1
+         ),
+         affinity AS (
+             SELECT category, COUNT(*) * 1.0 / (SELECT COUNT(*) FROM app_events) AS share
+             FROM app_events GROUP BY category
+         ),
+         scored AS (
+             SELECT ci.id,
+                    ci.id IN (SELECT item_id FROM app_events WHERE kind = 'paste') AS pasted,
+                    ci.source_app = ?1 AS copied_from,
+                    COALESCE(a.share, 0.0) AS share
+             FROM clipboard_items ci
+             LEFT JOIN affinity a ON a.category = ci.category
+             WHERE ci.is_sensitive = 0 AND ci.deleted_at IS NULL
+         )
+         SELECT {cols}, s.pasted, s.copied_from, s.share
+         FROM scored s JOIN clipboard_items ci ON ci.id = s.id
+         WHERE s.pasted OR s.copied_from OR s.share >= {min_share:?}
+         ORDER BY 1.0 / (1.0 + MAX(?2 - ci.copied_at, 0) / 86400.0)
+                    + s.pasted * {pasted} + s.copied_from * {copied} + s.share * {category} DESC,
+                  ci.copied_at DESC, ci.id DESC
+         LIMIT ?3",
+        cols = LIST_COLUMNS,
+        min_share = CONTEXT_MIN_CATEGORY_SHARE,
+        pasted = CONTEXT_PASTED_BOOST,
+        copied = CONTEXT_COPIED_FROM_BOOST,
+        category = CONTEXT_CATEGORY_BOOST,
+    ))?;
+
+    let now = chrono::Utc::now().timestamp();
+    let entries = stmt.query_map(params![app, now, limit], |row| {
+        let item = row_to_list_item(row)?;
+        let extra = list_columns_end();
+        let pasted: bool = row.get(extra)?;
+        let copied_from: bool = row.get(extra + 1)?;
+        let share: f64 = row.get(extra + 2)?;
+
+        // Explain by the largest boost
+        let boosts = [
+            (ContextReason::PastedIntoApp, if pasted { CONTEXT_PASTED_BOOST } else { 0.0 }),
+            (ContextReason::FrequentCategory, if share >= CONTEXT_MIN_CATEGORY_SHARE { share * CONTEXT_CATEGORY_BOOST } else { 0.0 }),
+            (ContextReason::CopiedFromApp, if copied_from { CONTEXT_COPIED_FROM_BOOST } else { 0.0 }),
+        ];
+        let reason = boosts
+            .iter()
+            .filter(|(_, boost)| *boost > 0.0)
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(reason, _)| *reason);
+
+        Ok(HistoryEntry { item, reason })
+    })?
+    .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    Ok(entries)
+}
+
+/// `ORDER BY` expression for a search. `table` is the FTS5 table being matched, if any.
+///
+/// Relevance adds weighted signals: bm25 (negated so higher is better), a
+/// hyperbolic recency decay that halves every `recency_half_life_days`, the
+/// favorite flag, and a saturating usage boost.
+fn order_clause(sort: SearchSort, weights: &RankingWeights, table: Option<&str>) -> String {
+    match sort {
+        SearchSort::Newest => "ci.copied_at DESC, ci.id DESC".to_string(),
+        SearchSort::Oldest => "ci.copied_at ASC, ci.id ASC".to_string(),
+        SearchSort::MostUsed => "ci.use_count DESC, ci.copied_at DESC".to_string(),
+        SearchSort::Relevance => {
+            let text = match table {
+                Some(table) => format!("{:?} * -bm25({})", weights.text, table),
+                None => "0".to_string(),
+            };
+            let half_life_secs = weights.recency_half_life_days * 86400.0;
+            format!(
+                "({text}
+                  + {recency:?} / (1.0 + MAX(0, strftime('%s', 'now') - ci.copied_at) / {half_life:?})
+                  + {favorite:?} * ci.is_favorite
+                  + {usage:?} * ci.use_count / (ci.use_count + 5.0)) DESC, ci.copied_at DESC",
+                recency = weights.recency,
+                half_life = half_life_secs,
+                favorite = weights.favorite,
+                usage = weights.usage,
+            )
+        }
+    }
+}
+
+fn query_matching_items(
+    conn: &Connection,
+    parsed: &ParsedQuery,
+    filters: SearchFilters,
+    index: Option<(&str, &str)>,
+    weights: &RankingWeights,
+    page: PageRequest,
+) -> Result<Vec<SearchHit>> {
+    let sort = filters.sort.unwrap_or_default();
+    let order = order_clause(sort, weights, index.map(|(table, _)| table));
+    let (mut clause, mut params) = search_clause(parsed, filters, index);
+    // Only the chronological sorts accept a cursor (checked in `search`)
+    if let Some(after) = page.after {
+        let comparison = if sort == SearchSort::Oldest { ">" } else { "<" };
+        clause.push_str(&format!(" AND (ci.copied_at, ci.id) {} (?, ?)", comparison));
+        params.push(Box::new(after.copied_at));
+        params.push(Box::new(after.id));
+    }
+    // Highlight markers are control characters, which never collide with copied text
+    let snippet = match index {
+        Some((table, _)) => format!("snippet({}, -1, char(2), char(3), '…', {})", table, SNIPPET_TOKENS),
+        None => "NULL".to_string(),
+    };
+    let sql = format!("SELECT {}, {} {} ORDER BY {} LIMIT ?", ITEM_COLUMNS, snippet, clause, order);
+    params.push(Box::new(page.limit));
+
+    let params_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+    let snippet_column = item_columns_end();
+
+    let mut stmt = conn.prepare(&sql)?;
+    let hits = stmt.query_map(params_refs.as_slice(), |row| {
+        Ok(SearchHit { item: row_to_item(row)?, snippet: row.get(snippet_column)?, matches: Vec::new() })
+    })?
+    .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    Ok(hits)
+}
+
+/// Append hits for items not already present, stopping at `limit`
+fn merge_unique(hits: &mut Vec<SearchHit>, extra: Vec<SearchHit>, limit: u32) {
+    for hit in extra {
+        if hits.len() >= limit as usize {
+            break;
+        }
+        if !hits.iter().any(|existing| existing.item.id == hit.item.id) {
+            hits.push(hit);
+        }
+    }
+}
+
+/// True if the item's content, title or note has a typo match for the query
+fn has_typo_match(item: &ClipboardItem, parsed: &ParsedQuery) -> bool {
+    [Some(&item.content), item.title.as_ref(), item.note.as_ref()]
+        .into_iter()
+        .flatten()
+        .any(|text| parsed.has_typo_match(text))
+}
+
+/// Files stored for an item: the image and its thumbnails
+const IMAGE_FILE_COLUMNS: &str = "image_path, thumb_small_path, thumb_medium_path";
+
+fn row_to_image_files(row: &rusqlite::Row) -> rusqlite::Result<Vec<String>> {
+    Ok((0..3).filter_map(|i| row.get::<_, Option<String>>(i).ok().flatten()).collect())
+}
+
+/// Remove files of deleted items; `reason` names the cleanup in log messages
+fn remove_image_files(files: Vec<Vec<String>>, reason: &str) {
+    for path in files.into_iter().flatten() {
+        if let Err(e) = std::fs::remove_file(&path) {
+            log::warn!("Failed to delete {} image file {}: {}", reason, path, e);
+        }
+    }
+}
+
+/// Copying the same content again moves it back to the top,
+/// and brings it back if it was in the trash
+fn record_recapture(conn: &Connection, id: i64, source_app: &str, copied_at: i64) -> Result<()> {
+    conn.execute(
+        "UPDATE clipboard_items
+         SET copied_at = MAX(copied_at, ?1), last_used_at = ?1, use_count = use_count + 1, deleted_at = NULL
+         WHERE id = ?2",
+        params![copied_at, id],
+    )?;
+    log_usage_event(conn, id, "capture", Some(source_app), copied_at)
+}
+
+fn log_usage_event(conn: &Connection, item_id: i64, kind: &str, app: Option<&str>, at: i64) -> Result<()> {
+    conn.execute(
+        "INSERT INTO usage_events (item_id, kind, app, occurred_at) VALUES (?1, ?2, ?3, ?4)",
+        params![item_id, kind, app, at],
+    )?;
+    Ok(())
+}
+
 pub struct Database {
     conn: Mutex<Connection>,
 }
@@ -22,6 +464,7 @@ impl Database {
         conn.pragma_update(None, "journal_mode", "WAL")?;
         conn.pragma_update(None, "synchronous", "NORMAL")?;
         conn.busy_timeout(Duration::from_millis(5000))?;
+        conn.pragma_update(None, "foreign_keys", "ON")?;
 
         let db = Database {
             conn: Mutex::new(conn),
@@ -43,6 +486,139 @@ impl Database {
             log::info!("Applied migration 001_init.sql");
         }
 
+        if user_version < 2 {
+            let migration_sql = include_str!("../migrations/002_transform_chains.sql");
+            conn.execute_batch(migration_sql)?;
+            conn.execute("PRAGMA user_version = 2", [])?;
+            log::info!("Applied migration 002_transform_chains.sql");
+        }
+
+        if user_version < 3 {
+            let migration_sql = include_str!("../migrations/003_item_derivations.sql");
+            conn.execute_batch(migration_sql)?;
+            conn.execute("PRAGMA user_version = 3", [])?;
+            log::info!("Applied migration 003_item_derivations.sql");
+        }
+
+        if user_version < 4 {
+            let migration_sql = include_str!("../migrations/004_snippets.sql");
+            conn.execute_batch(migration_sql)?;
+            conn.execute("PRAGMA user_version = 4", [])?;
+            log::info!("Applied migration 004_snippets.sql");
+        }
+
+        if user_version < 5 {
+            let migration_sql = include_str!("../migrations/005_snippet_packs.sql");
+            conn.execute_batch(migration_sql)?;
+            conn.execute("PRAGMA user_version = 5", [])?;
+            log::info!("Applied migration 005_snippet_packs.sql");
+        }
+
+        if user_version < 6 {
+            let migration_sql = include_str!("../migrations/006_collections.sql");
+            conn.execute_batch(migration_sql)?;
+            conn.execute("PRAGMA user_version = 6", [])?;
+            log::info!("Applied migration 006_collections.sql");
+        }
+
+        if user_version < 7 {
+            let migration_sql = include_str!("../migrations/007_item_annotations.sql");
+            conn.execute_batch(migration_sql)?;
+            conn.execute("PRAGMA user_version = 7", [])?;
+            log::info!("Applied migration 007_item_annotations.sql");
+        }
+
+        if user_version < 8 {
+            let migration_sql = include_str!("../migrations/008_item_revisions.sql");
+            conn.execute_batch(migration_sql)?;
+            conn.execute("PRAGMA user_version = 8", [])?;
+            log::info!("Applied migration 008_item_revisions.sql");
+        }
+
+        if user_version < 9 {
+            let migration_sql = include_str!("../migrations/009_trash.sql");
+            conn.execute_batch(migration_sql)?;
+            conn.execute("PRAGMA user_version = 9", [])?;
+            log::info!("Applied migration 009_trash.sql");
+        }
+
+        if user_version < 10 {
+            let migration_sql = include_str!("../migrations/010_trigram_index.sql");
+            conn.execute_batch(migration_sql)?;
+            conn.execute("PRAGMA user_version = 10", [])?;
+            log::info!("Applied migration 010_trigram_index.sql");
+        }
+
+        if user_version < 11 {
+            let migration_sql = include_str!("../migrations/011_usage_events.sql");
+            conn.execute_batch(migration_sql)?;
+            conn.execute("PRAGMA user_version = 11", [])?;
+            log::info!("Applied migration 011_usage_events.sql");
+        }
+
+        if user_version < 12 {
+            let migration_sql = include_str!("../migrations/012_skipped_captures.sql");
+            conn.execute_batch(migration_sql)?;
+            conn.execute("PRAGMA user_version = 12", [])?;
+            log::info!("Applied migration 012_skipped_captures.sql");
+        }
+
+        if user_version < 13 {
+            let migration_sql = include_str!("../migrations/013_history_keyset_index.sql");
+            conn.execute_batch(migration_sql)?;
+            conn.execute("PRAGMA user_version = 13", [])?;
+            log::info!("Applied migration 013_history_keyset_index.sql");
+        }
+
+        if user_version < 14 {
+            let migration_sql = include_str!("../migrations/014_thumbnails.sql");
+            conn.execute_batch(migration_sql)?;
+            conn.execute("PRAGMA user_version = 14", [])?;
+            log::info!("Applied migration 014_thumbnails.sql");
+        }
+
+        if user_version < 15 {
+            let migration_sql = include_str!("../migrations/015_image_metadata.sql");
+            conn.execute_batch(migration_sql)?;
+            conn.execute("PRAGMA user_version = 15", [])?;
+            log::info!("Applied migration 015_image_metadata.sql");
+        }
+
+        if user_version < 16 {
+            let migration_sql = include_str!("../migrations/016_image_dhash.sql");
+            conn.execute_batch(migration_sql)?;
+            conn.execute("PRAGMA user_version = 16", [])?;
+            log::info!("Applied migration 016_image_dhash.sql");
+        }
+
+        if user_version < 17 {
+            let migration_sql = include_str!("../migrations/017_snippet_usage_events.sql");
+            conn.execute_batch(migration_sql)?;
+            conn.execute("PRAGMA user_version = 17", [])?;
+            log::info!("Applied migration 017_snippet_usage_events.sql");
+        }
+
+        if user_version < 18 {
+            let migration_sql = include_str!("../migrations/018_recent_image_dhash_index.sql");
+            conn.execute_batch(migration_sql)?;
+            conn.execute("PRAGMA user_version = 18", [])?;
+            log::info!("Applied migration 018_recent_image_dhash_index.sql");
+        }
+
+        if user_version < 19 {
+            let migration_sql = include_str!("../migrations/019_usage_events_app_index.sql");
+            conn.execute_batch(migration_sql)?;
+            conn.execute("PRAGMA user_version = 19", [])?;
+            log::info!("Applied migration 019_usage_events_app_index.sql");
+        }
+
+        if user_version < 20 {
+            let migration_sql = include_str!("../migrations/020_drop_image_dhash_index.sql");
+            conn.execute_batch(migration_sql)?;
+            conn.execute("PRAGMA user_version = 20", [])?;
+            log::info!("Applied migration 020_drop_image_dhash_index.sql");
+        }
+
         Ok(())
     }
 
@@ -63,13 +639,14 @@ impl Database {
 
         // Try to insert; if hash exists, return existing ID
         match conn.execute(
-            "INSERT INTO clipboard_items (content, content_type, image_path, category, source_app, is_sensitive, hash, preview, copied_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            "INSERT INTO clipboard_items (content, content_type, image_path, category, source_app, is_sensitive, hash, preview, copied_at, last_used_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?9)",
             params![content, content_type, image_path, category, source_app, is_sensitive, hash, preview, copied_at],
         ) {
             Ok(_) => {
                 let id = conn.last_insert_rowid();
                 log::debug!("Inserted new clipboard item: id={}, category={}", id, category);
+                log_usage_event(&conn, id, "capture", Some(&source_app), copied_at)?;
 
                 // Check if we exceeded max_items
                 self.cleanup_excess_items_inner(&conn)?;
@@ -83,6 +660,7 @@ impl Database {
                     params![hash],
                     |row| row.get(0),
                 )?;
+                record_recapture(&conn, existing_id, &source_app, copied_at)?;
                 log::debug!("Duplicate item detected (hash exists): id={}", existing_id);
                 Ok(existing_id)
             }
@@ -90,101 +668,334 @@ impl Database {
         }
     }
 
-    /// Get clipboard history with pagination
-    pub fn get_history(&self, limit: u32, offset: u32) -> Result<Vec<ClipboardItem>> {
+    /// Get a page of clipboard history, favorites first and then newest first
+    pub fn get_history(&self, page: PageRequest) -> Result<Vec<ClipboardListItem>> {
         let conn = self.conn.lock().unwrap();
+        query_history(&conn, page, &[])
+    }
 
-        let mut stmt = conn.prepare(
-            "SELECT id, content, content_type, image_path, category, source_app, preview, copied_at, is_favorite, is_sensitive, hash
-             FROM clipboard_items
-             WHERE is_sensitive = 0
-             ORDER BY is_favorite DESC, copied_at DESC
-             LIMIT ?1 OFFSET ?2"
+    /// History ordered for the app the window was opened from. The first page
+    /// starts with up to `CONTEXT_SECTION_SIZE` items from anywhere in history
+    /// that were pasted into `app`, copied from it, or are in a category its
+    /// usage favours, best first. Plain history follows, and it continues on
+    /// later pages without the boosted items (`cursor.boosted`).
+    pub fn get_history_for_app(&self, app: &str, cursor: Option<&ContextCursor>, limit: u32) -> Result<Vec<HistoryEntry>> {
+        let conn = self.conn.lock().unwrap();
+
+        let boosted = match cursor {
+            None => query_context_section(&conn, app, limit.min(CONTEXT_SECTION_SIZE))?,
+            Some(_) => Vec::new(),
+        };
+        let exclude: Vec<i64> = match cursor {
+            Some(cursor) => cursor.boosted.clone(),
+            None => boosted.iter().map(|entry| entry.item.id).collect(),
+        };
+
+        let page = PageRequest {
+            after: cursor.and_then(|cursor| cursor.after),
+            limit: limit - boosted.len() as u32,
+        };
+        let plain = query_history(&conn, page, &exclude)?
+            .into_iter()
+            .map(|item| HistoryEntry { item, reason: None });
+
+        Ok(boosted.into_iter().chain(plain).collect())
+    }
+
+    /// Record a paste-back of an item from history into `target_app`
+    pub fn record_paste(&self, id: i64, target_app: Option<&str>) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        let now = chrono::Utc::now().timestamp();
+
+        let rows = conn.execute(
+            "UPDATE clipboard_items SET use_count = use_count + 1, last_used_at = ?1 WHERE id = ?2",
+            params![now, id],
         )?;
+        if rows == 0 {
+            return Err(AppError::NotFound(id));
+        }
 
-        let items = stmt.query_map(params![limit, offset], |row| {
-            Ok(ClipboardItem {
-                id: row.get(0)?,
-                content: row.get(1)?,
-                content_type: row.get(2)?,
-                image_path: row.get(3)?,
-                category: row.get(4)?,
-                source_app: row.get(5)?,
-                preview: row.get(6)?,
-                copied_at: row.get(7)?,
-                is_favorite: row.get::<_, i32>(8)? != 0,
-                is_sensitive: row.get::<_, i32>(9)? != 0,
-                hash: row.get(10)?,
-            })
+        log_usage_event(&conn, id, "paste", target_app, now)?;
+        Ok(())
+    }
+
+    /// Record that a snippet was expanded onto the clipboard for `target_app`
+    pub fn record_snippet_paste(&self, snippet_id: i64, target_app: Option<&str>) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO usage_events (snippet_id, kind, app, occurred_at) VALUES (?1, 'paste', ?2, ?3)",
+            params![snippet_id, target_app, chrono::Utc::now().timestamp()],
+        )
+        .map_err(|e| if is_constraint_violation(&e) { AppError::NotFound(snippet_id) } else { e.into() })?;
+        Ok(())
+    }
+
+    /// Items reused most often (re-captured or pasted back), most recently used first on ties
+    pub fn get_frequent_items(&self, limit: u32) -> Result<Vec<ClipboardListItem>> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {}
+             FROM clipboard_items ci
+             WHERE ci.is_sensitive = 0 AND ci.deleted_at IS NULL AND ci.use_count > 0
+             ORDER BY ci.use_count DESC, ci.last_used_at DESC
+             LIMIT ?1",
+            LIST_COLUMNS
+        ))?;
+        let items = stmt.query_map(params![limit], row_to_list_item)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(items)
+    }
+
+    /// Count clipboard content the monitor dropped instead of storing
+    pub fn record_skipped_capture(&self, reason: &str, source_app: &str, skipped_at: i64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO skipped_captures (reason, source_app, skipped_at) VALUES (?1, ?2, ?3)",
+            params![reason, source_app, skipped_at],
+        )?;
+        Ok(())
+    }
+
+    /// Aggregate statistics over items in the trash-free history copied within
+    /// `[date_from, date_to]` (either bound optional). Time buckets are aligned
+    /// to local midnight or hour using `utc_offset` seconds. The same offset is
+    /// applied to every bucket, so buckets on the other side of a DST change
+    /// start an hour off local midnight.
+    pub fn get_stats(
+        &self,
+        date_from: Option<i64>,
+        date_to: Option<i64>,
+        bucket: StatsBucket,
+        utc_offset: i64,
+    ) -> Result<ClipboardStats> {
+        let conn = self.conn.lock().unwrap();
+        let in_range = "(?1 IS NULL OR ci.copied_at >= ?1) AND (?2 IS NULL OR ci.copied_at <= ?2)";
+        let visible = format!("ci.deleted_at IS NULL AND {}", in_range);
+
+        let (total_items, favorite_count, text_bytes): (i64, i64, i64) = conn.query_row(
+            &format!(
+                "SELECT COUNT(*),
+                        COALESCE(SUM(ci.is_favorite), 0),
+                        COALESCE(SUM(CASE WHEN ci.content_type = 'text' THEN LENGTH(CAST(ci.content AS BLOB)) END), 0)
+                 FROM clipboard_items ci WHERE {}",
+                visible
+            ),
+            params![date_from, date_to],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )?;
+
+        let sensitive_skipped: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM skipped_captures
+             WHERE reason = 'sensitive' AND (?1 IS NULL OR skipped_at >= ?1) AND (?2 IS NULL OR skipped_at <= ?2)",
+            params![date_from, date_to],
+            |row| row.get(0),
+        )?;
+
+        let count_by = |column: &str| -> Result<Vec<KeyCount>> {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {col}, COUNT(*) FROM clipboard_items ci WHERE {visible}
+                 GROUP BY {col} ORDER BY COUNT(*) DESC, {col}",
+                col = column,
+                visible = visible
+            ))?;
+            let counts = stmt.query_map(params![date_from, date_to], |row| {
+                Ok(KeyCount { key: row.get(0)?, count: row.get(1)? })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+            Ok(counts)
+        };
+        let by_category = count_by("ci.category")?;
+        let by_source_app = count_by("ci.source_app")?;
+        let by_content_type = count_by("ci.content_type")?;
+
+        let count_by_time = |sql: &str| -> Result<Vec<TimeCount>> {
+            let mut stmt = conn.prepare(sql)?;
+            let counts = stmt.query_map(params![date_from, date_to, bucket.seconds(), utc_offset], |row| {
+                Ok(TimeCount { start: row.get(0)?, count: row.get(1)? })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+            Ok(counts)
+        };
+        // Integer division floors toward zero; timestamps are positive, so this aligns buckets
+        let by_time = count_by_time(&format!(
+            "SELECT ((ci.copied_at + ?4) / ?3) * ?3 - ?4 AS start, COUNT(*)
+             FROM clipboard_items ci WHERE {}
+             GROUP BY start ORDER BY start",
+            visible
+        ))?;
+        let capture_rate = count_by_time(
+            "SELECT ((occurred_at + ?4) / ?3) * ?3 - ?4 AS start, COUNT(*)
+             FROM usage_events
+             WHERE kind = 'capture' AND (?1 IS NULL OR occurred_at >= ?1) AND (?2 IS NULL OR occurred_at <= ?2)
+             GROUP BY start ORDER BY start",
+        )?;
+
+        // Image sizes are recorded at capture; legacy rows without metadata
+        // are measured on disk once the lock is released
+        let (recorded_image_bytes, legacy_paths): (i64, Vec<String>) = {
+            let recorded = conn.query_row(
+                &format!(
+                    "SELECT COALESCE(SUM(im.byte_size), 0) FROM clipboard_items ci
+                     JOIN image_metadata im ON im.item_id = ci.id
+                     WHERE {} AND ci.content_type = 'image'",
+                    visible
+                ),
+                params![date_from, date_to],
+                |row| row.get(0),
+            )?;
+            let mut stmt = conn.prepare(&format!(
+                "SELECT ci.image_path FROM clipboard_items ci
+                 LEFT JOIN image_metadata im ON im.item_id = ci.id
+                 WHERE {} AND ci.content_type = 'image' AND ci.image_path IS NOT NULL
+                   AND im.item_id IS NULL",
+                visible
+            ))?;
+            let paths = stmt
+                .query_map(params![date_from, date_to], |row| row.get::<_, String>(0))?
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            (recorded, paths)
+        };
+        drop(conn);
+
+        let image_bytes = recorded_image_bytes
+            + legacy_paths
+                .iter()
+                .filter_map(|path| std::fs::metadata(path).ok())
+                .map(|meta| meta.len() as i64)
+                .sum::<i64>();
+
+        Ok(ClipboardStats {
+            total_items,
+            favorite_count,
+            sensitive_skipped,
+            text_bytes,
+            image_bytes,
+            by_category,
+            by_source_app,
+            by_content_type,
+            by_time,
+            capture_rate,
+        })
+    }
+
+    /// Usage events of one item, newest first
+    pub fn get_usage_events(&self, item_id: i64) -> Result<Vec<UsageEvent>> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare(
+            "SELECT kind, app, occurred_at FROM usage_events
+             WHERE item_id = ?1
+             ORDER BY occurred_at DESC, id DESC"
+        )?;
+        let events = stmt.query_map(params![item_id], |row| {
+            Ok(UsageEvent { kind: row.get(0)?, app: row.get(1)?, occurred_at: row.get(2)? })
         })?
         .collect::<std::result::Result<Vec<_>, _>>()?;
 
-        Ok(items)
+        Ok(events)
     }
 
-    /// Search clipboard items with FTS5
-    pub fn search(&self, query: String, filters: SearchFilters, limit: u32) -> Result<Vec<ClipboardItem>> {
+    /// Expansions of one snippet, newest first
+    pub fn get_snippet_usage_events(&self, snippet_id: i64) -> Result<Vec<UsageEvent>> {
         let conn = self.conn.lock().unwrap();
 
-        // Build FTS5 query with filters
-        let mut sql = String::from(
-            "SELECT ci.id, ci.content, ci.content_type, ci.image_path, ci.category, ci.source_app, ci.preview, ci.copied_at, ci.is_favorite, ci.is_sensitive, ci.hash
-             FROM clipboard_items ci
-             JOIN clipboard_fts fts ON ci.id = fts.rowid
-             WHERE clipboard_fts MATCH ?1 AND ci.is_sensitive = 0"
-        );
+        let mut stmt = conn.prepare(
+            "SELECT kind, app, occurred_at FROM usage_events
+             WHERE snippet_id = ?1
+             ORDER BY occurred_at DESC, id DESC"
+        )?;
+        let events = stmt.query_map(params![snippet_id], |row| {
+            Ok(UsageEvent { kind: row.get(0)?, app: row.get(1)?, occurred_at: row.get(2)? })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
 
-        let mut params: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(query)];
+        Ok(events)
+    }
 
-        if let Some(category) = filters.category {
-            sql.push_str(" AND ci.category = ?");
-            params.push(Box::new(category));
+    /// Search clipboard items. Fuzzy mode tops up token matches with substring
+    /// matches from the trigram index, then typo matches for a lone short word;
+    /// each step only runs while fewer than `limit` items were found.
+    pub fn search(&self, query: String, filters: SearchFilters, mode: SearchMode, page: PageRequest) -> Result<Vec<SearchHit>> {
+        let parsed = query::parse(&query, chrono::Local::now())?;
+        let filters = parsed.merge_filters(filters);
+        let sort = filters.sort.unwrap_or_default();
+        if page.after.is_some() && !sort.is_chronological() {
+            return Err(AppError::InvalidInput(
+                "Keyset cursors are only supported when sorting by newest or oldest".to_string(),
+            ));
         }
+        let weights = self.get_settings()?.ranking_weights;
+        let conn = self.conn.lock().unwrap();
 
-        if let Some(source_app) = filters.source_app {
-            sql.push_str(" AND ci.source_app = ?");
-            params.push(Box::new(source_app));
-        }
+        let token_index = parsed.match_expr.as_deref().map(|expr| (TOKEN_INDEX, expr));
+        let mut hits = query_matching_items(&conn, &parsed, filters.clone(), token_index, &weights, page)?;
 
-        if let Some(content_type) = filters.content_type {
-            sql.push_str(" AND ci.content_type = ?");
-            params.push(Box::new(content_type));
+        if let (Some(match_expr), SearchMode::Fuzzy) = (parsed.match_expr.as_deref(), mode) {
+            let index = (TRIGRAM_INDEX, match_expr);
+            Self::add_fuzzy_hits(&conn, &parsed, filters, index, &weights, &mut hits, page)?;
         }
 
-        if let Some(date_from) = filters.date_from {
-            sql.push_str(" AND ci.copied_at >= ?");
-            params.push(Box::new(date_from));
+        for hit in &mut hits {
+            hit.matches = parsed
+                .match_ranges(&hit.item.content)
+                .into_iter()
+                .map(|(start, end)| MatchRange { start, end })
+                .collect();
         }
 
-        if let Some(date_to) = filters.date_to {
-            sql.push_str(" AND ci.copied_at <= ?");
-            params.push(Box::new(date_to));
-        }
+        Ok(hits)
+    }
 
-        sql.push_str(" ORDER BY ci.copied_at DESC LIMIT ?");
-        params.push(Box::new(limit));
+    /// Add substring and typo matches. For ranked sorts they fill up a page
+    /// the token index left short; for chronological sorts every pass runs and
+    /// the merged hits are re-sorted, so a page never skips a newer match.
+    fn add_fuzzy_hits(
+        conn: &Connection,
+        parsed: &ParsedQuery,
+        filters: SearchFilters,
+        substring_index: (&str, &str),
+        weights: &RankingWeights,
+        hits: &mut Vec<SearchHit>,
+        page: PageRequest,
+    ) -> Result<()> {
+        let sort = filters.sort.unwrap_or_default();
+        let limit = if sort.is_chronological() { u32::MAX } else { page.limit };
+
+        if hits.len() < limit as usize {
+            let substring = query_matching_items(conn, parsed, filters.clone(), Some(substring_index), weights, page)?;
+            merge_unique(hits, substring, limit);
+        }
 
-        let params_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+        if hits.len() < limit as usize {
+            if let Some(candidates_expr) = parsed.typo_candidates_expr() {
+                let candidates = query_matching_items(
+                    conn,
+                    parsed,
+                    filters,
+                    Some((TRIGRAM_INDEX, &candidates_expr)),
+                    weights,
+                    PageRequest { limit: TYPO_CANDIDATE_LIMIT, ..page },
+                )?;
+                let typo_hits = candidates
+                    .into_iter()
+                    .filter(|hit| has_typo_match(&hit.item, parsed))
+                    .map(|hit| SearchHit { snippet: None, ..hit })   // trigram snippet would mark fragments
+                    .collect();
+                merge_unique(hits, typo_hits, limit);
+            }
+        }
 
-        let mut stmt = conn.prepare(&sql)?;
-        let items = stmt.query_map(params_refs.as_slice(), |row| {
-            Ok(ClipboardItem {
-                id: row.get(0)?,
-                content: row.get(1)?,
-                content_type: row.get(2)?,
-                image_path: row.get(3)?,
-                category: row.get(4)?,
-                source_app: row.get(5)?,
-                preview: row.get(6)?,
-                copied_at: row.get(7)?,
-                is_favorite: row.get::<_, i32>(8)? != 0,
-                is_sensitive: row.get::<_, i32>(9)? != 0,
-                hash: row.get(10)?,
-            })
-        })?
-        .collect::<std::result::Result<Vec<_>, _>>()?;
+        if sort.is_chronological() {
+            hits.sort_by_key(|hit| std::cmp::Reverse((hit.item.copied_at, hit.item.id)));
+            if sort == SearchSort::Oldest {
+                hits.reverse();
+            }
+            hits.truncate(page.limit as usize);
+        }
 
-        Ok(items)
+        Ok(())
     }
 
     /// Get item content by ID (for copying to clipboard)
@@ -198,43 +1009,264 @@ impl Database {
         ).map_err(|_| AppError::NotFound(id))
     }
 
+    /// Read up to `max_bytes` of an item's content starting at byte `offset`,
+    /// without loading the rest. The chunk ends on a character boundary.
+    pub fn get_content_chunk(&self, id: i64, offset: usize, max_bytes: usize) -> Result<ContentChunk> {
+        let conn = self.conn.lock().unwrap();
+
+        // A few extra bytes let the end be moved back to a character boundary
+        let (total_bytes, window): (i64, Vec<u8>) = conn.query_row(
+            "SELECT LENGTH(CAST(content AS BLOB)), substr(CAST(content AS BLOB), ?2 + 1, ?3)
+             FROM clipboard_items WHERE id = ?1",
+            params![id, offset as i64, (max_bytes + 3) as i64],
+            |row| Ok((row.get(0)?, row.get::<_, Option<Vec<u8>>>(1)?.unwrap_or_default())),
+        ).map_err(|_| AppError::NotFound(id))?;
+        let total_bytes = total_bytes as usize;
+
+        let is_continuation = |byte: u8| byte & 0xC0 == 0x80;
+        if offset > total_bytes || window.first().is_some_and(|&b| is_continuation(b)) {
+            return Err(AppError::InvalidInput(format!("Offset {} is not a character boundary", offset)));
+        }
+
+        let mut end = window.len();
+        if end > max_bytes {
+            end = max_bytes;
+            while end > 0 && is_continuation(window[end]) {
+                end -= 1;
+            }
+        }
+        let text = String::from_utf8(window[..end].to_vec())
+            .map_err(|_| AppError::InvalidInput("Content is not valid UTF-8".to_string()))?;
+
+        Ok(ContentChunk {
+            text,
+            offset,
+            next_offset: (offset + end < total_bytes).then_some(offset + end),
+            total_bytes,
+        })
+    }
+
     /// Get a single item by ID
     pub fn get_item_by_id(&self, id: i64) -> Result<ClipboardItem> {
         let conn = self.conn.lock().unwrap();
 
         conn.query_row(
-            "SELECT id, content, content_type, image_path, category, source_app, preview, copied_at, is_favorite, is_sensitive, hash
-             FROM clipboard_items WHERE id = ?1",
+            &format!("SELECT {} FROM clipboard_items ci WHERE ci.id = ?1", ITEM_COLUMNS),
             params![id],
-            |row| {
-                Ok(ClipboardItem {
-                    id: row.get(0)?,
-                    content: row.get(1)?,
-                    content_type: row.get(2)?,
-                    image_path: row.get(3)?,
-                    category: row.get(4)?,
-                    source_app: row.get(5)?,
-                    preview: row.get(6)?,
-                    copied_at: row.get(7)?,
-                    is_favorite: row.get::<_, i32>(8)? != 0,
-                    is_sensitive: row.get::<_, i32>(9)? != 0,
-                    hash: row.get(10)?,
-                })
-            },
+            row_to_item,
         ).map_err(|_| AppError::NotFound(id))
     }
 
-    /// Check if an image path exists in the database.
-    pub fn image_path_exists(&self, image_path: &str) -> Result<bool> {
-        let conn = self.conn.lock().unwrap();
+    /// Record that `item_id` was built from `source_ids` (in order)
+    pub fn link_derivations(&self, item_id: i64, source_ids: &[i64]) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
 
-        let exists: i64 = conn.query_row(
-            "SELECT EXISTS(SELECT 1 FROM clipboard_items WHERE image_path = ?1 LIMIT 1)",
-            params![image_path],
-            |row| row.get(0),
-        )?;
+        for (position, source_id) in source_ids.iter().enumerate() {
+            if *source_id == item_id {
+                continue;
+            }
+            tx.execute(
+                "INSERT OR IGNORE INTO item_derivations (item_id, source_id, position) VALUES (?1, ?2, ?3)",
+                params![item_id, source_id, position as i64],
+            )?;
+        }
 
-        Ok(exists != 0)
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Get the ids an item was derived from, in their original order
+    pub fn get_derived_from(&self, item_id: i64) -> Result<Vec<i64>> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare(
+            "SELECT d.source_id FROM item_derivations d
+             JOIN clipboard_items ci ON ci.id = d.source_id
+             WHERE d.item_id = ?1 AND ci.deleted_at IS NULL
+             ORDER BY d.position"
+        )?;
+        let ids = stmt.query_map(params![item_id], |row| row.get(0))?
+            .collect::<std::result::Result<Vec<i64>, _>>()?;
+
+        Ok(ids)
+    }
+
+    /// Check if an image path exists in the database.
+    pub fn image_path_exists(&self, image_path: &str) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+
+        let exists: i64 = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM clipboard_items WHERE image_path = ?1 LIMIT 1)",
+            params![image_path],
+            |row| row.get(0),
+        )?;
+
+        Ok(exists != 0)
+    }
+
+    /// Record thumbnails generated for an image item. Returns false if the
+    /// item already has thumbnails (e.g. a re-copied image), leaving them as is.
+    pub fn set_thumbnails(&self, id: i64, thumbnails: &ThumbnailPaths) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let rows = conn.execute(
+            "UPDATE clipboard_items SET thumb_small_path = ?1, thumb_medium_path = ?2
+             WHERE id = ?3 AND content_type = 'image' AND thumb_small_path IS NULL",
+            params![thumbnails.small, thumbnails.medium, id],
+        )?;
+        Ok(rows > 0)
+    }
+
+    /// Record how an image item was stored. A re-copied image keeps its first record.
+    pub fn set_image_metadata(&self, id: i64, metadata: &ImageMetadata) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR IGNORE INTO image_metadata
+                (item_id, original_width, original_height, width, height, format, byte_size)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                id,
+                metadata.original_width,
+                metadata.original_height,
+                metadata.width,
+                metadata.height,
+                metadata.format.as_str(),
+                metadata.byte_size as i64,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Stored image metadata, if recorded for this item
+    pub fn get_image_metadata(&self, id: i64) -> Result<Option<ImageMetadata>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT original_width, original_height, width, height, format, byte_size
+             FROM image_metadata WHERE item_id = ?1"
+        )?;
+        let mut rows = stmt.query_map(params![id], |row| {
+            let format: String = row.get(4)?;
+            Ok(ImageMetadata {
+                original_width: row.get(0)?,
+                original_height: row.get(1)?,
+                width: row.get(2)?,
+                height: row.get(3)?,
+                format: StoredImageFormat::parse(&format).unwrap_or(StoredImageFormat::Png),
+                byte_size: row.get::<_, i64>(5)?.max(0) as u64,
+            })
+        })?;
+        Ok(rows.next().transpose()?)
+    }
+
+    /// Closest live image item within `max_distance` dHash bits, as (id, distance),
+    /// among the `NEAR_DUPLICATE_CANDIDATES` most recent images. Ties go to the
+    /// most recently copied item.
+    pub fn find_near_duplicate(&self, dhash: u64, max_distance: u32) -> Result<Option<(i64, u32)>> {
+        // Only the candidate fetch holds the lock; comparing happens after it is released
+        let candidates = {
+            let conn = self.conn.lock().unwrap();
+            let mut stmt = conn.prepare(
+                "SELECT id, image_dhash FROM clipboard_items
+                 WHERE image_dhash IS NOT NULL AND deleted_at IS NULL
+                 ORDER BY copied_at DESC, id DESC
+                 LIMIT ?1"
+            )?;
+            let rows = stmt.query_map(params![NEAR_DUPLICATE_CANDIDATES], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?))
+            })?;
+            rows.collect::<std::result::Result<Vec<_>, _>>()?
+        };
+
+        let mut best: Option<(i64, u32)> = None;
+        for (id, other) in candidates {
+            let distance = (dhash ^ other as u64).count_ones();
+            if distance <= max_distance && best.is_none_or(|(_, best)| distance < best) {
+                best = Some((id, distance));
+            }
+        }
+        Ok(best)
+    }
+
+    /// Count a near-duplicate capture as a re-copy of an existing item
+    pub fn merge_capture(&self, id: i64, source_app: &str, copied_at: i64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        record_recapture(&conn, id, source_app, copied_at)?;
+        log::debug!("Merged near-duplicate image into item {}", id);
+        Ok(())
+    }
+
+    /// Store an image's perceptual hash, and the item it was found to nearly
+    /// duplicate. An item keeps the hash it was first stored with.
+    pub fn set_image_dhash(&self, id: i64, dhash: u64, near_duplicate_of: Option<i64>) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE clipboard_items SET image_dhash = ?1, near_duplicate_of = ?2
+             WHERE id = ?3 AND image_dhash IS NULL",
+            params![dhash as i64, near_duplicate_of.filter(|other| *other != id), id],
+        )?;
+        Ok(())
+    }
+
+    /// Live image items within `max_distance` dHash bits of item `id`, closest
+    /// first. Items linked to it as near-duplicates are included at any distance.
+    pub fn find_similar_images(&self, id: i64, max_distance: u32) -> Result<Vec<SimilarImage>> {
+        let conn = self.conn.lock().unwrap();
+        let (dhash, linked_to): (Option<i64>, Option<i64>) = conn.query_row(
+            "SELECT image_dhash, near_duplicate_of FROM clipboard_items WHERE id = ?1 AND content_type = 'image'",
+            params![id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ).map_err(|_| AppError::NotFound(id))?;
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {}, ci.image_dhash, ci.near_duplicate_of
+             FROM clipboard_items ci
+             WHERE ci.content_type = 'image' AND ci.id != ?1 AND ci.deleted_at IS NULL AND ci.is_sensitive = 0
+               AND (ci.image_dhash IS NOT NULL OR ci.near_duplicate_of = ?1 OR ci.id = ?2)",
+            LIST_COLUMNS
+        ))?;
+        let extra = list_columns_end();
+        let rows = stmt.query_map(params![id, linked_to], |row| {
+            Ok((row_to_list_item(row)?, row.get::<_, Option<i64>>(extra)?, row.get::<_, Option<i64>>(extra + 1)?))
+        })?;
+
+        let mut similar = Vec::new();
+        for row in rows {
+            let (item, other, other_linked_to) = row?;
+            let linked = other_linked_to == Some(id) || linked_to == Some(item.id);
+            let distance = match (dhash, other) {
+                (Some(dhash), Some(other)) => (dhash ^ other).count_ones(),
+                _ => u64::BITS,
+            };
+            if distance <= max_distance || linked {
+                similar.push(SimilarImage { item, distance, linked });
+            }
+        }
+        similar.sort_by(|a, b| a.distance.cmp(&b.distance).then(b.item.copied_at.cmp(&a.item.copied_at)));
+        Ok(similar)
+    }
+
+    /// Cached (small, medium) thumbnail paths of an item
+    pub fn get_thumbnail_paths(&self, id: i64) -> Result<(Option<String>, Option<String>)> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            "SELECT thumb_small_path, thumb_medium_path FROM clipboard_items WHERE id = ?1",
+            params![id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ).map_err(|_| AppError::NotFound(id))
+    }
+
+    /// Image items without thumbnails, by id after `after_id`, as (id, image_path)
+    pub fn get_items_missing_thumbnails(&self, after_id: i64, limit: u32) -> Result<Vec<(i64, String)>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, image_path FROM clipboard_items
+             WHERE content_type = 'image' AND image_path IS NOT NULL AND thumb_small_path IS NULL AND id > ?1
+             ORDER BY id
+             LIMIT ?2"
+        )?;
+        let items = stmt.query_map(params![after_id, limit], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(items)
     }
 
     /// Set favorite status
@@ -253,11 +1285,130 @@ impl Database {
         Ok(())
     }
 
+    /// Set (or clear, with `None`) the title and note of an item
+    pub fn set_annotation(&self, id: i64, title: Option<String>, note: Option<String>) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+
+        let rows = conn.execute(
+            "UPDATE clipboard_items SET title = ?1, note = ?2 WHERE id = ?3",
+            params![title, note, id],
+        )?;
+
+        if rows == 0 {
+            return Err(AppError::NotFound(id));
+        }
+
+        Ok(())
+    }
+
+    /// Replace a text item's content, keeping the previous version as a revision.
+    /// Derived fields are computed by the caller with the monitor's functions.
+    pub fn update_item_content(
+        &self,
+        id: i64,
+        content: String,
+        hash: String,
+        category: String,
+        preview: String,
+        is_sensitive: bool,
+    ) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+
+        let (old_content, old_hash, content_type): (String, String, String) = tx.query_row(
+            "SELECT content, hash, content_type FROM clipboard_items WHERE id = ?1",
+            params![id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        ).map_err(|_| AppError::NotFound(id))?;
+
+        if content_type != "text" {
+            return Err(AppError::InvalidInput("Only text items can be edited".to_string()));
+        }
+
+        if old_hash == hash {
+            return Ok(());
+        }
+
+        tx.execute(
+            "INSERT INTO item_revisions (item_id, content, hash, replaced_at) VALUES (?1, ?2, ?3, ?4)",
+            params![id, old_content, old_hash, chrono::Utc::now().timestamp()],
+        )?;
+
+        tx.execute(
+            "UPDATE clipboard_items SET content = ?1, hash = ?2, category = ?3, preview = ?4, is_sensitive = ?5
+             WHERE id = ?6",
+            params![content, hash, category, preview, is_sensitive, id],
+        ).map_err(|e| {
+            if is_constraint_violation(&e) {
+                AppError::InvalidInput("Another item already has this content".to_string())
+            } else {
+                e.into()
+            }
+        })?;
+
+        tx.commit()?;
+        log::debug!("Updated content of item {}", id);
+        Ok(())
+    }
+
+    /// Get previous versions of an item, newest first
+    pub fn get_item_revisions(&self, item_id: i64) -> Result<Vec<ItemRevision>> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare(
+            "SELECT id, item_id, content, replaced_at FROM item_revisions
+             WHERE item_id = ?1
+             ORDER BY replaced_at DESC, id DESC"
+        )?;
+        let revisions = stmt.query_map(params![item_id], |row| {
+            Ok(ItemRevision {
+                id: row.get(0)?,
+                item_id: row.get(1)?,
+                content: row.get(2)?,
+                replaced_at: row.get(3)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(revisions)
+    }
+
+    /// Get the content of one revision of an item
+    pub fn get_revision_content(&self, item_id: i64, revision_id: i64) -> Result<String> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.query_row(
+            "SELECT content FROM item_revisions WHERE id = ?1 AND item_id = ?2",
+            params![revision_id, item_id],
+            |row| row.get(0),
+        ).map_err(|_| AppError::NotFound(revision_id))
+    }
+
     /// Delete item by ID
     pub fn delete_item(&self, id: i64) -> Result<()> {
         let conn = self.conn.lock().unwrap();
 
-        let rows = conn.execute("DELETE FROM clipboard_items WHERE id = ?1", params![id])?;
+        // Move to the trash; the row and any image file are removed by purge_trash
+        let rows = conn.execute(
+            "UPDATE clipboard_items SET deleted_at = ?1 WHERE id = ?2 AND deleted_at IS NULL",
+            params![chrono::Utc::now().timestamp(), id],
+        )?;
+
+        if rows == 0 {
+            return Err(AppError::NotFound(id));
+        }
+
+        Ok(())
+    }
+
+    /// Move an item out of the trash
+    pub fn restore_item(&self, id: i64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+
+        let rows = conn.execute(
+            "UPDATE clipboard_items SET deleted_at = NULL WHERE id = ?1 AND deleted_at IS NOT NULL",
+            params![id],
+        )?;
 
         if rows == 0 {
             return Err(AppError::NotFound(id));
@@ -266,6 +1417,163 @@ impl Database {
         Ok(())
     }
 
+    /// Get trashed items, most recently deleted first
+    pub fn get_trash(&self) -> Result<Vec<ClipboardListItem>> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {}
+             FROM clipboard_items ci
+             WHERE ci.deleted_at IS NOT NULL
+             ORDER BY ci.deleted_at DESC",
+            LIST_COLUMNS
+        ))?;
+        let items = stmt.query_map([], row_to_list_item)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(items)
+    }
+
+    /// Permanently remove items that have been in the trash longer than `trash_days`
+    pub fn purge_trash(&self, trash_days: u32) -> Result<u64> {
+        let conn = self.conn.lock().unwrap();
+
+        let safe_trash_days = std::cmp::min(trash_days, 3650);
+        let threshold = chrono::Utc::now().timestamp()
+            .saturating_sub(safe_trash_days as i64 * 86400);
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM clipboard_items
+             WHERE deleted_at IS NOT NULL AND deleted_at <= ?1 AND content_type = 'image'",
+            IMAGE_FILE_COLUMNS
+        ))?;
+        let image_files = stmt.query_map(params![threshold], row_to_image_files)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        let purged = conn.execute(
+            "DELETE FROM clipboard_items WHERE deleted_at IS NOT NULL AND deleted_at <= ?1",
+            params![threshold],
+        )?;
+
+        remove_image_files(image_files, "trashed");
+
+        if purged > 0 {
+            log::info!("Purged {} items from the trash", purged);
+        }
+
+        Ok(purged as u64)
+    }
+
+    /// Apply one action to many items in a single transaction.
+    /// Returns the ids of the items that actually changed.
+    pub fn bulk_update(&self, selection: BulkSelection, action: &BulkAction) -> Result<Vec<i64>> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+
+        let ids: Vec<i64> = match selection {
+            BulkSelection::Ids { ids } => {
+                let mut stmt = tx.prepare(
+                    "SELECT EXISTS(SELECT 1 FROM clipboard_items WHERE id = ?1 AND deleted_at IS NULL)"
+                )?;
+                let mut existing = Vec::with_capacity(ids.len());
+                for id in ids {
+                    if stmt.query_row(params![id], |row| row.get::<_, i64>(0))? != 0 && !existing.contains(&id) {
+                        existing.push(id);
+                    }
+                }
+                existing
+            }
+            BulkSelection::Search { query, filters } => {
+                // Destructive operations only use whole-token matches, never fuzzy ones
+                let parsed = query::parse(&query, chrono::Local::now())?;
+                let filters = parsed.merge_filters(filters);
+                let token_index = parsed.match_expr.as_deref().map(|expr| (TOKEN_INDEX, expr));
+                let (clause, params) = search_clause(&parsed, filters, token_index);
+                let params_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+                let mut stmt = tx.prepare(&format!("SELECT ci.id {}", clause))?;
+                let ids = stmt.query_map(params_refs.as_slice(), |row| row.get(0))?
+                    .collect::<std::result::Result<Vec<_>, _>>()?;
+                ids
+            }
+        };
+
+        let now = chrono::Utc::now().timestamp();
+        let mut affected = Vec::new();
+
+        if let BulkAction::AddToCollection { collection_id } = action {
+            let collection_exists: i64 = tx.query_row(
+                "SELECT EXISTS(SELECT 1 FROM collections WHERE id = ?1)",
+                params![collection_id],
+                |row| row.get(0),
+            )?;
+            if collection_exists == 0 {
+                return Err(AppError::NotFound(*collection_id));
+            }
+        }
+
+        for id in ids {
+            let rows = match action {
+                BulkAction::Delete => tx.execute(
+                    "UPDATE clipboard_items SET deleted_at = ?1 WHERE id = ?2",
+                    params![now, id],
+                )?,
+                BulkAction::Favorite | BulkAction::Unfavorite => {
+                    let favorite = matches!(action, BulkAction::Favorite);
+                    tx.execute(
+                        "UPDATE clipboard_items SET is_favorite = ?1 WHERE id = ?2 AND is_favorite != ?1",
+                        params![favorite, id],
+                    )?
+                }
+                BulkAction::AddToCollection { collection_id } => tx.execute(
+                    "INSERT OR IGNORE INTO collection_items (collection_id, item_id, position, added_at)
+                     VALUES (?1, ?2, (SELECT COALESCE(MAX(position), -1) + 1 FROM collection_items WHERE collection_id = ?1), ?3)",
+                    params![collection_id, id, now],
+                )?,
+                BulkAction::Retag { category } => tx.execute(
+                    "UPDATE clipboard_items SET category = ?1 WHERE id = ?2 AND category != ?1",
+                    params![category, id],
+                )?,
+            };
+            if rows > 0 {
+                affected.push(id);
+            }
+        }
+
+        tx.commit()?;
+        log::info!("Bulk {:?} changed {} items", action, affected.len());
+        Ok(affected)
+    }
+
+    /// Permanently delete all history except favorites and collection items.
+    /// Returns the ids of the deleted items.
+    pub fn clear_history(&self) -> Result<Vec<i64>> {
+        let conn = self.conn.lock().unwrap();
+
+        // Get image paths before deleting for cleanup
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM clipboard_items
+             WHERE {} AND content_type = 'image'",
+            IMAGE_FILE_COLUMNS, CLEANUP_ELIGIBLE
+        ))?;
+        let image_files = stmt.query_map([], row_to_image_files)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        let mut stmt = conn.prepare(&format!("SELECT id FROM clipboard_items WHERE {}", CLEANUP_ELIGIBLE))?;
+        let ids = stmt.query_map([], |row| row.get(0))?
+            .collect::<std::result::Result<Vec<i64>, _>>()?;
+
+        conn.execute(
+            &format!("DELETE FROM clipboard_items WHERE {}", CLEANUP_ELIGIBLE),
+            [],
+        )?;
+
+        // Clean up image files
+        remove_image_files(image_files, "cleared");
+
+        log::info!("Cleared {} clipboard items", ids.len());
+        Ok(ids)
+    }
+
     /// Cleanup items older than retention period
     pub fn cleanup_expired(&self, retention_days: u32) -> Result<u64> {
         let conn = self.conn.lock().unwrap();
@@ -276,25 +1584,29 @@ impl Database {
             .saturating_sub(safe_retention_days as i64 * 86400);
 
         // Get image paths before deleting for cleanup
-        let mut stmt = conn.prepare(
-            "SELECT image_path FROM clipboard_items
-             WHERE copied_at < ?1 AND is_favorite = 0 AND content_type = 'image' AND image_path IS NOT NULL"
-        )?;
-        let image_paths: Vec<String> = stmt.query_map(params![threshold], |row| row.get(0))?
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM clipboard_items
+             WHERE copied_at < ?1 AND deleted_at IS NULL AND {} AND content_type = 'image'",
+            IMAGE_FILE_COLUMNS, CLEANUP_ELIGIBLE
+        ))?;
+        let image_files = stmt.query_map(params![threshold], row_to_image_files)?
             .collect::<std::result::Result<Vec<_>, _>>()?;
 
-        // Delete items from database
+        // Delete items from database; trashed items are left to `purge_trash`
         let deleted = conn.execute(
-            "DELETE FROM clipboard_items WHERE copied_at < ?1 AND is_favorite = 0",
+            &format!(
+                "DELETE FROM clipboard_items WHERE copied_at < ?1 AND deleted_at IS NULL AND {}",
+                CLEANUP_ELIGIBLE
+            ),
             params![threshold],
         )?;
 
+        // Usage history of kept items (favorites, collections) ages out too
+        conn.execute("DELETE FROM usage_events WHERE occurred_at < ?1", params![threshold])?;
+        conn.execute("DELETE FROM skipped_captures WHERE skipped_at < ?1", params![threshold])?;
+
         // Clean up image files
-        for path in image_paths {
-            if let Err(e) = std::fs::remove_file(&path) {
-                log::warn!("Failed to delete expired image file {}: {}", path, e);
-            }
-        }
+        remove_image_files(image_files, "expired");
 
         if deleted > 0 {
             log::info!("Cleaned up {} expired clipboard items", deleted);
@@ -315,45 +1627,46 @@ impl Database {
             },
         )?;
 
-        // Count current items
-        let count: i64 = conn.query_row("SELECT COUNT(*) FROM clipboard_items", [], |row| row.get(0))?;
+        // Count current items; the trash has its own retention and doesn't count
+        let count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM clipboard_items WHERE deleted_at IS NULL",
+            [],
+            |row| row.get(0),
+        )?;
 
         if count > max_items as i64 {
             let to_delete = count - max_items as i64;
 
             // Gather image paths for the same candidate set before deletion.
-            let mut stmt = conn.prepare(
-                "SELECT image_path FROM clipboard_items
-                 WHERE is_favorite = 0
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {} FROM clipboard_items
+                 WHERE deleted_at IS NULL AND {}
                  ORDER BY copied_at ASC
-                 LIMIT ?1"
-            )?;
-            let image_paths: Vec<String> = stmt
-                .query_map(params![to_delete], |row| row.get::<_, Option<String>>(0))?
-                .collect::<std::result::Result<Vec<_>, _>>()?
-                .into_iter()
-                .flatten()
-                .collect();
+                 LIMIT ?1",
+                IMAGE_FILE_COLUMNS, CLEANUP_ELIGIBLE
+            ))?;
+            let image_files = stmt
+                .query_map(params![to_delete], row_to_image_files)?
+                .collect::<std::result::Result<Vec<_>, _>>()?;
 
             let deleted = conn.execute(
-                "DELETE FROM clipboard_items WHERE id IN (
-                    SELECT id FROM clipboard_items
-                    WHERE is_favorite = 0
-                    ORDER BY copied_at ASC
-                    LIMIT ?1
-                )",
+                &format!(
+                    "DELETE FROM clipboard_items WHERE id IN (
+                        SELECT id FROM clipboard_items
+                        WHERE deleted_at IS NULL AND {}
+                        ORDER BY copied_at ASC
+                        LIMIT ?1
+                    )",
+                    CLEANUP_ELIGIBLE
+                ),
                 params![to_delete],
             )?;
 
-            for path in image_paths {
-                if let Err(e) = std::fs::remove_file(&path) {
-                    log::warn!("Failed to delete excess image file {}: {}", path, e);
-                }
-            }
+            remove_image_files(image_files, "excess");
 
             if deleted == 0 {
                 log::warn!(
-                    "max_items exceeded but no non-favorite items were eligible for deletion"
+                    "max_items exceeded but no unprotected items were eligible for deletion"
                 );
             } else {
                 log::info!(
@@ -366,48 +1679,224 @@ impl Database {
         Ok(())
     }
 
-    /// Get settings
-    pub fn get_settings(&self) -> Result<Settings> {
+    /// Get collections in manual order, with item counts
+    pub fn get_collections(&self) -> Result<Vec<Collection>> {
         let conn = self.conn.lock().unwrap();
 
-        let mut stmt = conn.prepare("SELECT key, value FROM settings")?;
-        let rows = stmt.query_map([], |row| {
-            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
-        })?;
-
-        let mut settings = Settings::default();
-
-        for row in rows {
-            let (key, value) = row?;
-            match key.as_str() {
-                "retention_days" => settings.retention_days = value.parse().unwrap_or(30),
-                "max_items" => settings.max_items = value.parse().unwrap_or(1000),
-                "keyboard_shortcut" => settings.keyboard_shortcut = value,
-                "auto_exclude_sensitive" => settings.auto_exclude_sensitive = value == "true",
-                "max_image_size_mb" => settings.max_image_size_mb = value.parse().unwrap_or(5),
-                _ => {}
-            }
-        }
+        let mut stmt = conn.prepare(
+            "SELECT c.id, c.name, c.position, c.created_at, COUNT(ci.item_id)
+             FROM collections c
+             LEFT JOIN collection_items ci ON ci.collection_id = c.id
+             GROUP BY c.id
+             ORDER BY c.position, c.id"
+        )?;
+        let collections = stmt.query_map([], |row| {
+            Ok(Collection {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                position: row.get(2)?,
+                created_at: row.get(3)?,
+                item_count: row.get(4)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
 
-        Ok(settings)
+        Ok(collections)
     }
 
-    /// Update settings
-    pub fn update_settings(&self, settings: Settings) -> Result<()> {
+    /// Create a collection at the end of the list and return its ID
+    pub fn create_collection(&self, name: String) -> Result<i64> {
         let conn = self.conn.lock().unwrap();
 
-        conn.execute("INSERT OR REPLACE INTO settings (key, value) VALUES ('retention_days', ?1)", params![settings.retention_days.to_string()])?;
-        conn.execute("INSERT OR REPLACE INTO settings (key, value) VALUES ('max_items', ?1)", params![settings.max_items.to_string()])?;
-        conn.execute("INSERT OR REPLACE INTO settings (key, value) VALUES ('keyboard_shortcut', ?1)", params![settings.keyboard_shortcut])?;
-        conn.execute("INSERT OR REPLACE INTO settings (key, value) VALUES ('auto_exclude_sensitive', ?1)", params![settings.auto_exclude_sensitive.to_string()])?;
-        conn.execute("INSERT OR REPLACE INTO settings (key, value) VALUES ('max_image_size_mb', ?1)", params![settings.max_image_size_mb.to_string()])?;
-
-        log::info!("Settings updated");
-        Ok(())
+        conn.execute(
+            "INSERT INTO collections (name, position, created_at)
+             VALUES (?1, (SELECT COALESCE(MAX(position), -1) + 1 FROM collections), ?2)",
+            params![name, chrono::Utc::now().timestamp()],
+        ).map_err(map_collection_write_error)?;
+
+        let id = conn.last_insert_rowid();
+        log::info!("Created collection: id={}, name={}", id, name);
+        Ok(id)
     }
 
-    /// Get app exclusions
-    pub fn get_exclusions(&self) -> Result<Vec<String>> {
+    /// Rename a collection
+    pub fn rename_collection(&self, id: i64, name: String) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+
+        let rows = conn.execute(
+            "UPDATE collections SET name = ?1 WHERE id = ?2",
+            params![name, id],
+        ).map_err(map_collection_write_error)?;
+
+        if rows == 0 {
+            return Err(AppError::NotFound(id));
+        }
+
+        Ok(())
+    }
+
+    /// Delete a collection (its items stay in history, but lose the protection it gave)
+    pub fn delete_collection(&self, id: i64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+
+        let rows = conn.execute("DELETE FROM collections WHERE id = ?1", params![id])?;
+
+        if rows == 0 {
+            return Err(AppError::NotFound(id));
+        }
+
+        Ok(())
+    }
+
+    /// Set the manual order of collections; `ids` lists them front to back
+    pub fn reorder_collections(&self, ids: &[i64]) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+
+        for (position, id) in ids.iter().enumerate() {
+            let rows = tx.execute(
+                "UPDATE collections SET position = ?1 WHERE id = ?2",
+                params![position as i64, id],
+            )?;
+            if rows == 0 {
+                return Err(AppError::NotFound(*id));
+            }
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Add an item to the end of a collection (no-op if it is already there)
+    pub fn add_to_collection(&self, collection_id: i64, item_id: i64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+
+        let collection_exists: i64 = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM collections WHERE id = ?1)",
+            params![collection_id],
+            |row| row.get(0),
+        )?;
+        if collection_exists == 0 {
+            return Err(AppError::NotFound(collection_id));
+        }
+
+        conn.execute(
+            "INSERT OR IGNORE INTO collection_items (collection_id, item_id, position, added_at)
+             VALUES (?1, ?2, (SELECT COALESCE(MAX(position), -1) + 1 FROM collection_items WHERE collection_id = ?1), ?3)",
+            params![collection_id, item_id, chrono::Utc::now().timestamp()],
+        ).map_err(|e| if is_constraint_violation(&e) { AppError::NotFound(item_id) } else { e.into() })?;
+
+        Ok(())
+    }
+
+    /// Remove an item from a collection
+    pub fn remove_from_collection(&self, collection_id: i64, item_id: i64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+
+        let rows = conn.execute(
+            "DELETE FROM collection_items WHERE collection_id = ?1 AND item_id = ?2",
+            params![collection_id, item_id],
+        )?;
+
+        if rows == 0 {
+            return Err(AppError::NotFound(item_id));
+        }
+
+        Ok(())
+    }
+
+    /// Set the manual order of items in a collection; `item_ids` lists them front to back
+    pub fn reorder_collection_items(&self, collection_id: i64, item_ids: &[i64]) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+
+        for (position, item_id) in item_ids.iter().enumerate() {
+            let rows = tx.execute(
+                "UPDATE collection_items SET position = ?1 WHERE collection_id = ?2 AND item_id = ?3",
+                params![position as i64, collection_id, item_id],
+            )?;
+            if rows == 0 {
+                return Err(AppError::NotFound(*item_id));
+            }
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Get the items of a collection in manual order
+    pub fn get_collection_items(&self, collection_id: i64) -> Result<Vec<ClipboardListItem>> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {}
+             FROM clipboard_items ci
+             JOIN collection_items col ON col.item_id = ci.id
+             WHERE col.collection_id = ?1 AND ci.deleted_at IS NULL
+             ORDER BY col.position, col.added_at",
+            LIST_COLUMNS
+        ))?;
+        let items = stmt.query_map(params![collection_id], row_to_list_item)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(items)
+    }
+
+    /// Get settings
+    pub fn get_settings(&self) -> Result<Settings> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare("SELECT key, value FROM settings")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        let mut settings = Settings::default();
+
+        for row in rows {
+            let (key, value) = row?;
+            match key.as_str() {
+                "retention_days" => settings.retention_days = value.parse().unwrap_or(30),
+                "max_items" => settings.max_items = value.parse().unwrap_or(1000),
+                "keyboard_shortcut" => settings.keyboard_shortcut = value,
+                "auto_exclude_sensitive" => settings.auto_exclude_sensitive = value == "true",
+                "max_image_size_mb" => settings.max_image_size_mb = value.parse().unwrap_or(5),
+                "snippet_pack_dir" => settings.snippet_pack_dir = value,
+                "trash_retention_days" => settings.trash_retention_days = value.parse().unwrap_or(7),
+                "ranking_weights" => settings.ranking_weights = serde_json::from_str(&value).unwrap_or_default(),
+                "oversized_images" => settings.oversized_images = serde_json::from_str(&value).unwrap_or_default(),
+                "near_duplicate_images" => settings.near_duplicate_images = serde_json::from_str(&value).unwrap_or_default(),
+                _ => {}
+            }
+        }
+
+        Ok(settings)
+    }
+
+    /// Update settings
+    pub fn update_settings(&self, settings: Settings) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.execute("INSERT OR REPLACE INTO settings (key, value) VALUES ('retention_days', ?1)", params![settings.retention_days.to_string()])?;
+        conn.execute("INSERT OR REPLACE INTO settings (key, value) VALUES ('max_items', ?1)", params![settings.max_items.to_string()])?;
+        conn.execute("INSERT OR REPLACE INTO settings (key, value) VALUES ('keyboard_shortcut', ?1)", params![settings.keyboard_shortcut])?;
+        conn.execute("INSERT OR REPLACE INTO settings (key, value) VALUES ('auto_exclude_sensitive', ?1)", params![settings.auto_exclude_sensitive.to_string()])?;
+        conn.execute("INSERT OR REPLACE INTO settings (key, value) VALUES ('max_image_size_mb', ?1)", params![settings.max_image_size_mb.to_string()])?;
+        conn.execute("INSERT OR REPLACE INTO settings (key, value) VALUES ('snippet_pack_dir', ?1)", params![settings.snippet_pack_dir])?;
+        conn.execute("INSERT OR REPLACE INTO settings (key, value) VALUES ('trash_retention_days', ?1)", params![settings.trash_retention_days.to_string()])?;
+        let ranking_weights = serde_json::to_string(&settings.ranking_weights).unwrap_or_default();
+        conn.execute("INSERT OR REPLACE INTO settings (key, value) VALUES ('ranking_weights', ?1)", params![ranking_weights])?;
+        let oversized_images = serde_json::to_string(&settings.oversized_images).unwrap_or_default();
+        conn.execute("INSERT OR REPLACE INTO settings (key, value) VALUES ('oversized_images', ?1)", params![oversized_images])?;
+        let near_duplicate_images = serde_json::to_string(&settings.near_duplicate_images).unwrap_or_default();
+        conn.execute("INSERT OR REPLACE INTO settings (key, value) VALUES ('near_duplicate_images', ?1)", params![near_duplicate_images])?;
+
+        log::info!("Settings updated");
+        Ok(())
+    }
+
+    /// Get app exclusions
+    pub fn get_exclusions(&self) -> Result<Vec<String>> {
         let conn = self.conn.lock().unwrap();
 
         let mut stmt = conn.prepare("SELECT app_name FROM app_exclusions ORDER BY app_name")?;
@@ -439,12 +1928,329 @@ impl Database {
         log::info!("Removed app from exclusion list: {}", app_name);
         Ok(())
     }
+
+    /// Get saved transform chains
+    pub fn get_transform_chains(&self) -> Result<Vec<TransformChain>> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare(
+            "SELECT name, transforms, updated_at FROM transform_chains ORDER BY name"
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, i64>(2)?))
+        })?;
+
+        let mut chains = Vec::new();
+        for row in rows {
+            let (name, transforms_json, updated_at) = row?;
+            let transforms = serde_json::from_str(&transforms_json).unwrap_or_else(|e| {
+                log::warn!("Ignoring malformed transform chain {}: {}", name, e);
+                Vec::new()
+            });
+            chains.push(TransformChain { name, transforms, updated_at });
+        }
+
+        Ok(chains)
+    }
+
+    /// Save (or overwrite) a named transform chain
+    pub fn save_transform_chain(&self, name: String, transforms: Vec<String>) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+
+        let transforms_json = serde_json::to_string(&transforms)
+            .map_err(|e| AppError::InvalidInput(e.to_string()))?;
+        conn.execute(
+            "INSERT OR REPLACE INTO transform_chains (name, transforms, updated_at) VALUES (?1, ?2, ?3)",
+            params![name, transforms_json, chrono::Utc::now().timestamp()],
+        )?;
+
+        log::info!("Saved transform chain: {}", name);
+        Ok(())
+    }
+
+    /// Delete a named transform chain
+    pub fn delete_transform_chain(&self, name: String) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.execute("DELETE FROM transform_chains WHERE name = ?1", params![name])?;
+
+        log::info!("Deleted transform chain: {}", name);
+        Ok(())
+    }
+
+    /// Get snippets, optionally limited to one folder
+    pub fn get_snippets(&self, folder: Option<String>) -> Result<Vec<Snippet>> {
+        let conn = self.conn.lock().unwrap();
+
+        let snippets = match folder {
+            Some(folder) => {
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT {} FROM snippets WHERE folder = ?1 ORDER BY name COLLATE NOCASE",
+                    SNIPPET_COLUMNS
+                ))?;
+                let rows = stmt.query_map(params![folder], row_to_snippet)?
+                    .collect::<std::result::Result<Vec<_>, _>>()?;
+                rows
+            }
+            None => {
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT {} FROM snippets ORDER BY folder COLLATE NOCASE, name COLLATE NOCASE",
+                    SNIPPET_COLUMNS
+                ))?;
+                let rows = stmt.query_map([], row_to_snippet)?
+                    .collect::<std::result::Result<Vec<_>, _>>()?;
+                rows
+            }
+        };
+
+        Ok(snippets)
+    }
+
+    /// Get a single snippet by ID
+    pub fn get_snippet(&self, id: i64) -> Result<Snippet> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.query_row(
+            &format!("SELECT {} FROM snippets WHERE id = ?1", SNIPPET_COLUMNS),
+            params![id],
+            row_to_snippet,
+        ).map_err(|_| AppError::NotFound(id))
+    }
+
+    /// Create a snippet and return its ID
+    pub fn create_snippet(&self, input: SnippetInput) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+        let now = chrono::Utc::now().timestamp();
+
+        conn.execute(
+            "INSERT INTO snippets (name, abbreviation, folder, content, tags, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6)",
+            params![input.name, input.abbreviation, input.folder, input.content, tags_to_json(&input.tags), now],
+        ).map_err(map_snippet_write_error)?;
+
+        let id = conn.last_insert_rowid();
+        log::debug!("Created snippet: id={}", id);
+        Ok(id)
+    }
+
+    /// Update a snippet in place
+    pub fn update_snippet(&self, id: i64, input: SnippetInput) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        ensure_user_snippet(&conn, id)?;
+
+        conn.execute(
+            "UPDATE snippets SET name = ?1, abbreviation = ?2, folder = ?3, content = ?4, tags = ?5, updated_at = ?6
+             WHERE id = ?7",
+            params![
+                input.name,
+                input.abbreviation,
+                input.folder,
+                input.content,
+                tags_to_json(&input.tags),
+                chrono::Utc::now().timestamp(),
+                id
+            ],
+        ).map_err(map_snippet_write_error)?;
+
+        Ok(())
+    }
+
+    /// Delete snippet by ID
+    pub fn delete_snippet(&self, id: i64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        ensure_user_snippet(&conn, id)?;
+
+        conn.execute("DELETE FROM snippets WHERE id = ?1", params![id])?;
+
+        Ok(())
+    }
+
+    /// Search snippets (user and pack) with FTS5
+    pub fn search_snippets(&self, query: String, limit: u32) -> Result<Vec<Snippet>> {
+        let conn = self.conn.lock().unwrap();
+
+        let columns: Vec<String> = SNIPPET_COLUMNS.split(", ").map(|c| format!("s.{}", c)).collect();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM snippets s
+             JOIN snippets_fts fts ON s.id = fts.rowid
+             WHERE snippets_fts MATCH ?1
+             ORDER BY fts.rank
+             LIMIT ?2",
+            columns.join(", ")
+        ))?;
+        let snippets = stmt.query_map(params![query, limit], row_to_snippet)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(snippets)
+    }
+
+    /// Replace the snippets loaded from one pack file, keeping IDs stable by name.
+    /// Returns how many snippets were stored (duplicates skipped), and warnings
+    /// for snippets that could not be stored as written.
+    pub fn sync_pack_snippets(&self, pack_path: &str, snippets: Vec<SnippetInput>) -> Result<(usize, Vec<String>)> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let now = chrono::Utc::now().timestamp();
+        let mut warnings = Vec::new();
+        let mut kept_ids: Vec<i64> = Vec::new();
+
+        for snippet in snippets {
+            let existing_id: Option<i64> = tx.query_row(
+                "SELECT id FROM snippets WHERE pack_path = ?1 AND name = ?2",
+                params![pack_path, snippet.name],
+                |row| row.get(0),
+            ).ok();
+
+            if existing_id.is_some_and(|id| kept_ids.contains(&id)) {
+                warnings.push(format!("Duplicate snippet name '{}' skipped", snippet.name));
+                continue;
+            }
+
+            let mut abbreviation = snippet.abbreviation.clone();
+            loop {
+                let result = match existing_id {
+                    Some(id) => tx.execute(
+                        "UPDATE snippets SET abbreviation = ?1, folder = ?2, content = ?3, tags = ?4, updated_at = ?5
+                         WHERE id = ?6",
+                        params![abbreviation, snippet.folder, snippet.content, tags_to_json(&snippet.tags), now, id],
+                    ).map(|_| id),
+                    None => tx.execute(
+                        "INSERT INTO snippets (name, abbreviation, folder, content, tags, pack_path, created_at, updated_at)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?7)",
+                        params![snippet.name, abbreviation, snippet.folder, snippet.content, tags_to_json(&snippet.tags), pack_path, now],
+                    ).map(|_| tx.last_insert_rowid()),
+                };
+
+                match result {
+                    Ok(id) => {
+                        kept_ids.push(id);
+                        break;
+                    }
+                    // Abbreviation clash with another snippet: keep the snippet, drop the trigger
+                    Err(e) if is_constraint_violation(&e) && abbreviation.is_some() => {
+                        warnings.push(format!(
+                            "Abbreviation '{}' of '{}' is already in use; loaded without it",
+                            abbreviation.take().unwrap_or_default(),
+                            snippet.name
+                        ));
+                    }
+                    Err(e) => return Err(e.into()),
+                }
+            }
+        }
+
+        // Drop snippets that were removed from the file
+        let existing: Vec<i64> = {
+            let mut stmt = tx.prepare("SELECT id FROM snippets WHERE pack_path = ?1")?;
+            let ids = stmt.query_map(params![pack_path], |row| row.get(0))?
+                .collect::<std::result::Result<Vec<i64>, _>>()?;
+            ids
+        };
+        for id in existing.into_iter().filter(|id| !kept_ids.contains(id)) {
+            tx.execute("DELETE FROM snippets WHERE id = ?1", params![id])?;
+        }
+
+        tx.commit()?;
+        Ok((kept_ids.len(), warnings))
+    }
+
+    /// Remove snippets from pack files that are no longer present
+    pub fn remove_pack_snippets_except(&self, pack_paths: &[String]) -> Result<u64> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare("SELECT DISTINCT pack_path FROM snippets WHERE pack_path IS NOT NULL")?;
+        let stale: Vec<String> = stmt.query_map([], |row| row.get(0))?
+            .collect::<std::result::Result<Vec<String>, _>>()?
+            .into_iter()
+            .filter(|path| !pack_paths.contains(path))
+            .collect();
+
+        let mut removed = 0;
+        for path in stale {
+            removed += conn.execute("DELETE FROM snippets WHERE pack_path = ?1", params![path])?;
+        }
+
+        if removed > 0 {
+            log::info!("Removed {} snippets from deleted pack files", removed);
+        }
+
+        Ok(removed as u64)
+    }
+
+    /// Fuzzy-match snippets by abbreviation (and, with lower weight, by name)
+    pub fn find_snippets(&self, query: &str, limit: u32) -> Result<Vec<Snippet>> {
+        let snippets = self.get_snippets(None)?;
+
+        let mut scored: Vec<(i64, Snippet)> = snippets
+            .into_iter()
+            .filter_map(|snippet| {
+                let abbreviation_score = snippet
+                    .abbreviation
+                    .as_deref()
+                    .and_then(|abbr| fuzzy_score(query, abbr))
+                    .map(|score| score * 2);
+                let name_score = fuzzy_score(query, &snippet.name);
+                abbreviation_score.max(name_score).map(|score| (score, snippet))
+            })
+            .collect();
+
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.name.cmp(&b.1.name)));
+
+        Ok(scored
+            .into_iter()
+            .take(limit as usize)
+            .map(|(_, snippet)| snippet)
+            .collect())
+    }
+}
+
+/// Insert a text item in the "misc" category, hashed by its text
+#[cfg(test)]
+pub(crate) fn insert_text(db: &Database, text: &str, app: &str, copied_at: i64) -> i64 {
+    insert_categorized(db, text, "misc", app, copied_at)
+}
+
+/// Insert a text item in `category`, hashed by its text
+#[cfg(test)]
+pub(crate) fn insert_categorized(db: &Database, text: &str, category: &str, app: &str, copied_at: i64) -> i64 {
+    db.insert_item(
+        text.to_string(),
+        "text".to_string(),
+        None,
+        category.to_string(),
+        app.to_string(),
+        false,
+        format!("hash_{}", text),
+        text.to_string(),
+        copied_at,
+    )
+    .expect("insert")
+}
+
+/// Insert an image item stored at `path`
+#[cfg(test)]
+pub(crate) fn insert_image(db: &Database, path: &std::path::Path, hash: &str) -> i64 {
+    db.insert_item(
+        "Image".to_string(),
+        "image".to_string(),
+        Some(path.to_string_lossy().to_string()),
+        "misc".to_string(),
+        "Tests".to_string(),
+        false,
+        hash.to_string(),
+        "Image".to_string(),
+        1,
+    )
+    .expect("insert")
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Database;
-    use crate::models::Settings;
+    use super::{insert_categorized, insert_image, insert_text, Database};
+    use crate::models::{
+        ContextCursor, ContextReason, ImageMetadata, PageCursor, PageRequest, SearchFilters, SearchMode, SearchSort, Settings,
+        SnippetInput, StatsBucket, StoredImageFormat,
+    };
 
     #[test]
     fn test_image_path_exists_uses_exact_db_membership() {
@@ -529,9 +2335,910 @@ mod tests {
         )
         .expect("insert newest text");
 
-        let history = db.get_history(10, 0).expect("get history");
+        let history = db.get_history(PageRequest::first(10)).expect("get history");
         assert_eq!(history.len(), 2);
         assert!(!old_image.exists(), "old image should be deleted with excess row");
         assert!(new_image.exists(), "new image should remain on disk");
     }
+
+    #[test]
+    fn test_transform_chains_roundtrip() {
+        let temp_dir = tempfile::tempdir().expect("tempdir");
+        let db = Database::new(temp_dir.path()).expect("db init");
+
+        db.save_transform_chain(
+            "clean".to_string(),
+            vec!["trim".to_string(), "collapse_whitespace".to_string()],
+        )
+        .expect("save chain");
+        db.save_transform_chain("clean".to_string(), vec!["trim".to_string()])
+            .expect("overwrite chain");
+
+        let chains = db.get_transform_chains().expect("get chains");
+        assert_eq!(chains.len(), 1);
+        assert_eq!(chains[0].transforms, vec!["trim".to_string()]);
+
+        db.delete_transform_chain("clean".to_string()).expect("delete chain");
+        assert!(db.get_transform_chains().expect("get chains").is_empty());
+    }
+
+    #[test]
+    fn test_derivations_follow_source_order_and_cascade() {
+        let temp_dir = tempfile::tempdir().expect("tempdir");
+        let db = Database::new(temp_dir.path()).expect("db init");
+
+        let mut ids = Vec::new();
+        for (i, text) in ["first", "second", "first\nsecond"].iter().enumerate() {
+            ids.push(
+                db.insert_item(
+                    text.to_string(),
+                    "text".to_string(),
+                    None,
+                    "misc".to_string(),
+                    "Tests".to_string(),
+                    false,
+                    format!("hash_derived_{}", i),
+                    text.to_string(),
+                    i as i64,
+                )
+                .expect("insert"),
+            );
+        }
+
+        db.link_derivations(ids[2], &[ids[1], ids[0]]).expect("link");
+        assert_eq!(db.get_derived_from(ids[2]).expect("sources"), vec![ids[1], ids[0]]);
+
+        db.delete_item(ids[1]).expect("delete source");
+        assert_eq!(db.get_derived_from(ids[2]).expect("sources"), vec![ids[0]]);
+
+        db.restore_item(ids[1]).expect("restore source");
+        db.delete_item(ids[0]).expect("delete source");
+        db.purge_trash(0).expect("purge");
+        assert_eq!(db.get_derived_from(ids[2]).expect("sources"), vec![ids[1]]);
+    }
+
+    #[test]
+    fn test_snippets_survive_history_cleanup() {
+        let temp_dir = tempfile::tempdir().expect("tempdir");
+        let db = Database::new(temp_dir.path()).expect("db init");
+
+        let id = db
+            .create_snippet(SnippetInput {
+                name: "Email signature".to_string(),
+                abbreviation: Some("sig".to_string()),
+                folder: Some("Support".to_string()),
+                content: "Best regards".to_string(),
+                tags: Vec::new(),
+            })
+            .expect("create snippet");
+
+        db.cleanup_expired(1).expect("cleanup");
+        assert_eq!(db.get_snippet(id).expect("snippet").content, "Best regards");
+
+        let duplicate = db.create_snippet(SnippetInput {
+            name: "Other".to_string(),
+            abbreviation: Some("sig".to_string()),
+            folder: None,
+            content: "x".to_string(),
+            tags: Vec::new(),
+        });
+        assert!(duplicate.is_err(), "abbreviations must be unique");
+    }
+
+    #[test]
+    fn test_find_snippets_prefers_abbreviation_match() {
+        let temp_dir = tempfile::tempdir().expect("tempdir");
+        let db = Database::new(temp_dir.path()).expect("db init");
+
+        for (name, abbreviation) in [("Address", "addr"), ("Admin dashboard", "adm"), ("Refund policy", "refund")] {
+            db.create_snippet(SnippetInput {
+                name: name.to_string(),
+                abbreviation: Some(abbreviation.to_string()),
+                folder: None,
+                content: name.to_string(),
+                tags: Vec::new(),
+            })
+            .expect("create snippet");
+        }
+
+        let found = db.find_snippets("ad", 10).expect("find");
+        let names: Vec<_> = found.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names.len(), 2);
+        assert!(names.contains(&"Address") && names.contains(&"Admin dashboard"));
+
+        let found = db.find_snippets("rfd", 10).expect("find");
+        assert_eq!(found[0].name, "Refund policy");
+    }
+
+    #[test]
+    fn test_sync_pack_snippets_is_read_only_and_searchable() {
+        let temp_dir = tempfile::tempdir().expect("tempdir");
+        let db = Database::new(temp_dir.path()).expect("db init");
+
+        let user_id = db
+            .create_snippet(SnippetInput {
+                name: "Mine".to_string(),
+                abbreviation: Some("ty".to_string()),
+                folder: None,
+                content: "thanks".to_string(),
+                tags: Vec::new(),
+            })
+            .expect("create snippet");
+
+        let pack_snippet = |name: &str, content: &str| SnippetInput {
+            name: name.to_string(),
+            abbreviation: Some("ty".to_string()),
+            folder: Some("Support".to_string()),
+            content: content.to_string(),
+            tags: vec!["team".to_string()],
+        };
+
+        let (stored, warnings) = db
+            .sync_pack_snippets(
+                "/packs/support.yaml",
+                vec![pack_snippet("Thank you", "Thanks for reaching out"), pack_snippet("Thank you", "Duplicate")],
+            )
+            .expect("sync");
+        assert_eq!(stored, 1, "the duplicate name is not counted");
+        assert_eq!(warnings.len(), 2, "abbreviation clash and duplicate should be reported");
+
+        let found = db.search_snippets("reaching".to_string(), 10).expect("search");
+        assert_eq!(found.len(), 1);
+        let pack_id = found[0].id;
+        assert_eq!(found[0].pack.as_deref(), Some("/packs/support.yaml"));
+        assert_eq!(found[0].abbreviation, None);
+        assert!(db.delete_snippet(pack_id).is_err(), "pack snippets are read-only");
+
+        // Re-sync keeps the ID for the same name and picks up new content
+        db.sync_pack_snippets("/packs/support.yaml", vec![pack_snippet("Thank you", "Updated reply")])
+            .expect("resync");
+        assert_eq!(db.get_snippet(pack_id).expect("snippet").content, "Updated reply");
+
+        db.remove_pack_snippets_except(&[]).expect("remove stale packs");
+        assert!(db.get_snippet(pack_id).is_err());
+        assert!(db.get_snippet(user_id).is_ok(), "user snippets are not pack-managed");
+    }
+
+    #[test]
+    fn test_collection_items_are_protected_from_cleanup() {
+        let temp_dir = tempfile::tempdir().expect("tempdir");
+        let db = Database::new(temp_dir.path()).expect("db init");
+
+        db.update_settings(Settings {
+            max_items: 2,
+            ..Settings::default()
+        })
+        .expect("update settings");
+
+        let collection_id = db.create_collection("Release checklist".to_string()).expect("create");
+        assert!(db.create_collection("Release checklist".to_string()).is_err());
+
+        let mut ids = Vec::new();
+        for i in 0..2 {
+            ids.push(
+                db.insert_item(
+                    format!("step {}", i),
+                    "text".to_string(),
+                    None,
+                    "misc".to_string(),
+                    "Tests".to_string(),
+                    false,
+                    format!("hash_collection_{}", i),
+                    format!("step {}", i),
+                    i,
+                )
+                .expect("insert"),
+            );
+        }
+        db.add_to_collection(collection_id, ids[1]).expect("add");
+        db.add_to_collection(collection_id, ids[0]).expect("add");
+
+        // Both old items are past retention, and a third insert exceeds max_items
+        db.cleanup_expired(1).expect("cleanup");
+        db.insert_item(
+            "newest".to_string(),
+            "text".to_string(),
+            None,
+            "misc".to_string(),
+            "Tests".to_string(),
+            false,
+            "hash_collection_new".to_string(),
+            "newest".to_string(),
+            chrono::Utc::now().timestamp(),
+        )
+        .expect("insert newest");
+
+        let items = db.get_collection_items(collection_id).expect("collection items");
+        let item_ids: Vec<i64> = items.iter().map(|i| i.id).collect();
+        assert_eq!(item_ids, vec![ids[1], ids[0]], "manual order, nothing cleaned up");
+
+        db.reorder_collection_items(collection_id, &[ids[0], ids[1]]).expect("reorder");
+        let items = db.get_collection_items(collection_id).expect("collection items");
+        assert_eq!(items[0].id, ids[0]);
+
+        let filters = SearchFilters {
+            collection_id: Some(collection_id),
+            ..SearchFilters::default()
+        };
+        assert_eq!(db.search("step".to_string(), filters, SearchMode::Exact, PageRequest::first(10)).expect("search").len(), 2);
+
+        db.delete_collection(collection_id).expect("delete collection");
+        assert!(db.get_collections().expect("collections").is_empty());
+    }
+
+    #[test]
+    fn test_annotations_are_searchable() {
+        let temp_dir = tempfile::tempdir().expect("tempdir");
+        let db = Database::new(temp_dir.path()).expect("db init");
+
+        let id = db
+            .insert_item(
+                "hunter2-7f3a".to_string(),
+                "text".to_string(),
+                None,
+                "misc".to_string(),
+                "Tests".to_string(),
+                false,
+                "hash_annotated_001".to_string(),
+                "hunter2-7f3a".to_string(),
+                1,
+            )
+            .expect("insert");
+
+        db.set_annotation(
+            id,
+            Some("Staging DB".to_string()),
+            Some("password rotation for staging".to_string()),
+        )
+        .expect("annotate");
+
+        let item = db.get_item_by_id(id).expect("item");
+        assert_eq!(item.title.as_deref(), Some("Staging DB"));
+
+        let found = db
+            .search("staging db password rotation".to_string(), SearchFilters::default(), SearchMode::Exact, PageRequest::first(10))
+            .expect("search");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].item.id, id);
+
+        db.set_annotation(id, None, None).expect("clear annotation");
+        let found = db
+            .search("rotation".to_string(), SearchFilters::default(), SearchMode::Exact, PageRequest::first(10))
+            .expect("search");
+        assert!(found.is_empty(), "FTS should drop the cleared note");
+    }
+
+    #[test]
+    fn test_update_item_content_keeps_revisions_and_fts_in_sync() {
+        let temp_dir = tempfile::tempdir().expect("tempdir");
+        let db = Database::new(temp_dir.path()).expect("db init");
+
+        let id = db
+            .insert_item(
+                "kubectl get pods $".to_string(),
+                "text".to_string(),
+                None,
+                "command".to_string(),
+                "Tests".to_string(),
+                false,
+                "hash_edit_v1".to_string(),
+                "kubectl get pods $".to_string(),
+                1,
+            )
+            .expect("insert");
+        db.insert_item(
+            "other".to_string(),
+            "text".to_string(),
+            None,
+            "misc".to_string(),
+            "Tests".to_string(),
+            false,
+            "hash_other".to_string(),
+            "other".to_string(),
+            2,
+        )
+        .expect("insert other");
+
+        db.update_item_content(
+            id,
+            "kubectl describe pods".to_string(),
+            "hash_edit_v2".to_string(),
+            "command".to_string(),
+            "kubectl describe pods".to_string(),
+            false,
+        )
+        .expect("update");
+
+        let revisions = db.get_item_revisions(id).expect("revisions");
+        assert_eq!(revisions.len(), 1);
+        assert_eq!(revisions[0].content, "kubectl get pods $");
+
+        let filters = SearchFilters::default;
+        assert_eq!(db.search("describe".to_string(), filters(), SearchMode::Exact, PageRequest::first(10)).expect("search").len(), 1);
+        assert!(db.search("get".to_string(), filters(), SearchMode::Exact, PageRequest::first(10)).expect("search").is_empty());
+
+        let clash = db.update_item_content(
+            id,
+            "other".to_string(),
+            "hash_other".to_string(),
+            "misc".to_string(),
+            "other".to_string(),
+            false,
+        );
+        assert!(clash.is_err(), "content of another item must be rejected");
+        assert_eq!(db.get_item_revisions(id).expect("revisions").len(), 1, "failed edit is rolled back");
+    }
+
+    #[test]
+    fn test_deleted_items_stay_in_trash_until_purged() {
+        let temp_dir = tempfile::tempdir().expect("tempdir");
+        let db = Database::new(temp_dir.path()).expect("db init");
+
+        let image_path = temp_dir.path().join("trashed.png");
+        std::fs::write(&image_path, b"png").expect("write image");
+        let id = db
+            .insert_item(
+                "[Image]".to_string(),
+                "image".to_string(),
+                Some(image_path.to_string_lossy().to_string()),
+                "misc".to_string(),
+                "Tests".to_string(),
+                false,
+                "hash_trashed".to_string(),
+                "[Image]".to_string(),
+                1,
+            )
+            .expect("insert");
+
+        db.delete_item(id).expect("delete");
+        assert!(db.get_history(PageRequest::first(10)).expect("history").is_empty());
+        assert_eq!(db.get_trash().expect("trash").len(), 1);
+        assert!(db.delete_item(id).is_err(), "already in the trash");
+
+        db.restore_item(id).expect("restore");
+        assert_eq!(db.get_history(PageRequest::first(10)).expect("history").len(), 1);
+
+        db.delete_item(id).expect("delete again");
+        assert_eq!(db.purge_trash(7).expect("purge"), 0, "recently deleted items are kept");
+        assert!(image_path.exists());
+
+        assert_eq!(db.purge_trash(0).expect("purge"), 1);
+        assert!(db.get_trash().expect("trash").is_empty());
+        assert!(!image_path.exists(), "image is removed at purge time");
+    }
+
+    #[test]
+    fn test_cleanup_leaves_trashed_items_to_purge() {
+        let temp_dir = tempfile::tempdir().expect("tempdir");
+        let db = Database::new(temp_dir.path()).expect("db init");
+        db.update_settings(Settings {
+            max_items: 2,
+            ..Settings::default()
+        })
+        .expect("update settings");
+
+        let trashed = insert_text(&db, "trashed", "Tests", 1);
+        db.delete_item(trashed).expect("delete");
+        insert_text(&db, "kept one", "Tests", 2);
+        insert_text(&db, "kept two", "Tests", 3);
+
+        // The trashed row doesn't count toward max_items, so both live items stay
+        assert_eq!(db.get_history(PageRequest::first(10)).expect("history").len(), 2);
+        assert_eq!(db.get_trash().expect("trash").len(), 1);
+
+        // Retention never hard-deletes trashed rows either
+        db.cleanup_expired(0).expect("cleanup");
+        assert_eq!(db.get_trash().expect("trash").len(), 1);
+        assert_eq!(db.purge_trash(0).expect("purge"), 1);
+    }
+
+    #[test]
+    fn test_bulk_update_by_ids_and_search() {
+        use crate::models::{BulkAction, BulkSelection};
+
+        let temp_dir = tempfile::tempdir().expect("tempdir");
+        let db = Database::new(temp_dir.path()).expect("db init");
+
+        let mut ids = Vec::new();
+        for (i, text) in ["deploy script", "deploy notes", "lunch order"].iter().enumerate() {
+            ids.push(
+                db.insert_item(
+                    text.to_string(),
+                    "text".to_string(),
+                    None,
+                    "misc".to_string(),
+                    "Tests".to_string(),
+                    false,
+                    format!("hash_bulk_{}", i),
+                    text.to_string(),
+                    i as i64,
+                )
+                .expect("insert"),
+            );
+        }
+
+        let by_search = BulkSelection::Search { query: "deploy".to_string(), filters: SearchFilters::default() };
+        let changed = db.bulk_update(by_search, &BulkAction::Retag { category: "command".to_string() }).expect("retag");
+        assert_eq!(changed.len(), 2);
+
+        let by_ids = BulkSelection::Ids { ids: vec![ids[0], ids[2], 9999] };
+        assert_eq!(db.bulk_update(by_ids.clone(), &BulkAction::Favorite).expect("favorite").len(), 2);
+        assert!(db.bulk_update(by_ids.clone(), &BulkAction::Favorite).expect("favorite").is_empty(), "already favorite");
+
+        let missing = db.bulk_update(by_ids, &BulkAction::AddToCollection { collection_id: 42 });
+        assert!(missing.is_err());
+
+        let filters = SearchFilters { category: Some("command".to_string()), ..Default::default() };
+        let by_filter = BulkSelection::Search { query: String::new(), filters };
+        assert_eq!(db.bulk_update(by_filter, &BulkAction::Delete).expect("delete").len(), 2);
+        assert_eq!(db.get_history(PageRequest::first(10)).expect("history").len(), 1);
+        assert_eq!(db.get_trash().expect("trash").len(), 2);
+    }
+
+    #[test]
+    fn test_clear_history_keeps_favorites_and_removes_images() {
+        let temp_dir = tempfile::tempdir().expect("tempdir");
+        let db = Database::new(temp_dir.path()).expect("db init");
+
+        let image_path = temp_dir.path().join("cleared.png");
+        std::fs::write(&image_path, b"png").expect("write image");
+        db.insert_item(
+            "[Image]".to_string(),
+            "image".to_string(),
+            Some(image_path.to_string_lossy().to_string()),
+            "misc".to_string(),
+            "Tests".to_string(),
+            false,
+            "hash_cleared".to_string(),
+            "[Image]".to_string(),
+            1,
+        )
+        .expect("insert image");
+        let pinned = db
+            .insert_item(
+                "keep me".to_string(),
+                "text".to_string(),
+                None,
+                "misc".to_string(),
+                "Tests".to_string(),
+                false,
+                "hash_pinned".to_string(),
+                "keep me".to_string(),
+                2,
+            )
+            .expect("insert pinned");
+        db.set_favorite(pinned, true).expect("favorite");
+
+        assert_eq!(db.clear_history().expect("clear").len(), 1);
+        assert!(!image_path.exists());
+        let remaining = db.get_history(PageRequest::first(10)).expect("history");
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].id, pinned);
+    }
+
+    #[test]
+    fn test_search_query_language() {
+        let temp_dir = tempfile::tempdir().expect("tempdir");
+        let db = Database::new(temp_dir.path()).expect("db init");
+
+        for (i, (text, app)) in [("foo-bar baz", "Slack"), ("foo qux", "Terminal"), ("https://example.com", "Safari")]
+            .iter()
+            .enumerate()
+        {
+            db.insert_item(
+                text.to_string(),
+                "text".to_string(),
+                None,
+                "misc".to_string(),
+                app.to_string(),
+                false,
+                format!("hash_query_{}", i),
+                text.to_string(),
+                i as i64,
+            )
+            .expect("insert");
+        }
+
+        let search = |q: &str| db.search(q.to_string(), SearchFilters::default(), SearchMode::Exact, PageRequest::first(10));
+        assert_eq!(search("foo-bar").expect("search").len(), 1);
+        assert_eq!(search("foo -baz").expect("search")[0].item.source_app, "Terminal");
+        assert_eq!(search("app:Slack").expect("search").len(), 1);
+        assert_eq!(search("baz OR qux").expect("search").len(), 2);
+        assert_eq!(search("-foo").expect("search").len(), 1);
+        assert_eq!(search("example.com").expect("search").len(), 1);
+        assert!(search("\"unbalanced").is_err());
+    }
+
+    #[test]
+    fn test_fuzzy_search_merges_substring_and_typo_matches() {
+        let temp_dir = tempfile::tempdir().expect("tempdir");
+        let db = Database::new(temp_dir.path()).expect("db init");
+
+        for (i, text) in ["kubectl get pods", "commit 3f9a2c71e", "会议记录 明天", "docker compose up"].iter().enumerate() {
+            db.insert_item(
+                text.to_string(),
+                "text".to_string(),
+                None,
+                "misc".to_string(),
+                "Tests".to_string(),
+                false,
+                format!("hash_fuzzy_{}", i),
+                text.to_string(),
+                i as i64,
+            )
+            .expect("insert");
+        }
+
+        let search = |q: &str, mode| db.search(q.to_string(), SearchFilters::default(), mode, PageRequest::first(10)).expect("search");
+        assert!(search("kubect", SearchMode::Exact).is_empty());
+        assert_eq!(search("kubect", SearchMode::Fuzzy)[0].item.content, "kubectl get pods");
+        assert_eq!(search("9a2c", SearchMode::Fuzzy).len(), 1);
+        assert_eq!(search("记录", SearchMode::Fuzzy).len(), 0, "trigram needs three characters");
+        assert_eq!(search("会议记", SearchMode::Fuzzy).len(), 1);
+        assert_eq!(search("kubctl", SearchMode::Fuzzy)[0].item.content, "kubectl get pods");
+        assert_eq!(search("dokcer", SearchMode::Fuzzy)[0].item.content, "docker compose up");
+        assert!(search("dokcer -compose", SearchMode::Fuzzy).is_empty(), "exclusions still apply");
+        assert!(search("ls", SearchMode::Fuzzy).is_empty());
+    }
+
+    #[test]
+    fn test_search_hits_carry_snippet_and_byte_offsets() {
+        let temp_dir = tempfile::tempdir().expect("tempdir");
+        let db = Database::new(temp_dir.path()).expect("db init");
+
+        let log = format!("{} ERROR: disk full {}", "ok ".repeat(200), "ok ".repeat(200));
+        db.insert_item(
+            log.clone(),
+            "text".to_string(),
+            None,
+            "error".to_string(),
+            "Tests".to_string(),
+            false,
+            "hash_log".to_string(),
+            log.chars().take(80).collect(),
+            1,
+        )
+        .expect("insert");
+
+        let hits = db
+            .search("disk".to_string(), SearchFilters::default(), SearchMode::Exact, PageRequest::first(10))
+            .expect("search");
+        let snippet = hits[0].snippet.as_deref().expect("snippet");
+        assert!(snippet.contains("\u{2}disk\u{3}"));
+        assert!(snippet.len() < 200, "snippet is context, not the whole log");
+
+        let range = hits[0].matches[0];
+        assert_eq!(&log[range.start..range.end], "disk");
+
+        let filter_only = db
+            .search("cat:error".to_string(), SearchFilters::default(), SearchMode::Exact, PageRequest::first(10))
+            .expect("search");
+        assert_eq!(filter_only[0].snippet, None);
+        assert!(filter_only[0].matches.is_empty());
+    }
+
+    #[test]
+    fn test_relevance_ranking_and_sort_options() {
+        use crate::models::SearchSort;
+
+        let temp_dir = tempfile::tempdir().expect("tempdir");
+        let db = Database::new(temp_dir.path()).expect("db init");
+        let now = chrono::Utc::now().timestamp();
+
+        // Old but strong match vs. fresh passing mention
+        let strong = db
+            .insert_item(
+                "timeout timeout timeout in the payment service".to_string(),
+                "text".to_string(),
+                None,
+                "error".to_string(),
+                "Tests".to_string(),
+                false,
+                "hash_rank_strong".to_string(),
+                "timeout".to_string(),
+                now - 30 * 86400,
+            )
+            .expect("insert");
+        let recent = db
+            .insert_item(
+                "lunch plans, also a timeout somewhere in a very long unrelated message about many other things".to_string(),
+                "text".to_string(),
+                None,
+                "misc".to_string(),
+                "Tests".to_string(),
+                false,
+                "hash_rank_recent".to_string(),
+                "lunch".to_string(),
+                now,
+            )
+            .expect("insert");
+        db.record_paste(strong, None).expect("use");
+        db.record_paste(strong, None).expect("use");
+
+        let ids = |sort| {
+            let filters = SearchFilters { sort: Some(sort), ..Default::default() };
+            db.search("timeout".to_string(), filters, SearchMode::Exact, PageRequest::first(10))
+                .expect("search")
+                .into_iter()
+                .map(|hit| hit.item.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(SearchSort::Newest), vec![recent, strong]);
+        assert_eq!(ids(SearchSort::Oldest), vec![strong, recent]);
+        assert_eq!(ids(SearchSort::MostUsed), vec![strong, recent]);
+
+        // Recency dominates when it is the only signal
+        let mut settings = db.get_settings().expect("settings");
+        settings.ranking_weights.text = 0.0;
+        settings.ranking_weights.usage = 0.0;
+        db.update_settings(settings.clone()).expect("update");
+        assert_eq!(ids(SearchSort::Relevance), vec![recent, strong]);
+
+        settings.ranking_weights = crate::models::RankingWeights { recency: 0.0, ..Default::default() };
+        db.update_settings(settings).expect("update");
+        assert_eq!(ids(SearchSort::Relevance), vec![strong, recent]);
+        assert_eq!(db.get_item_by_id(strong).expect("item").use_count, 2);
+    }
+
+    #[test]
+    fn test_recopy_bumps_item_and_usage_is_tracked() {
+        let temp_dir = tempfile::tempdir().expect("tempdir");
+        let db = Database::new(temp_dir.path()).expect("db init");
+
+        let first = insert_text(&db, "first", "Notes", 100);
+        let second = insert_text(&db, "second", "Notes", 200);
+        assert_eq!(db.get_history(PageRequest::first(10)).expect("history")[0].id, second);
+
+        assert_eq!(insert_text(&db, "first", "Terminal", 300), first);
+        let history = db.get_history(PageRequest::first(10)).expect("history");
+        assert_eq!(history[0].id, first, "re-copied item moves to the top");
+        assert_eq!(history[0].copied_at, 300);
+        assert_eq!(history[0].use_count, 1);
+
+        db.record_paste(second, Some("Slack")).expect("paste");
+        db.record_paste(second, Some("Mail")).expect("paste");
+        assert!(db.record_paste(9999, None).is_err());
+
+        let frequent: Vec<i64> = db.get_frequent_items(10).expect("frequent").iter().map(|i| i.id).collect();
+        assert_eq!(frequent, vec![second, first]);
+
+        let events = db.get_usage_events(first).expect("events");
+        let apps: Vec<(&str, Option<&str>)> = events.iter().map(|e| (e.kind.as_str(), e.app.as_deref())).collect();
+        assert_eq!(apps, vec![("capture", Some("Terminal")), ("capture", Some("Notes"))]);
+    }
+
+    #[test]
+    fn test_history_for_app_boosts_items_used_in_that_app() {
+        let temp_dir = tempfile::tempdir().expect("tempdir");
+        let db = Database::new(temp_dir.path()).expect("db init");
+        let now = chrono::Utc::now().timestamp();
+
+        let pasted = insert_categorized(&db, "git status", "command", "Notes", now - 7 * 86400);
+        let command = insert_categorized(&db, "ls -la", "command", "Notes", now - 6 * 86400);
+        let from_terminal = insert_categorized(&db, "hello", "misc", "Terminal", now - 5 * 86400);
+        db.record_paste(pasted, Some("Terminal")).expect("paste");
+        db.record_paste(pasted, Some("Terminal")).expect("paste");
+        // Newer unrelated items push the boosted ones off the first plain page
+        let recent: Vec<i64> = (0..3).map(|i| insert_categorized(&db, &format!("https://example.com/{}", i), "url", "Safari", now - 60 * i)).collect();
+
+        let first = db.get_history_for_app("Terminal", None, 4).expect("history");
+        let order: Vec<(i64, Option<ContextReason>)> = first.iter().map(|e| (e.item.id, e.reason)).collect();
+        assert_eq!(
+            order,
+            vec![
+                (pasted, Some(ContextReason::PastedIntoApp)),
+                (from_terminal, Some(ContextReason::CopiedFromApp)),
+                (command, Some(ContextReason::FrequentCategory)),
+                (recent[0], None),
+            ]
+        );
+
+        // Later pages continue plain history without the boosted items
+        let cursor = ContextCursor::decode(&ContextCursor::after_page(None, &first).encode()).expect("cursor");
+        assert_eq!(cursor.boosted, vec![pasted, from_terminal, command]);
+        let rest: Vec<i64> = db.get_history_for_app("Terminal", Some(&cursor), 4).expect("history")
+            .iter().map(|e| e.item.id).collect();
+        assert_eq!(rest, vec![recent[1], recent[2]]);
+
+        // An app without history keeps plain recency order
+        let ids: Vec<i64> = db.get_history_for_app("Safari Technology Preview", None, 10).expect("history")
+            .iter().map(|e| e.item.id).collect();
+        assert_eq!(ids, vec![recent[0], recent[1], recent[2], from_terminal, command, pasted]);
+    }
+
+    #[test]
+    fn test_stats_are_aggregated_in_sql_and_filter_by_date() {
+        let temp_dir = tempfile::tempdir().expect("tempdir");
+        let db = Database::new(temp_dir.path()).expect("db init");
+        let day = 86_400;
+
+        let url = insert_categorized(&db, "https://example.com", "url", "Safari", 10 * day + 100);
+        insert_categorized(&db, "git status", "command", "Terminal", 10 * day + 200);
+        insert_categorized(&db, "ls", "command", "Terminal", 11 * day + 300);
+        insert_text(&db, "héllo", "Notes", 12 * day);
+        let trashed = insert_text(&db, "gone", "Notes", 12 * day + 50);
+        db.set_favorite(url, true).expect("favorite");
+        db.delete_item(trashed).expect("delete");
+        insert_categorized(&db, "ls", "command", "Terminal", 11 * day + 400); // re-copy counts as a capture
+        db.record_skipped_capture("sensitive", "1Password", 11 * day).expect("skip");
+
+        let stats = db.get_stats(None, None, StatsBucket::Day, 0).expect("stats");
+        assert_eq!(stats.total_items, 4);
+        assert_eq!(stats.favorite_count, 1);
+        assert_eq!(stats.sensitive_skipped, 1);
+        assert_eq!(stats.text_bytes, 19 + 10 + 2 + 6);
+        assert_eq!(stats.image_bytes, 0);
+        let categories: Vec<(&str, i64)> = stats.by_category.iter().map(|c| (c.key.as_str(), c.count)).collect();
+        assert_eq!(categories, vec![("command", 2), ("misc", 1), ("url", 1)]);
+        assert_eq!(stats.by_source_app[0].key, "Terminal");
+        assert_eq!(stats.by_content_type[0].key, "text");
+        let days: Vec<(i64, i64)> = stats.by_time.iter().map(|t| (t.start, t.count)).collect();
+        assert_eq!(days, vec![(10 * day, 2), (11 * day, 1), (12 * day, 1)]);
+        let captures: Vec<(i64, i64)> = stats.capture_rate.iter().map(|t| (t.start, t.count)).collect();
+        assert_eq!(captures, vec![(10 * day, 2), (11 * day, 2), (12 * day, 2)]);
+
+        // Date range and hourly buckets shifted to a UTC+1 local clock
+        let stats = db.get_stats(Some(11 * day), Some(12 * day), StatsBucket::Hour, 3600).expect("stats");
+        assert_eq!(stats.total_items, 2);
+        assert_eq!(stats.sensitive_skipped, 1);
+        let hours: Vec<(i64, i64)> = stats.by_time.iter().map(|t| (t.start, t.count)).collect();
+        assert_eq!(hours, vec![(11 * day, 1), (12 * day, 1)]);
+    }
+
+    #[test]
+    fn test_stats_image_bytes_use_recorded_sizes_and_fall_back_to_disk() {
+        let temp_dir = tempfile::tempdir().expect("tempdir");
+        let db = Database::new(temp_dir.path()).expect("db init");
+
+        // The recorded size wins over the file, which is never read
+        let recorded = insert_image(&db, &temp_dir.path().join("missing.png"), "hash_recorded");
+        db.set_image_metadata(
+            recorded,
+            &ImageMetadata {
+                original_width: 10,
+                original_height: 10,
+                width: 10,
+                height: 10,
+                format: StoredImageFormat::Png,
+                byte_size: 1234,
+            },
+        )
+        .expect("metadata");
+
+        // Items captured before metadata existed are measured on disk
+        let legacy_path = temp_dir.path().join("legacy.png");
+        std::fs::write(&legacy_path, [0u8; 56]).expect("write image");
+        insert_image(&db, &legacy_path, "hash_legacy");
+
+        let stats = db.get_stats(None, None, StatsBucket::Day, 0).expect("stats");
+        assert_eq!(stats.image_bytes, 1234 + 56);
+    }
+
+    #[test]
+    fn test_list_items_measure_content_in_sql() {
+        let temp_dir = tempfile::tempdir().expect("tempdir");
+        let db = Database::new(temp_dir.path()).expect("db init");
+
+        for (i, text) in ["héllo\nwörld\n", "one line", ""].iter().enumerate() {
+            insert_text(&db, text, "Notes", 100 - i as i64);
+        }
+
+        let measured: Vec<(usize, usize)> = db
+            .get_history(PageRequest::first(10))
+            .expect("history")
+            .iter()
+            .map(|item| (item.content_length, item.line_count))
+            .collect();
+        // Characters, not bytes; lines as `str::split('\n')` counts them
+        assert_eq!(measured, vec![(12, 3), (8, 1), (0, 1)]);
+    }
+
+    #[test]
+    fn test_keyset_pages_are_stable_when_items_arrive() {
+        let temp_dir = tempfile::tempdir().expect("tempdir");
+        let db = Database::new(temp_dir.path()).expect("db init");
+
+        // Two items share a timestamp, so the id breaks the tie
+        let ids: Vec<i64> = (0..5).map(|i| insert_text(&db, &format!("note {}", i), "Notes", 100 + i.min(3))).collect();
+        db.set_favorite(ids[0], true).expect("favorite");
+
+        let first = db.get_history(PageRequest::first(2)).expect("page");
+        assert_eq!(first.iter().map(|i| i.id).collect::<Vec<_>>(), vec![ids[0], ids[4]]);
+
+        // A new copy lands at the top and must not shift the next page
+        insert_text(&db, "note new", "Notes", 500);
+        let cursor = PageCursor::decode(&PageCursor::after(&first[1]).encode()).expect("cursor");
+        let next = db.get_history(PageRequest { after: Some(cursor), limit: 10 }).expect("page");
+        assert_eq!(next.iter().map(|i| i.id).collect::<Vec<_>>(), vec![ids[3], ids[2], ids[1]]);
+        assert!(PageCursor::decode("not a cursor").is_err());
+
+        // Search pages by cursor for chronological sorts, across fuzzy passes
+        let filters = SearchFilters { sort: Some(SearchSort::Newest), ..Default::default() };
+        let page = db.search("note".to_string(), filters.clone(), SearchMode::Fuzzy, PageRequest::first(3)).expect("search");
+        let seen: Vec<i64> = page.iter().map(|hit| hit.item.id).collect();
+        let after = Some(PageCursor::after(&page[2].clone().into_list_hit().item));
+        let rest = db.search("note".to_string(), filters, SearchMode::Fuzzy, PageRequest { after, limit: 10 }).expect("search");
+        let mut all: Vec<i64> = seen.into_iter().chain(rest.iter().map(|hit| hit.item.id)).collect();
+        all.sort();
+        all.dedup();
+        assert_eq!(all.len(), 6, "every match appears exactly once");
+
+        let ranked = PageRequest { after, limit: 10 };
+        assert!(db.search("note".to_string(), SearchFilters::default(), SearchMode::Fuzzy, ranked).is_err());
+    }
+
+    #[test]
+    fn test_content_chunks_split_on_character_boundaries() {
+        let temp_dir = tempfile::tempdir().expect("tempdir");
+        let db = Database::new(temp_dir.path()).expect("db init");
+
+        let content = "héllo wörld — ünïcode ✓ ".repeat(50);
+        let id = db
+            .insert_item(
+                content.clone(),
+                "text".to_string(),
+                None,
+                "misc".to_string(),
+                "Notes".to_string(),
+                false,
+                "hash_chunks".to_string(),
+                "héllo".to_string(),
+                100,
+            )
+            .expect("insert");
+
+        let mut assembled = String::new();
+        let mut offset = Some(0);
+        let mut chunks = 0;
+        while let Some(at) = offset {
+            let chunk = db.get_content_chunk(id, at, 7).expect("chunk");
+            assert!(chunk.text.len() <= 7);
+            assert_eq!(chunk.total_bytes, content.len());
+            assembled.push_str(&chunk.text);
+            offset = chunk.next_offset;
+            chunks += 1;
+        }
+        assert_eq!(assembled, content);
+        assert!(chunks > content.len() / 7);
+
+        assert!(db.get_content_chunk(id, 2, 7).is_err(), "inside the two-byte 'é'");
+        assert!(db.get_content_chunk(id, content.len() + 1, 7).is_err());
+        assert!(db.get_content_chunk(9999, 0, 7).is_err());
+        let empty_tail = db.get_content_chunk(id, content.len(), 7).expect("end");
+        assert_eq!((empty_tail.text.as_str(), empty_tail.next_offset), ("", None));
+    }
+
+    #[test]
+    fn test_near_duplicate_images_are_found_by_dhash_distance() {
+        let temp_dir = tempfile::tempdir().expect("tempdir");
+        let db = Database::new(temp_dir.path()).expect("db init");
+
+        let original = insert_image(&db, std::path::Path::new("/tmp/original.png"), "hash_dhash_original");
+        let different = insert_image(&db, std::path::Path::new("/tmp/different.png"), "hash_dhash_different");
+        db.set_image_dhash(original, 0xF0F0_F0F0_F0F0_F0F0, None).expect("dhash");
+        db.set_image_dhash(different, 0x0F0F_0F0F_0F0F_0F0F, None).expect("dhash");
+
+        // Three bits off the original
+        let tweaked_hash = 0xF0F0_F0F0_F0F0_F0F7;
+        assert_eq!(db.find_near_duplicate(tweaked_hash, 5).expect("find"), Some((original, 3)));
+        assert_eq!(db.find_near_duplicate(tweaked_hash, 2).expect("find"), None);
+
+        let linked = insert_image(&db, std::path::Path::new("/tmp/linked.png"), "hash_dhash_linked");
+        db.set_image_dhash(linked, tweaked_hash, Some(original)).expect("dhash");
+        db.set_image_dhash(linked, 0, None).expect("kept first hash");
+
+        let similar = db.find_similar_images(original, 5).expect("similar");
+        let found: Vec<(i64, u32, bool)> = similar.iter().map(|s| (s.item.id, s.distance, s.linked)).collect();
+        assert_eq!(found, vec![(linked, 3, true)]);
+
+        // Links are reported even when the threshold is tightened later
+        let similar = db.find_similar_images(linked, 0).expect("similar");
+        let found: Vec<(i64, u32, bool)> = similar.iter().map(|s| (s.item.id, s.distance, s.linked)).collect();
+        assert_eq!(found, vec![(original, 3, true)]);
+
+        db.merge_capture(original, "Safari", 200).expect("merge");
+        let item = db.get_item_by_id(original).expect("item");
+        assert_eq!((item.copied_at, item.use_count), (200, 1));
+        assert!(db.find_similar_images(9999, 5).is_err());
+    }
 }
@@ -0,0 +1,125 @@
+/// Score how well `query` fuzzily matches `candidate` (case-insensitive).
+///
+/// Every query character must appear in order in the candidate. Exact and
+/// prefix matches rank highest; consecutive runs and word-start hits add
+/// points, gaps subtract. Returns `None` when there is no match.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let query: Vec<char> = query.to_lowercase().chars().collect();
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    if query == candidate {
+        return Some(1000);
+    }
+
+    let mut score: i64 = 0;
+    let mut query_idx = 0;
+    let mut last_match: Option<usize> = None;
+
+    for (idx, &c) in candidate.iter().enumerate() {
+        if query_idx == query.len() {
+            break;
+        }
+        if c != query[query_idx] {
+            continue;
+        }
+
+        score += 10;
+        match last_match {
+            Some(last) if last + 1 == idx => score += 15,
+            Some(last) => score -= (idx - last - 1) as i64,
+            None => score -= idx as i64,
+        }
+
+        let at_word_start = idx == 0 || !candidate[idx - 1].is_alphanumeric();
+        if at_word_start {
+            score += 10;
+        }
+
+        last_match = Some(idx);
+        query_idx += 1;
+    }
+
+    if query_idx < query.len() {
+        return None;
+    }
+
+    if candidate.starts_with(&query) {
+        score += 100;
+    }
+
+    Some(score)
+}
+
+/// True when `a` can be turned into `b` with at most `max` single-character
+/// insertions, deletions, substitutions or adjacent swaps (case-sensitive).
+pub fn within_edit_distance(a: &str, b: &str, max: usize) -> bool {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    if a.len().abs_diff(b.len()) > max {
+        return false;
+    }
+
+    // Optimal string alignment distance, keeping the last three rows
+    let mut two_back: Vec<usize> = Vec::new();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            row[j] = (prev[j] + 1).min(row[j - 1] + 1).min(prev[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(two_back[j - 2] + 1);
+            }
+        }
+        if row.iter().min().copied().unwrap_or(0) > max {
+            return false;
+        }
+        two_back = std::mem::replace(&mut prev, row);
+    }
+
+    prev[b.len()] <= max
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fuzzy_score, within_edit_distance};
+
+    #[test]
+    fn test_requires_in_order_subsequence() {
+        assert!(fuzzy_score("sig", "signature").is_some());
+        assert!(fuzzy_score("sgn", "signature").is_some());
+        assert!(fuzzy_score("gis", "signature").is_none());
+        assert!(fuzzy_score("sigx", "sig").is_none());
+    }
+
+    #[test]
+    fn test_ranks_exact_then_prefix_then_scattered() {
+        let exact = fuzzy_score("addr", "addr").unwrap();
+        let prefix = fuzzy_score("addr", "address").unwrap();
+        let scattered = fuzzy_score("addr", "a_dear_driver").unwrap();
+
+        assert!(exact > prefix);
+        assert!(prefix > scattered);
+    }
+
+    #[test]
+    fn test_is_case_insensitive() {
+        assert_eq!(fuzzy_score("SIG", "sig"), Some(1000));
+    }
+
+    #[test]
+    fn test_edit_distance_allows_small_typos() {
+        assert!(within_edit_distance("kubectl", "kubectl", 0));
+        assert!(within_edit_distance("kubctl", "kubectl", 1));
+        assert!(within_edit_distance("kuebctl", "kubectl", 1), "adjacent swap counts once");
+        assert!(within_edit_distance("kubecrl", "kubectl", 1));
+        assert!(!within_edit_distance("kbuecrl", "kubectl", 1));
+        assert!(!within_edit_distance("docker", "kubectl", 2));
+    }
+}
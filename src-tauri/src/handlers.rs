@@ -1,16 +1,39 @@
-use crate::clipmon::ClipboardMonitor;
+use crate::categorizer::{detect_category, CATEGORIES};
+use crate::clipmon::{build_preview, compute_image_hash, compute_text_hash, ClipboardMonitor};
 use crate::db::Database;
 use crate::error::{AppError, Result};
-use crate::models::{ClipboardItem, SearchFilters, Settings};
+use crate::models::{
+    BulkAction, BulkResult, BulkSelection, ClipboardItem, ClipboardListItem, ClipboardStats,
+    Collection, ContentChunk, ContextCursor, HistoryEntry, HistoryPage, ItemDetail, ItemRevision,
+    JoinResult, PackDiagnostic, PageCursor, PageRequest, QueueOrder, QueueState,
+    SearchFilters, SearchHit, SearchMode, SearchResults, Settings, SimilarImage, Snippet,
+    SnippetInput, StatsBucket, TemplateExpansion, TransformChain, TransformInfo, UsageEvent,
+};
+use crate::paste_queue::PasteQueue;
+use crate::platform::get_frontmost_app;
+use crate::query;
+use crate::sensitive::is_sensitive;
+use crate::snippet_packs::SnippetPackWatcher;
+use crate::templates::{self, Placeholder, TemplateContext};
+use crate::transforms;
 use arboard::Clipboard;
 use std::borrow::Cow;
-use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
 use tauri::State;
 
+/// Content is sent to the detail view in chunks of this many bytes, so a
+/// multi-megabyte log never travels as one IPC payload
+const CONTENT_CHUNK_BYTES: usize = 256 * 1024;
+
 pub struct AppState {
     pub db: Arc<Database>,
     pub monitor: Arc<ClipboardMonitor>,
+    pub queue: Arc<PasteQueue>,
+    pub packs: Arc<SnippetPackWatcher>,
+    /// Frontmost app when the window was last shown, used to order history
+    pub opened_from: Arc<Mutex<Option<String>>>,
 }
 
 fn decode_png_for_clipboard(image_path: &str) -> Result<arboard::ImageData<'static>> {
@@ -34,28 +57,104 @@ fn decode_png_for_clipboard(image_path: &str) -> Result<arboard::ImageData<'stat
     })
 }
 
-fn canonicalize_requested_image_path(image_path: &str, images_dir: &Path) -> Result<PathBuf> {
-    let canonical_path = Path::new(image_path)
-        .canonicalize()
-        .map_err(|_| AppError::InvalidInput("Image file not found".to_string()))?;
-    let canonical_images_dir = images_dir.canonicalize()?;
+fn normalize_collection_name(name: String) -> Result<String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(AppError::InvalidInput("Collection name cannot be empty".to_string()));
+    }
+    Ok(name)
+}
 
-    if !canonical_path.starts_with(&canonical_images_dir) {
-        return Err(AppError::InvalidInput(
-            "Invalid image path: outside images directory".to_string(),
-        ));
+/// Store edited text, recomputing derived fields exactly as the monitor does on capture.
+fn apply_content_edit(db: &Database, id: i64, content: String) -> Result<ClipboardItem> {
+    if content.is_empty() {
+        return Err(AppError::InvalidInput("Item content cannot be empty".to_string()));
+    }
+
+    let hash = compute_text_hash(&content);
+    let category = detect_category(&content);
+    let preview = build_preview(&content);
+    let is_sens = is_sensitive(&content);
+
+    db.update_item_content(id, content, hash, category, preview, is_sens)?;
+    db.get_item_by_id(id)
+}
+
+/// Pages must hold at least one row, or the cursor would never advance
+fn check_page_limit(limit: u32) -> Result<()> {
+    if limit == 0 {
+        return Err(AppError::InvalidInput("Page limit must be at least 1".to_string()));
     }
+    Ok(())
+}
 
-    Ok(canonical_path)
+/// Decode the cursor a previous page returned
+fn page_request(limit: u32, cursor: Option<String>) -> Result<PageRequest> {
+    check_page_limit(limit)?;
+    let after = cursor.as_deref().map(PageCursor::decode).transpose()?;
+    Ok(PageRequest { after, limit })
 }
 
 #[tauri::command]
 pub async fn get_history(
     state: State<'_, AppState>,
     limit: u32,
-    offset: u32,
-) -> Result<Vec<ClipboardItem>> {
-    state.db.get_history(limit, offset)
+    cursor: Option<String>,
+) -> Result<HistoryPage> {
+    check_page_limit(limit)?;
+    let context_app = state.opened_from.lock().unwrap().clone();
+
+    let (items, next_cursor) = match &context_app {
+        Some(app) => {
+            let cursor = cursor.as_deref().map(ContextCursor::decode).transpose()?;
+            let items = state.db.get_history_for_app(app, cursor.as_ref(), limit)?;
+            let next_cursor = (items.len() == limit as usize)
+                .then(|| ContextCursor::after_page(cursor.as_ref(), &items).encode());
+            (items, next_cursor)
+        }
+        None => {
+            let items: Vec<HistoryEntry> = state
+                .db
+                .get_history(page_request(limit, cursor)?)?
+                .into_iter()
+                .map(|item| HistoryEntry { item, reason: None })
+                .collect();
+            let next_cursor = match items.last() {
+                Some(last) if items.len() == limit as usize => Some(PageCursor::after(&last.item).encode()),
+                _ => None,
+            };
+            (items, next_cursor)
+        }
+    };
+
+    Ok(HistoryPage { context_app, items, next_cursor })
+}
+
+/// One page of search hits and the cursor for the next. Chronological sorts
+/// page by keyset. Relevance and most-used scores drift as items age, so no
+/// key orders ranked hits stably between requests: ranked sorts return only
+/// the best `limit` hits and no cursor, and `Database::search` rejects one.
+fn search_page(
+    db: &Database,
+    query: String,
+    filters: SearchFilters,
+    mode: SearchMode,
+    limit: u32,
+    cursor: Option<String>,
+) -> Result<(Vec<SearchHit<ClipboardListItem>>, Option<String>)> {
+    let sort = filters.sort.unwrap_or_default();
+    let hits: Vec<_> = db
+        .search(query, filters, mode, page_request(limit, cursor)?)?
+        .into_iter()
+        .map(SearchHit::into_list_hit)
+        .collect();
+    let next_cursor = match hits.last() {
+        Some(last) if sort.is_chronological() && hits.len() == limit as usize => {
+            Some(PageCursor::after(&last.item).encode())
+        }
+        _ => None,
+    };
+    Ok((hits, next_cursor))
 }
 
 #[tauri::command]
@@ -64,45 +163,443 @@ pub async fn search(
     query: String,
     filters: SearchFilters,
     limit: u32,
-) -> Result<Vec<ClipboardItem>> {
-    state.db.search(query, filters, limit)
+    mode: Option<SearchMode>,
+    cursor: Option<String>,
+) -> Result<SearchResults> {
+    let parsed = query::parse(&query, chrono::Local::now())?;
+
+    // Item-only filters (category, app, type, dates, collection, favorite) don't apply to snippets
+    let item_filters_set = !filters.is_empty() || !parsed.filters.is_empty();
+    let first_page = cursor.is_none();
+
+    let (items, next_cursor) = search_page(&state.db, query, filters, mode.unwrap_or_default(), limit, cursor)?;
+    // Snippets are not paged; they come with the first page only
+    let snippets = match parsed.fts_expression() {
+        Some(expr) if !item_filters_set && first_page => state.db.search_snippets(expr, limit)?,
+        _ => Vec::new(),
+    };
+
+    Ok(SearchResults { items, snippets, next_cursor })
 }
 
+/// Full item for the detail view, with the first chunk of its content
 #[tauri::command]
-pub async fn copy_to_clipboard(
+pub async fn get_item(state: State<'_, AppState>, id: i64) -> Result<ItemDetail> {
+    let item = state.db.get_item_by_id(id)?;
+    let content = state.db.get_content_chunk(id, 0, CONTENT_CHUNK_BYTES)?;
+    let image = state.db.get_image_metadata(id)?;
+    Ok(ItemDetail { item: item.into(), content, image })
+}
+
+/// The next chunk of an item's content, from the `nextOffset` of the previous one
+#[tauri::command]
+pub async fn get_item_content_chunk(
     state: State<'_, AppState>,
     id: i64,
+    offset: usize,
+) -> Result<ContentChunk> {
+    state.db.get_content_chunk(id, offset, CONTENT_CHUNK_BYTES)
+}
+
+/// Destination of every clipboard write, so copy paths can be exercised without a display
+pub trait ClipboardSink {
+    fn set_text(&self, text: String) -> Result<()>;
+    fn set_image(&self, image: arboard::ImageData<'static>) -> Result<()>;
+}
+
+/// The system clipboard
+pub struct SystemClipboard;
+
+impl ClipboardSink for SystemClipboard {
+    fn set_text(&self, text: String) -> Result<()> {
+        let mut clipboard = Clipboard::new().map_err(|e| AppError::Clipboard(e.to_string()))?;
+        clipboard.set_text(text).map_err(|e| AppError::Clipboard(e.to_string()))
+    }
+
+    fn set_image(&self, image: arboard::ImageData<'static>) -> Result<()> {
+        let mut clipboard = Clipboard::new().map_err(|e| AppError::Clipboard(e.to_string()))?;
+        clipboard.set_image(image).map_err(|e| AppError::Clipboard(e.to_string()))
+    }
+}
+
+/// Content written to the clipboard by a copy command
+enum ClipboardContent {
+    Text(String),
+    Image(arboard::ImageData<'static>),
+}
+
+/// What pasted content came from, for the usage event it records
+#[derive(Clone, Copy)]
+enum PasteSource<'a> {
+    Items(&'a [i64]),
+    Snippet(i64),
+}
+
+/// Place content on the clipboard, mark it so the monitor does not re-capture
+/// it, and record a paste into `target_app` for whatever it came from.
+/// Every copy goes through here: history items, the paste queue, transforms,
+/// joins and snippets.
+fn paste_to_clipboard(
+    db: &Database,
+    monitor: &ClipboardMonitor,
+    clipboard: &dyn ClipboardSink,
+    content: ClipboardContent,
+    source: PasteSource,
+    target_app: Option<&str>,
 ) -> Result<()> {
+    match content {
+        ClipboardContent::Text(text) => {
+            monitor.set_last_copied_hash(compute_text_hash(&text));
+            clipboard.set_text(text)?;
+        }
+        ClipboardContent::Image(data) => {
+            // Hash the pixels being written, as the monitor will when it sees
+            // them; a downscaled or recompressed file no longer matches `item.hash`
+            monitor.set_last_copied_hash(compute_image_hash(&data.bytes));
+            clipboard.set_image(data)?;
+        }
+    }
+
+    match source {
+        PasteSource::Items(ids) => {
+            for id in ids {
+                db.record_paste(*id, target_app)?;
+            }
+        }
+        PasteSource::Snippet(id) => db.record_snippet_paste(id, target_app)?,
+    }
+    Ok(())
+}
+
+/// Place a stored item on the clipboard without re-capturing it.
+/// Shared by `copy_to_clipboard`, the paste queue and the paste-next shortcut.
+pub fn copy_item_to_clipboard(
+    db: &Database,
+    monitor: &ClipboardMonitor,
+    clipboard: &dyn ClipboardSink,
+    id: i64,
+    target_app: Option<&str>,
+) -> Result<ClipboardItem> {
     // Get item by ID efficiently
-    let item = state.db.get_item_by_id(id)?;
+    let item = db.get_item_by_id(id)?;
+
+    let content = if item.content_type == "image" {
+        let image_path = item.image_path.as_deref()
+            .ok_or_else(|| AppError::InvalidInput("Image path not found".to_string()))?;
+        ClipboardContent::Image(decode_png_for_clipboard(image_path)?)
+    } else {
+        ClipboardContent::Text(item.content.clone())
+    };
 
-    // Set last copied hash to prevent re-capture
-    state.monitor.set_last_copied_hash(item.hash.clone());
+    paste_to_clipboard(db, monitor, clipboard, content, PasteSource::Items(&[id]), target_app)?;
+    log::debug!("Copied {} item {} to clipboard", item.content_type, id);
 
-    // Copy to system clipboard
-    let mut clipboard = Clipboard::new()
-        .map_err(|e| crate::error::AppError::Clipboard(e.to_string()))?;
+    Ok(item)
+}
 
-    if item.content_type == "image" {
-        // Copy image from file
-        if let Some(image_path) = &item.image_path {
-            let img = decode_png_for_clipboard(image_path)?;
-            clipboard.set_image(img)
-                .map_err(|e| crate::error::AppError::Clipboard(e.to_string()))?;
-            log::debug!("Copied image item {} to clipboard", id);
-        } else {
-            return Err(crate::error::AppError::InvalidInput("Image path not found".to_string()));
+/// Apply a transform chain to a text item and copy the result
+fn copy_transformed_item(
+    db: &Database,
+    monitor: &ClipboardMonitor,
+    clipboard: &dyn ClipboardSink,
+    id: i64,
+    chain: &[String],
+    target_app: Option<&str>,
+) -> Result<String> {
+    let item = db.get_item_by_id(id)?;
+
+    if item.content_type != "text" {
+        return Err(AppError::InvalidInput(
+            "Transforms can only be applied to text items".to_string(),
+        ));
+    }
+
+    let output = transforms::apply_chain(&item.content, chain)?;
+    paste_to_clipboard(
+        db,
+        monitor,
+        clipboard,
+        ClipboardContent::Text(output.clone()),
+        PasteSource::Items(&[id]),
+        target_app,
+    )?;
+    log::debug!("Copied text item {} to clipboard with {} transform(s)", id, chain.len());
+
+    Ok(output)
+}
+
+/// Join text items with a separator or template and copy the result; each
+/// joined item records a paste
+fn copy_joined_items(
+    db: &Database,
+    monitor: &ClipboardMonitor,
+    clipboard: &dyn ClipboardSink,
+    ids: &[i64],
+    separator: &str,
+    template: Option<&str>,
+    target_app: Option<&str>,
+) -> Result<String> {
+    if ids.is_empty() {
+        return Err(AppError::InvalidInput("No items selected to join".to_string()));
+    }
+
+    let mut contents = Vec::with_capacity(ids.len());
+    for id in ids {
+        let item = db.get_item_by_id(*id)?;
+        if item.content_type != "text" {
+            return Err(AppError::InvalidInput(format!(
+                "Item {} is not a text item and cannot be joined",
+                id
+            )));
         }
+        contents.push(item.content);
+    }
+
+    let text = transforms::join_contents(&contents, separator, template)?;
+    paste_to_clipboard(
+        db,
+        monitor,
+        clipboard,
+        ClipboardContent::Text(text.clone()),
+        PasteSource::Items(ids),
+        target_app,
+    )?;
+    log::debug!("Copied {} joined items to clipboard", ids.len());
+
+    Ok(text)
+}
+
+/// Expand a snippet and copy it once no input is missing
+fn expand_snippet_to_clipboard(
+    db: &Database,
+    monitor: &ClipboardMonitor,
+    clipboard: &dyn ClipboardSink,
+    id: i64,
+    inputs: HashMap<String, String>,
+    target_app: Option<&str>,
+) -> Result<TemplateExpansion> {
+    let snippet = db.get_snippet(id)?;
+    let used = templates::placeholders(&snippet.content)?;
+
+    // Only touch the clipboard and history when the template asks for them
+    let current_clipboard = if used.contains(&Placeholder::Clipboard) {
+        Clipboard::new().and_then(|mut c| c.get_text()).ok()
     } else {
-        // Copy text
-        clipboard.set_text(item.content)
-            .map_err(|e| crate::error::AppError::Clipboard(e.to_string()))?;
-        log::debug!("Copied text item {} to clipboard", id);
+        None
+    };
+
+    let history_depth = used
+        .iter()
+        .filter_map(|p| match p {
+            Placeholder::History(n) => Some(*n),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0);
+    let history = if history_depth > 0 {
+        db.get_history(PageRequest::first(history_depth as u32))?
+            .into_iter()
+            .map(|item| db.get_item_content(item.id))
+            .collect::<Result<Vec<_>>>()?
+    } else {
+        Vec::new()
+    };
+
+    let ctx = TemplateContext {
+        now: chrono::Local::now(),
+        clipboard: current_clipboard,
+        history,
+        inputs,
+        allow_env: snippet.pack.is_none(),
+    };
+
+    let expansion = templates::expand(&snippet.content, &ctx)?;
+    if let TemplateExpansion::Complete { text } = &expansion {
+        paste_to_clipboard(
+            db,
+            monitor,
+            clipboard,
+            ClipboardContent::Text(text.clone()),
+            PasteSource::Snippet(id),
+            target_app,
+        )?;
+        log::debug!("Copied expanded snippet {} to clipboard", id);
+    }
+
+    Ok(expansion)
+}
+
+/// Pop queued ids until one still exists, then copy it to the clipboard.
+pub fn paste_next_from_queue(
+    db: &Database,
+    monitor: &ClipboardMonitor,
+    clipboard: &dyn ClipboardSink,
+    queue: &PasteQueue,
+) -> Result<Option<ClipboardItem>> {
+    while let Some(id) = queue.pop() {
+        // Paste-next runs from a global shortcut, so the frontmost app is the target
+        match copy_item_to_clipboard(db, monitor, clipboard, id, Some(&get_frontmost_app())) {
+            Ok(item) => return Ok(Some(item)),
+            Err(AppError::NotFound(_)) => {
+                log::debug!("Skipping queued item {} that no longer exists", id);
+            }
+            Err(e) => return Err(e),
+        }
     }
 
+    Ok(None)
+}
+
+#[tauri::command]
+pub async fn get_frequent_items(
+    state: State<'_, AppState>,
+    limit: u32,
+) -> Result<Vec<ClipboardListItem>> {
+    state.db.get_frequent_items(limit)
+}
+
+#[tauri::command]
+pub async fn get_stats(
+    state: State<'_, AppState>,
+    date_from: Option<i64>,
+    date_to: Option<i64>,
+    bucket: Option<StatsBucket>,
+) -> Result<ClipboardStats> {
+    if let (Some(from), Some(to)) = (date_from, date_to) {
+        if from > to {
+            return Err(AppError::InvalidInput("dateFrom must not be after dateTo".to_string()));
+        }
+    }
+
+    let utc_offset = chrono::Local::now().offset().local_minus_utc() as i64;
+    state.db.get_stats(date_from, date_to, bucket.unwrap_or_default(), utc_offset)
+}
+
+#[tauri::command]
+pub async fn get_usage_events(
+    state: State<'_, AppState>,
+    id: i64,
+) -> Result<Vec<UsageEvent>> {
+    state.db.get_usage_events(id)
+}
+
+/// Image items that look like `id`, within the near-duplicate threshold
+#[tauri::command]
+pub async fn find_similar_images(
+    state: State<'_, AppState>,
+    id: i64,
+) -> Result<Vec<SimilarImage>> {
+    let max_distance = state.db.get_settings()?.near_duplicate_images.max_distance;
+    state.db.find_similar_images(id, max_distance)
+}
+
+#[tauri::command]
+pub async fn copy_to_clipboard(
+    state: State<'_, AppState>,
+    id: i64,
+) -> Result<()> {
+    let target_app = state.opened_from.lock().unwrap().clone();
+    copy_item_to_clipboard(&state.db, &state.monitor, &SystemClipboard, id, target_app.as_deref())?;
     Ok(())
 }
 
+#[tauri::command]
+pub async fn copy_transformed(
+    state: State<'_, AppState>,
+    id: i64,
+    transforms: Vec<String>,
+) -> Result<String> {
+    let target_app = state.opened_from.lock().unwrap().clone();
+    copy_transformed_item(&state.db, &state.monitor, &SystemClipboard, id, &transforms, target_app.as_deref())
+}
+
+#[tauri::command]
+pub async fn copy_joined(
+    state: State<'_, AppState>,
+    ids: Vec<i64>,
+    separator: String,
+    template: Option<String>,
+    save: bool,
+) -> Result<JoinResult> {
+    let target_app = state.opened_from.lock().unwrap().clone();
+    let text = copy_joined_items(
+        &state.db,
+        &state.monitor,
+        &SystemClipboard,
+        &ids,
+        &separator,
+        template.as_deref(),
+        target_app.as_deref(),
+    )?;
+
+    let mut item_id = None;
+    if save {
+        let is_sens = is_sensitive(&text);
+        if is_sens && state.db.get_settings()?.auto_exclude_sensitive {
+            log::warn!("Not saving joined text to history: sensitive content");
+        } else {
+            let id = state.db.insert_item(
+                text.clone(),
+                "text".to_string(),
+                None,
+                detect_category(&text),
+                "SmartClipboard".to_string(),
+                is_sens,
+                compute_text_hash(&text),
+                build_preview(&text),
+                chrono::Utc::now().timestamp(),
+            )?;
+            state.db.link_derivations(id, &ids)?;
+            item_id = Some(id);
+        }
+    }
+
+    Ok(JoinResult { text, item_id })
+}
+
+#[tauri::command]
+pub async fn get_derived_from(
+    state: State<'_, AppState>,
+    id: i64,
+) -> Result<Vec<i64>> {
+    state.db.get_derived_from(id)
+}
+
+#[tauri::command]
+pub async fn list_transforms() -> Result<Vec<TransformInfo>> {
+    Ok(transforms::list_transforms())
+}
+
+#[tauri::command]
+pub async fn get_transform_chains(
+    state: State<'_, AppState>,
+) -> Result<Vec<TransformChain>> {
+    state.db.get_transform_chains()
+}
+
+#[tauri::command]
+pub async fn save_transform_chain(
+    state: State<'_, AppState>,
+    name: String,
+    transforms: Vec<String>,
+) -> Result<()> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(AppError::InvalidInput("Transform chain name cannot be empty".to_string()));
+    }
+
+    transforms::validate_chain(&transforms)?;
+    state.db.save_transform_chain(name, transforms)
+}
+
+#[tauri::command]
+pub async fn delete_transform_chain(
+    state: State<'_, AppState>,
+    name: String,
+) -> Result<()> {
+    state.db.delete_transform_chain(name)
+}
+
 #[tauri::command]
 pub async fn set_favorite(
     state: State<'_, AppState>,
@@ -113,29 +610,118 @@ pub async fn set_favorite(
 }
 
 #[tauri::command]
-pub async fn delete_item(
+pub async fn set_annotation(
     state: State<'_, AppState>,
     id: i64,
+    title: Option<String>,
+    note: Option<String>,
 ) -> Result<()> {
-    // Get item to check if it has an image file to clean up
-    let item = state.db.get_item_by_id(id)?;
+    // Blank strings clear the field
+    let title = title.map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+    let note = note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+    state.db.set_annotation(id, title, note)
+}
+
+#[tauri::command]
+pub async fn update_item_content(
+    state: State<'_, AppState>,
+    id: i64,
+    content: String,
+) -> Result<ClipboardItem> {
+    apply_content_edit(&state.db, id, content)
+}
+
+#[tauri::command]
+pub async fn get_item_revisions(
+    state: State<'_, AppState>,
+    id: i64,
+) -> Result<Vec<ItemRevision>> {
+    state.db.get_item_revisions(id)
+}
 
-    // Delete from database first
+#[tauri::command]
+pub async fn revert_item_revision(
+    state: State<'_, AppState>,
+    id: i64,
+    revision_id: i64,
+) -> Result<ClipboardItem> {
+    // Reverting is an edit too, so the current content becomes a revision
+    let content = state.db.get_revision_content(id, revision_id)?;
+    apply_content_edit(&state.db, id, content)
+}
+
+#[tauri::command]
+pub async fn delete_item(
+    state: State<'_, AppState>,
+    id: i64,
+) -> Result<()> {
+    // Items go to the trash; image files are removed when the trash is purged
     state.db.delete_item(id)?;
+    state.queue.remove(id);
 
-    // Clean up image file if it exists
-    if item.content_type == "image" {
-        if let Some(image_path) = &item.image_path {
-            if let Err(e) = std::fs::remove_file(image_path) {
-                log::warn!("Failed to delete image file {}: {}", image_path, e);
-                // Don't fail the whole operation if file cleanup fails
-            } else {
-                log::debug!("Deleted image file: {}", image_path);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn restore_item(
+    state: State<'_, AppState>,
+    id: i64,
+) -> Result<ClipboardItem> {
+    state.db.restore_item(id)?;
+    state.db.get_item_by_id(id)
+}
+
+#[tauri::command]
+pub async fn get_trash(
+    state: State<'_, AppState>,
+) -> Result<Vec<ClipboardListItem>> {
+    state.db.get_trash()
+}
+
+#[tauri::command]
+pub async fn bulk_update(
+    state: State<'_, AppState>,
+    selection: BulkSelection,
+    action: BulkAction,
+) -> Result<BulkResult> {
+    let action = match action {
+        BulkAction::Retag { category } => {
+            let category = category.trim().to_lowercase();
+            if !CATEGORIES.contains(&category.as_str()) {
+                return Err(AppError::InvalidInput(format!("Unknown category: {}", category)));
             }
+            BulkAction::Retag { category }
+        }
+        other => other,
+    };
+
+    let changed = state.db.bulk_update(selection, &action)?;
+
+    if matches!(action, BulkAction::Delete) {
+        for id in &changed {
+            state.queue.remove(*id);
         }
     }
 
-    Ok(())
+    Ok(BulkResult { affected: changed.len() })
+}
+
+#[tauri::command]
+pub async fn clear_history(
+    state: State<'_, AppState>,
+) -> Result<BulkResult> {
+    let affected = clear_history_and_queue(&state.db, &state.queue)?;
+    Ok(BulkResult { affected })
+}
+
+/// Clear unprotected history and drop the cleared ids from the paste queue,
+/// so paste-next never reaches for a row that is gone
+fn clear_history_and_queue(db: &Database, queue: &PasteQueue) -> Result<usize> {
+    let cleared = db.clear_history()?;
+    for id in &cleared {
+        queue.remove(*id);
+    }
+    Ok(cleared.len())
 }
 
 #[tauri::command]
@@ -163,18 +749,64 @@ pub async fn update_settings(
         ));
     }
 
+    if settings.trash_retention_days > 365 {
+        return Err(crate::error::AppError::InvalidInput(
+            "trash_retention_days must be at most 365".to_string()
+        ));
+    }
+
     if settings.max_image_size_mb < 1 || settings.max_image_size_mb > 100 {
         return Err(crate::error::AppError::InvalidInput(
             "max_image_size_mb must be between 1 and 100".to_string()
         ));
     }
 
+    let oversized = &settings.oversized_images;
+    if oversized.max_dimension < 256 || oversized.max_dimension > 8192 {
+        return Err(crate::error::AppError::InvalidInput(
+            "oversized image max_dimension must be between 256 and 8192".to_string()
+        ));
+    }
+    if oversized.quality < 1 || oversized.quality > 100 {
+        return Err(crate::error::AppError::InvalidInput(
+            "oversized image quality must be between 1 and 100".to_string()
+        ));
+    }
+
+    if settings.near_duplicate_images.max_distance > 16 {
+        return Err(crate::error::AppError::InvalidInput(
+            "near-duplicate max_distance must be at most 16".to_string()
+        ));
+    }
+
+    let weights = &settings.ranking_weights;
+    let signal_weights = [weights.text, weights.recency, weights.favorite, weights.usage];
+    if signal_weights.iter().any(|w| !w.is_finite() || *w < 0.0) {
+        return Err(crate::error::AppError::InvalidInput(
+            "ranking weights must be zero or positive numbers".to_string()
+        ));
+    }
+    if !weights.recency_half_life_days.is_finite() || weights.recency_half_life_days <= 0.0 {
+        return Err(crate::error::AppError::InvalidInput(
+            "recency_half_life_days must be greater than 0".to_string()
+        ));
+    }
+
+    let snippet_pack_dir = settings.snippet_pack_dir.trim();
+    if !snippet_pack_dir.is_empty() && !Path::new(snippet_pack_dir).is_dir() {
+        return Err(crate::error::AppError::InvalidInput(
+            "snippet_pack_dir must be an existing directory".to_string()
+        ));
+    }
+
     // Update database
     state.db.update_settings(settings.clone())?;
 
     // Update monitor settings
     state.monitor.set_auto_exclude_sensitive(settings.auto_exclude_sensitive);
     state.monitor.set_max_image_size_mb(settings.max_image_size_mb);
+    state.monitor.set_oversized_image_policy(settings.oversized_images.clone());
+    state.packs.set_dir(&settings.snippet_pack_dir);
 
     Ok(())
 }
@@ -215,24 +847,401 @@ pub async fn remove_exclusion(
 }
 
 #[tauri::command]
-pub async fn get_image_data(
+pub async fn enqueue(
+    state: State<'_, AppState>,
+    id: i64,
+) -> Result<QueueState> {
+    // Only queue ids that exist right now
+    state.db.get_item_by_id(id)?;
+    state.queue.push(id);
+    Ok(state.queue.state())
+}
+
+#[tauri::command]
+pub async fn dequeue(
+    state: State<'_, AppState>,
+) -> Result<Option<ClipboardItem>> {
+    paste_next_from_queue(&state.db, &state.monitor, &SystemClipboard, &state.queue)
+}
+
+#[tauri::command]
+pub async fn peek(
+    state: State<'_, AppState>,
+) -> Result<Option<ClipboardItem>> {
+    match state.queue.peek() {
+        Some(id) => state.db.get_item_by_id(id).map(Some),
+        None => Ok(None),
+    }
+}
+
+#[tauri::command]
+pub async fn clear_queue(
+    state: State<'_, AppState>,
+) -> Result<()> {
+    state.queue.clear();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_queue(
+    state: State<'_, AppState>,
+) -> Result<QueueState> {
+    Ok(state.queue.state())
+}
+
+#[tauri::command]
+pub async fn set_queue_mode(
+    state: State<'_, AppState>,
+    order: QueueOrder,
+    capture_new: bool,
+) -> Result<QueueState> {
+    state.queue.set_mode(order, capture_new);
+    Ok(state.queue.state())
+}
+
+#[tauri::command]
+pub async fn get_snippets(
+    state: State<'_, AppState>,
+    folder: Option<String>,
+) -> Result<Vec<Snippet>> {
+    state.db.get_snippets(folder)
+}
+
+#[tauri::command]
+pub async fn get_snippet(
+    state: State<'_, AppState>,
+    id: i64,
+) -> Result<Snippet> {
+    state.db.get_snippet(id)
+}
+
+#[tauri::command]
+pub async fn create_snippet(
+    state: State<'_, AppState>,
+    snippet: SnippetInput,
+) -> Result<Snippet> {
+    let id = state.db.create_snippet(snippet.normalize()?)?;
+    state.db.get_snippet(id)
+}
+
+#[tauri::command]
+pub async fn update_snippet(
+    state: State<'_, AppState>,
+    id: i64,
+    snippet: SnippetInput,
+) -> Result<Snippet> {
+    state.db.update_snippet(id, snippet.normalize()?)?;
+    state.db.get_snippet(id)
+}
+
+#[tauri::command]
+pub async fn delete_snippet(
     state: State<'_, AppState>,
-    image_path: String,
-) -> Result<Vec<u8>> {
-    let images_dir = state.monitor.images_dir();
-    let canonical_path = canonicalize_requested_image_path(&image_path, &images_dir)?;
-    let canonical_path_str = canonical_path.to_string_lossy().to_string();
+    id: i64,
+) -> Result<()> {
+    state.db.delete_snippet(id)
+}
 
-    // Security: Verify file path is present in DB (supports legacy rows with non-canonical paths).
-    let is_valid = state.db.image_path_exists(&canonical_path_str)?
-        || state.db.image_path_exists(&image_path)?;
+#[tauri::command]
+pub async fn find_snippets(
+    state: State<'_, AppState>,
+    query: String,
+    limit: u32,
+) -> Result<Vec<Snippet>> {
+    state.db.find_snippets(query.trim(), limit)
+}
 
-    if !is_valid {
+#[tauri::command]
+pub async fn promote_to_snippet(
+    state: State<'_, AppState>,
+    id: i64,
+    name: String,
+    abbreviation: Option<String>,
+    folder: Option<String>,
+) -> Result<Snippet> {
+    let item = state.db.get_item_by_id(id)?;
+
+    if item.content_type != "text" {
         return Err(AppError::InvalidInput(
-            "Image path not found in database".to_string()
+            "Only text items can be promoted to snippets".to_string(),
         ));
     }
 
-    let bytes = std::fs::read(canonical_path)?;
-    Ok(bytes)
+    let snippet = SnippetInput {
+        name,
+        abbreviation,
+        folder,
+        content: item.content,
+        tags: Vec::new(),
+    }
+    .normalize()?;
+    let snippet_id = state.db.create_snippet(snippet)?;
+    log::info!("Promoted clipboard item {} to snippet {}", id, snippet_id);
+
+    state.db.get_snippet(snippet_id)
+}
+
+#[tauri::command]
+pub async fn expand_snippet(
+    state: State<'_, AppState>,
+    id: i64,
+    inputs: Option<HashMap<String, String>>,
+) -> Result<TemplateExpansion> {
+    let target_app = state.opened_from.lock().unwrap().clone();
+    expand_snippet_to_clipboard(
+        &state.db,
+        &state.monitor,
+        &SystemClipboard,
+        id,
+        inputs.unwrap_or_default(),
+        target_app.as_deref(),
+    )
+}
+
+#[tauri::command]
+pub async fn get_snippet_pack_diagnostics(
+    state: State<'_, AppState>,
+) -> Result<Vec<PackDiagnostic>> {
+    Ok(state.packs.diagnostics())
+}
+
+#[tauri::command]
+pub async fn reload_snippet_packs(
+    state: State<'_, AppState>,
+) -> Result<Vec<PackDiagnostic>> {
+    let dir = state.db.get_settings()?.snippet_pack_dir;
+    state.packs.set_dir(&dir);
+    state.packs.reload_if_changed();
+    Ok(state.packs.diagnostics())
+}
+
+#[tauri::command]
+pub async fn get_collections(
+    state: State<'_, AppState>,
+) -> Result<Vec<Collection>> {
+    state.db.get_collections()
+}
+
+#[tauri::command]
+pub async fn create_collection(
+    state: State<'_, AppState>,
+    name: String,
+) -> Result<i64> {
+    state.db.create_collection(normalize_collection_name(name)?)
+}
+
+#[tauri::command]
+pub async fn rename_collection(
+    state: State<'_, AppState>,
+    id: i64,
+    name: String,
+) -> Result<()> {
+    state.db.rename_collection(id, normalize_collection_name(name)?)
+}
+
+#[tauri::command]
+pub async fn delete_collection(
+    state: State<'_, AppState>,
+    id: i64,
+) -> Result<()> {
+    state.db.delete_collection(id)
+}
+
+#[tauri::command]
+pub async fn reorder_collections(
+    state: State<'_, AppState>,
+    ids: Vec<i64>,
+) -> Result<()> {
+    state.db.reorder_collections(&ids)
+}
+
+#[tauri::command]
+pub async fn get_collection_items(
+    state: State<'_, AppState>,
+    collection_id: i64,
+) -> Result<Vec<ClipboardListItem>> {
+    state.db.get_collection_items(collection_id)
+}
+
+#[tauri::command]
+pub async fn add_to_collection(
+    state: State<'_, AppState>,
+    collection_id: i64,
+    item_id: i64,
+) -> Result<()> {
+    state.db.add_to_collection(collection_id, item_id)
+}
+
+#[tauri::command]
+pub async fn remove_from_collection(
+    state: State<'_, AppState>,
+    collection_id: i64,
+    item_id: i64,
+) -> Result<()> {
+    state.db.remove_from_collection(collection_id, item_id)
+}
+
+#[tauri::command]
+pub async fn reorder_collection_items(
+    state: State<'_, AppState>,
+    collection_id: i64,
+    item_ids: Vec<i64>,
+) -> Result<()> {
+    state.db.reorder_collection_items(collection_id, &item_ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        clear_history_and_queue, copy_item_to_clipboard, copy_joined_items, copy_transformed_item,
+        expand_snippet_to_clipboard, search_page, ClipboardSink,
+    };
+    use crate::clipmon::{compute_image_hash, ClipboardMonitor};
+    use crate::db::{insert_image, insert_text, Database};
+    use crate::error::Result;
+    use crate::models::{PageCursor, SearchFilters, SearchMode, SearchSort, SnippetInput, TemplateExpansion};
+    use crate::paste_queue::PasteQueue;
+    use image::imageops::FilterType;
+    use image::{DynamicImage, Rgba, RgbaImage};
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    /// Records what would have been written to the system clipboard
+    #[derive(Default)]
+    struct FakeClipboard {
+        texts: RefCell<Vec<String>>,
+        images: RefCell<Vec<arboard::ImageData<'static>>>,
+    }
+
+    impl ClipboardSink for FakeClipboard {
+        fn set_text(&self, text: String) -> Result<()> {
+            self.texts.borrow_mut().push(text);
+            Ok(())
+        }
+
+        fn set_image(&self, image: arboard::ImageData<'static>) -> Result<()> {
+            self.images.borrow_mut().push(image);
+            Ok(())
+        }
+    }
+
+    fn paste_apps(db: &Database, id: i64) -> Vec<Option<String>> {
+        db.get_usage_events(id)
+            .expect("events")
+            .into_iter()
+            .filter(|e| e.kind == "paste")
+            .map(|e| e.app)
+            .collect()
+    }
+
+    #[test]
+    fn test_clear_history_prunes_paste_queue() {
+        let temp_dir = tempfile::tempdir().expect("tempdir");
+        let db = Database::new(temp_dir.path()).expect("db init");
+        let cleared = insert_text(&db, "cleared", "Tests", 1);
+        let pinned = insert_text(&db, "pinned", "Tests", 2);
+        db.set_favorite(pinned, true).expect("favorite");
+
+        let queue = PasteQueue::new();
+        queue.push(cleared);
+        queue.push(pinned);
+        queue.push(cleared);
+
+        assert_eq!(clear_history_and_queue(&db, &queue).expect("clear"), 1);
+        assert_eq!(queue.state().item_ids, vec![pinned]);
+    }
+
+    #[test]
+    fn test_pasting_a_downscaled_image_suppresses_its_recapture() {
+        let temp_dir = tempfile::tempdir().expect("tempdir");
+        let db = Database::new(temp_dir.path()).expect("db init");
+        let (monitor, _events) = ClipboardMonitor::new(temp_dir.path());
+        let clipboard = FakeClipboard::default();
+
+        // The item keeps the hash of the captured pixels, but the stored file was downscaled
+        let original = RgbaImage::from_fn(64, 64, |x, y| Rgba([(x * 4) as u8, (y * 4) as u8, 128, 255]));
+        let stored = DynamicImage::ImageRgba8(original.clone()).resize(32, 32, FilterType::Triangle);
+        let image_path = temp_dir.path().join("downscaled.png");
+        stored.save(&image_path).expect("save image");
+        let id = insert_image(&db, &image_path, &compute_image_hash(original.as_raw()));
+
+        copy_item_to_clipboard(&db, &monitor, &clipboard, id, None).expect("copy");
+
+        let written = &clipboard.images.borrow()[0];
+        assert_eq!((written.width, written.height), (32, 32));
+        assert_eq!(monitor.last_copied_hash(), Some(compute_image_hash(&written.bytes)));
+        assert_ne!(monitor.last_copied_hash(), Some(compute_image_hash(original.as_raw())));
+    }
+
+    #[test]
+    fn test_ranked_search_returns_one_page_without_a_cursor() {
+        let temp_dir = tempfile::tempdir().expect("tempdir");
+        let db = Database::new(temp_dir.path()).expect("db init");
+        for i in 0..5 {
+            insert_text(&db, &format!("deploy step {}", i), "Tests", 100 + i);
+        }
+        insert_text(&db, "lunch order", "Tests", 200);
+
+        let (hits, next) = search_page(&db, "deploy".to_string(), SearchFilters::default(), SearchMode::Exact, 2, None)
+            .expect("search");
+        assert_eq!(hits.len(), 2);
+        assert_eq!(next, None, "ranked sorts are not paged");
+
+        let keyset = PageCursor::after(&hits[0].item).encode();
+        let paged = search_page(&db, "deploy".to_string(), SearchFilters::default(), SearchMode::Exact, 2, Some(keyset));
+        assert!(paged.is_err(), "a cursor is rejected for ranked sorts");
+
+        let newest = SearchFilters { sort: Some(SearchSort::Newest), ..Default::default() };
+        let (_, next) = search_page(&db, "deploy".to_string(), newest.clone(), SearchMode::Exact, 2, None).expect("search");
+        assert!(next.is_some(), "chronological sorts are paged");
+
+        let empty = search_page(&db, "deploy".to_string(), newest, SearchMode::Exact, 0, None);
+        assert!(empty.is_err(), "a zero limit would never advance the cursor");
+    }
+
+    #[test]
+    fn test_every_copy_path_records_a_paste_into_the_target_app() {
+        let temp_dir = tempfile::tempdir().expect("tempdir");
+        let db = Database::new(temp_dir.path()).expect("db init");
+        let (monitor, _events) = ClipboardMonitor::new(temp_dir.path());
+        let clipboard = FakeClipboard::default();
+        let first = insert_text(&db, "hello", "Tests", 1);
+        let second = insert_text(&db, "world", "Tests", 2);
+
+        copy_item_to_clipboard(&db, &monitor, &clipboard, first, Some("Terminal")).expect("copy");
+        assert_eq!(paste_apps(&db, first), vec![Some("Terminal".to_string())]);
+
+        let output = copy_transformed_item(&db, &monitor, &clipboard, second, &["uppercase".to_string()], Some("Slack"))
+            .expect("transform");
+        assert_eq!(output, "WORLD");
+        assert_eq!(paste_apps(&db, second), vec![Some("Slack".to_string())]);
+
+        let joined = copy_joined_items(&db, &monitor, &clipboard, &[first, second], " ", None, Some("Mail"))
+            .expect("join");
+        assert_eq!(joined, "hello world");
+        assert_eq!(paste_apps(&db, first)[0].as_deref(), Some("Mail"));
+        assert_eq!(paste_apps(&db, second)[0].as_deref(), Some("Mail"));
+
+        let snippet = db
+            .create_snippet(SnippetInput {
+                name: "Greeting".to_string(),
+                abbreviation: None,
+                folder: None,
+                content: "Hi {input:Name}".to_string(),
+                tags: Vec::new(),
+            })
+            .expect("snippet");
+        let pending = expand_snippet_to_clipboard(&db, &monitor, &clipboard, snippet, HashMap::new(), Some("Mail"))
+            .expect("expand");
+        assert!(matches!(pending, TemplateExpansion::NeedsInput { .. }));
+        assert!(db.get_snippet_usage_events(snippet).expect("events").is_empty(), "nothing was pasted yet");
+
+        let inputs = HashMap::from([("Name".to_string(), "Ada".to_string())]);
+        expand_snippet_to_clipboard(&db, &monitor, &clipboard, snippet, inputs, Some("Mail")).expect("expand");
+        let events = db.get_snippet_usage_events(snippet).expect("events");
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].app.as_deref(), Some("Mail"));
+
+        assert_eq!(*clipboard.texts.borrow(), vec!["hello", "WORLD", "hello world", "Hi Ada"]);
+    }
 }
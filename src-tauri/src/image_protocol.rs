@@ -0,0 +1,216 @@
+//! `clip-image://<id>` URI scheme. Serves stored clipboard images as raw file
+//! bytes, authorized by item id, instead of a JSON array of numbers over IPC.
+//! `?thumb=N` returns a PNG scaled to fit within N×N pixels, read from the
+//! cached thumbnails when one is large enough and served as stored when N is
+//! a cached size.
+
+use crate::db::Database;
+use crate::error::{AppError, Result};
+use crate::thumbnails;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use tauri::http::{header, Response, StatusCode, Uri};
+
+pub const SCHEME: &str = "clip-image";
+
+const MIN_THUMB_SIZE: u32 = 16;
+const MAX_THUMB_SIZE: u32 = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageRequest {
+    pub id: i64,
+    pub thumb: Option<u32>,
+}
+
+/// Parse `/<id>` and an optional `thumb=<size>` query
+pub fn parse_request(path: &str, query: Option<&str>) -> Result<ImageRequest> {
+    let id = path
+        .trim_matches('/')
+        .parse::<i64>()
+        .map_err(|_| AppError::InvalidInput(format!("Invalid image id: {}", path)))?;
+
+    let mut thumb = None;
+    for pair in query.unwrap_or_default().split('&').filter(|pair| !pair.is_empty()) {
+        match pair.split_once('=') {
+            Some(("thumb", size)) => {
+                let size = size
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|size| (MIN_THUMB_SIZE..=MAX_THUMB_SIZE).contains(size))
+                    .ok_or_else(|| {
+                        AppError::InvalidInput(format!(
+                            "thumb must be between {} and {}",
+                            MIN_THUMB_SIZE, MAX_THUMB_SIZE
+                        ))
+                    })?;
+                thumb = Some(size);
+            }
+            _ => return Err(AppError::InvalidInput(format!("Unknown image parameter: {}", pair))),
+        }
+    }
+
+    Ok(ImageRequest { id, thumb })
+}
+
+/// Resolve a user-visible image path and ensure it stays inside the images directory
+pub(crate) fn canonicalize_requested_image_path(image_path: &str, images_dir: &Path) -> Result<PathBuf> {
+    let canonical_path = Path::new(image_path)
+        .canonicalize()
+        .map_err(|_| AppError::InvalidInput("Image file not found".to_string()))?;
+    let canonical_images_dir = images_dir.canonicalize()?;
+
+    if !canonical_path.starts_with(&canonical_images_dir) {
+        return Err(AppError::InvalidInput(
+            "Invalid image path: outside images directory".to_string(),
+        ));
+    }
+
+    Ok(canonical_path)
+}
+
+/// Image bytes for a request; thumbnails are always PNG. Only paths recorded on the item's own row are
+/// served, and only from inside the images directory.
+pub fn load_image(db: &Database, images_dir: &Path, request: ImageRequest) -> Result<Vec<u8>> {
+    let item = db.get_item_by_id(request.id)?;
+    let image_path = match (item.content_type.as_str(), item.image_path) {
+        ("image", Some(path)) => path,
+        _ => return Err(AppError::NotFound(request.id)),
+    };
+
+    let Some(size) = request.thumb else {
+        let canonical_path = canonicalize_requested_image_path(&image_path, images_dir)?;
+        return Ok(std::fs::read(canonical_path)?);
+    };
+
+    // Start from the smallest cached thumbnail that still covers the requested size
+    let (small, medium) = db.get_thumbnail_paths(request.id)?;
+    let (source, cached_size) = match (small, medium) {
+        (Some(small), _) if size <= thumbnails::SMALL => (small, Some(thumbnails::SMALL)),
+        (_, Some(medium)) if size <= thumbnails::MEDIUM => (medium, Some(thumbnails::MEDIUM)),
+        _ => (image_path, None),
+    };
+    let canonical_path = canonicalize_requested_image_path(&source, images_dir)?;
+    let bytes = std::fs::read(canonical_path)?;
+
+    // A cached thumbnail already fits its own size; serve it without decoding
+    if cached_size == Some(size) {
+        return Ok(bytes);
+    }
+
+    let decoded = image::load_from_memory(&bytes)
+        .map_err(|e| AppError::InvalidInput(format!("Failed to decode stored image: {}", e)))?;
+    if decoded.width() <= size && decoded.height() <= size {
+        return Ok(bytes);
+    }
+
+    let mut thumbnail = Vec::new();
+    decoded
+        .thumbnail(size, size)
+        .write_to(&mut Cursor::new(&mut thumbnail), image::ImageFormat::Png)
+        .map_err(|e| AppError::InvalidInput(format!("Failed to encode thumbnail: {}", e)))?;
+
+    Ok(thumbnail)
+}
+
+/// Build the HTTP response for a `clip-image://` request
+pub fn respond(db: &Database, images_dir: &Path, uri: &Uri) -> Response<Vec<u8>> {
+    let result = parse_request(uri.path(), uri.query()).and_then(|request| load_image(db, images_dir, request));
+
+    let (status, content_type, body) = match result {
+        Ok(bytes) => {
+            // Oversized images may have been stored as WebP or JPEG
+            let content_type = image::guess_format(&bytes).map_or("image/png", |format| format.to_mime_type());
+            (StatusCode::OK, content_type, bytes)
+        }
+        Err(e) => {
+            let status = match e {
+                AppError::NotFound(_) => StatusCode::NOT_FOUND,
+                AppError::InvalidInput(_) => StatusCode::BAD_REQUEST,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            log::debug!("Refused {} request {}: {}", SCHEME, uri, e);
+            (status, "text/plain", e.to_string().into_bytes())
+        }
+    };
+
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, content_type)
+        .body(body)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{load_image, parse_request, respond, ImageRequest};
+    use crate::db::{insert_image, Database};
+    use crate::thumbnails;
+
+    fn write_png(path: &std::path::Path, width: u32, height: u32) {
+        image::RgbaImage::from_pixel(width, height, image::Rgba([10, 20, 30, 255]))
+            .save(path)
+            .expect("write png");
+    }
+
+    #[test]
+    fn test_parse_request() {
+        assert_eq!(parse_request("/42", None).expect("parse"), ImageRequest { id: 42, thumb: None });
+        assert_eq!(
+            parse_request("/42", Some("thumb=256")).expect("parse"),
+            ImageRequest { id: 42, thumb: Some(256) }
+        );
+        assert!(parse_request("/../etc/passwd", None).is_err());
+        assert!(parse_request("/42", Some("thumb=4096")).is_err());
+        assert!(parse_request("/42", Some("path=/tmp/x.png")).is_err());
+    }
+
+    #[test]
+    fn test_images_are_served_by_id_from_the_images_dir_only() {
+        let temp_dir = tempfile::tempdir().expect("tempdir");
+        let db = Database::new(temp_dir.path()).expect("db init");
+        let images_dir = temp_dir.path().join("images");
+        std::fs::create_dir_all(&images_dir).expect("images dir");
+
+        let inside = images_dir.join("shot.png");
+        write_png(&inside, 600, 300);
+        let id = insert_image(&db, &inside, "hash_protocol_inside");
+
+        let outside = temp_dir.path().join("outside.png");
+        write_png(&outside, 10, 10);
+        let outside_id = insert_image(&db, &outside, "hash_protocol_outside");
+
+        let original = load_image(&db, &images_dir, ImageRequest { id, thumb: None }).expect("image");
+        assert_eq!(original, std::fs::read(&inside).expect("read"));
+
+        let thumb = load_image(&db, &images_dir, ImageRequest { id, thumb: Some(64) }).expect("thumb");
+        let decoded = image::load_from_memory(&thumb).expect("decode thumb");
+        assert_eq!((decoded.width(), decoded.height()), (64, 32));
+
+        assert!(load_image(&db, &images_dir, ImageRequest { id: outside_id, thumb: None }).is_err());
+
+        let ok = respond(&db, &images_dir, &format!("clip-image://localhost/{}", id).parse().expect("uri"));
+        assert_eq!(ok.status(), 200);
+        assert_eq!(ok.headers()["content-type"], "image/png");
+        let missing = respond(&db, &images_dir, &"clip-image://localhost/9999".parse().expect("uri"));
+        assert_eq!(missing.status(), 404);
+    }
+
+    #[test]
+    fn test_cached_thumbnail_sizes_are_served_as_stored() {
+        let temp_dir = tempfile::tempdir().expect("tempdir");
+        let db = Database::new(temp_dir.path()).expect("db init");
+        let images_dir = temp_dir.path().join("images");
+        std::fs::create_dir_all(&images_dir).expect("images dir");
+
+        let path = images_dir.join("wide.png");
+        write_png(&path, 1200, 600);
+        let id = insert_image(&db, &path, "hash_protocol_cached");
+        thumbnails::backfill(&db, &images_dir).expect("backfill");
+        let (small, medium) = db.get_thumbnail_paths(id).expect("paths");
+
+        for (size, cached) in [(thumbnails::SMALL, small), (thumbnails::MEDIUM, medium)] {
+            let served = load_image(&db, &images_dir, ImageRequest { id, thumb: Some(size) }).expect("thumb");
+            assert_eq!(served, std::fs::read(cached.expect("cached")).expect("read"));
+        }
+    }
+}
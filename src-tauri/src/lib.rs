@@ -2,26 +2,135 @@ pub mod categorizer;
 pub mod clipmon;
 pub mod db;
 pub mod error;
+pub mod fuzzy;
 pub mod handlers;
+pub mod image_protocol;
 pub mod models;
+pub mod paste_queue;
 pub mod platform;
+pub mod qr;
+pub mod query;
 pub mod sensitive;
+pub mod snippet_packs;
+pub mod templates;
+pub mod thumbnails;
+pub mod transforms;
 
 use handlers::{
-    add_exclusion, copy_to_clipboard, delete_item, get_exclusions, get_history,
-    get_image_data, get_settings, remove_exclusion, search, set_favorite,
-    update_settings, AppState,
+    add_exclusion, add_to_collection, bulk_update, clear_history, clear_queue, copy_joined,
+    copy_to_clipboard, copy_transformed, create_collection, create_snippet, delete_collection,
+    delete_item, delete_snippet, delete_transform_chain, dequeue, enqueue, expand_snippet,
+    find_similar_images, find_snippets, get_collection_items, get_collections, get_derived_from,
+    get_exclusions, get_frequent_items, get_history, get_item, get_item_content_chunk,
+    get_item_revisions, get_queue, get_settings, get_snippet, get_snippet_pack_diagnostics,
+    get_snippets, get_stats, get_transform_chains, get_trash, get_usage_events, list_transforms,
+    peek, promote_to_snippet, reload_snippet_packs, remove_exclusion, remove_from_collection,
+    rename_collection, reorder_collection_items, reorder_collections, restore_item,
+    revert_item_revision, save_transform_chain, search, set_annotation, set_favorite,
+    set_queue_mode, update_item_content, update_settings, update_snippet, AppState,
 };
-use std::sync::Arc;
+use models::NearDuplicateAction;
+use std::sync::{Arc, Mutex};
 use tauri::Manager;
 use tauri_plugin_global_shortcut::GlobalShortcutExt;
 
+/// App the user was in before opening the window, if it can be determined.
+/// Read before the window is shown, while the other app still has focus.
+fn context_app() -> Option<String> {
+    let app = platform::get_frontmost_app();
+    (app != "Unknown" && app != "SmartClipboard").then_some(app)
+}
+
+/// Store a capture from the monitor. Text decoded from an image's QR codes is
+/// stored as items derived from the image.
+fn store_capture(db: &db::Database, mut item: clipmon::NewClipboardItem) -> error::Result<i64> {
+    let decoded_text = std::mem::take(&mut item.decoded_text);
+    let id = store_item(db, item)?;
+
+    for text_item in decoded_text {
+        let linked = store_item(db, text_item).and_then(|text_id| db.link_derivations(text_id, &[id]));
+        if let Err(e) = linked {
+            log::error!("Failed to store decoded QR code text: {}", e);
+        }
+    }
+
+    Ok(id)
+}
+
+/// Store one item along with its image data. A near-duplicate image is merged
+/// into the existing item or linked to it, per settings.
+fn store_item(db: &db::Database, item: clipmon::NewClipboardItem) -> error::Result<i64> {
+    let near_duplicate = match item.image_dhash {
+        Some(dhash) => {
+            let policy = db.get_settings()?.near_duplicate_images;
+            db.find_near_duplicate(dhash, policy.max_distance)?
+                .map(|(id, _)| (id, policy.action))
+        }
+        None => None,
+    };
+
+    if let Some((existing_id, NearDuplicateAction::Merge)) = near_duplicate {
+        db.merge_capture(existing_id, &item.source_app, item.copied_at)?;
+        let new_files = item.thumbnails.iter().flat_map(|thumbs| [&thumbs.small, &thumbs.medium]);
+        for path in item.image_path.iter().chain(new_files) {
+            let _ = std::fs::remove_file(path);
+        }
+        return Ok(existing_id);
+    }
+
+    let id = db.insert_item(
+        item.content,
+        item.content_type,
+        item.image_path,
+        item.category,
+        item.source_app,
+        item.is_sensitive,
+        item.hash,
+        item.preview,
+        item.copied_at,
+    )?;
+
+    if let Some(metadata) = &item.image_metadata {
+        if let Err(e) = db.set_image_metadata(id, metadata) {
+            log::error!("Failed to record image metadata: {}", e);
+        }
+    }
+    if let Some(dhash) = item.image_dhash {
+        if let Err(e) = db.set_image_dhash(id, dhash, near_duplicate.map(|(other, _)| other)) {
+            log::error!("Failed to record image hash: {}", e);
+        }
+    }
+    if let Some(thumbnails) = item.thumbnails {
+        // A re-copied image keeps the thumbnails it already has
+        let recorded = db.set_thumbnails(id, &thumbnails).unwrap_or_else(|e| {
+            log::error!("Failed to record thumbnails: {}", e);
+            false
+        });
+        if !recorded {
+            let _ = std::fs::remove_file(&thumbnails.small);
+            let _ = std::fs::remove_file(&thumbnails.medium);
+        }
+    }
+
+    Ok(id)
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     env_logger::init();
 
     tauri::Builder::default()
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .register_asynchronous_uri_scheme_protocol(image_protocol::SCHEME, |ctx, request, responder| {
+            // Decoding thumbnails can take a while; keep it off the main thread
+            // on the runtime's bounded blocking pool rather than a thread per request
+            let state = ctx.app_handle().state::<AppState>();
+            let db = state.db.clone();
+            let images_dir = state.monitor.images_dir();
+            tauri::async_runtime::spawn_blocking(move || {
+                responder.respond(image_protocol::respond(&db, &images_dir, request.uri()));
+            });
+        })
         .setup(|app| {
             use tauri_plugin_global_shortcut::ShortcutState;
 
@@ -36,6 +145,9 @@ pub fn run() {
                 if let Err(e) = db.cleanup_expired(settings.retention_days) {
                     log::error!("Failed to cleanup expired items on startup: {}", e);
                 }
+                if let Err(e) = db.purge_trash(settings.trash_retention_days) {
+                    log::error!("Failed to purge trash on startup: {}", e);
+                }
             }
 
             // Initialize clipboard monitor
@@ -46,6 +158,7 @@ pub fn run() {
             if let Ok(settings) = db.get_settings() {
                 monitor.set_auto_exclude_sensitive(settings.auto_exclude_sensitive);
                 monitor.set_max_image_size_mb(settings.max_image_size_mb);
+                monitor.set_oversized_image_policy(settings.oversized_images);
             }
 
             if let Ok(exclusions) = db.get_exclusions() {
@@ -55,22 +168,47 @@ pub fn run() {
             // Start clipboard monitor
             monitor.start();
 
+            // Create thumbnails for images captured before they existed
+            let db_clone = db.clone();
+            let images_dir = monitor.images_dir();
+            std::thread::spawn(move || {
+                if let Err(e) = thumbnails::backfill(&db_clone, &images_dir) {
+                    log::error!("Failed to backfill thumbnails: {}", e);
+                }
+            });
+
+            // Load team snippet packs and keep watching the directory for changes
+            let packs = Arc::new(snippet_packs::SnippetPackWatcher::new(db.clone()));
+            if let Ok(settings) = db.get_settings() {
+                packs.set_dir(&settings.snippet_pack_dir);
+            }
+            packs.start();
+
+            // Paste queue lives in backend state so it survives the window being hidden
+            let queue = Arc::new(paste_queue::PasteQueue::new());
+
             // Handle clipboard items from monitor in background
             let db_clone = db.clone();
+            let queue_clone = queue.clone();
             std::thread::spawn(move || {
-                for item in receiver {
-                    if let Err(e) = db_clone.insert_item(
-                        item.content,
-                        item.content_type,
-                        item.image_path,
-                        item.category,
-                        item.source_app,
-                        item.is_sensitive,
-                        item.hash,
-                        item.preview,
-                        item.copied_at,
-                    ) {
-                        log::error!("Failed to insert clipboard item: {}", e);
+                for event in receiver {
+                    let item = match event {
+                        clipmon::MonitorEvent::Captured(item) => item,
+                        clipmon::MonitorEvent::SkippedSensitive { source_app, skipped_at } => {
+                            if let Err(e) = db_clone.record_skipped_capture("sensitive", &source_app, skipped_at) {
+                                log::error!("Failed to record skipped clipboard item: {}", e);
+                            }
+                            continue;
+                        }
+                    };
+
+                    match store_capture(&db_clone, *item) {
+                        Ok(id) => {
+                            if queue_clone.capture_new() {
+                                queue_clone.push(id);
+                            }
+                        }
+                        Err(e) => log::error!("Failed to insert clipboard item: {}", e),
                     }
                 }
             });
@@ -85,14 +223,21 @@ pub fn run() {
                         if let Err(e) = db_clone.cleanup_expired(settings.retention_days) {
                             log::error!("Failed to cleanup expired items: {}", e);
                         }
+                        if let Err(e) = db_clone.purge_trash(settings.trash_retention_days) {
+                            log::error!("Failed to purge trash: {}", e);
+                        }
                     }
                 }
             });
 
+            // Frontmost app at the moment the window is shown; history is ordered for it
+            let opened_from: Arc<Mutex<Option<String>>> = Arc::new(Mutex::new(None));
+
             // Set up tray icon with click handler
             let tray = app.tray_by_id("main-tray").expect("Tray icon not found");
             let window = app.get_webview_window("main").expect("Window not found");
             let window_clone = window.clone();
+            let opened_from_tray = opened_from.clone();
 
             tray.on_tray_icon_event(move |_tray, event| {
                 if let tauri::tray::TrayIconEvent::Click { .. } = event {
@@ -112,6 +257,7 @@ pub fn run() {
                                 ));
                             }
                         }
+                        *opened_from_tray.lock().unwrap() = context_app();
                         let _ = window_clone.show();
                         let _ = window_clone.set_focus();
                     }
@@ -120,6 +266,7 @@ pub fn run() {
 
             // Register global keyboard shortcut (Cmd+Shift+V)
             let window_for_shortcut = window.clone();
+            let opened_from_shortcut = opened_from.clone();
 
             app.global_shortcut().on_shortcut("CmdOrCtrl+Shift+V", move |_app, _shortcut, event| {
                 if event.state() == ShortcutState::Pressed {
@@ -138,31 +285,97 @@ pub fn run() {
                                 ));
                             }
                         }
+                        *opened_from_shortcut.lock().unwrap() = context_app();
                         let _ = window_for_shortcut.show();
                         let _ = window_for_shortcut.set_focus();
                     }
                 }
             })?;
 
+            // Register paste-next shortcut (Cmd+Alt+V): places the next queued item on the clipboard
+            let db_for_queue = db.clone();
+            let monitor_for_queue = monitor.clone();
+            let queue_for_shortcut = queue.clone();
+
+            app.global_shortcut().on_shortcut("CmdOrCtrl+Alt+V", move |_app, _shortcut, event| {
+                if event.state() == ShortcutState::Pressed {
+                    match handlers::paste_next_from_queue(
+                        &db_for_queue,
+                        &monitor_for_queue,
+                        &handlers::SystemClipboard,
+                        &queue_for_shortcut,
+                    ) {
+                        Ok(Some(item)) => log::debug!("Paste queue advanced to item {}", item.id),
+                        Ok(None) => log::debug!("Paste queue is empty"),
+                        Err(e) => log::error!("Failed to paste next queued item: {}", e),
+                    }
+                }
+            })?;
+
             log::info!("SmartClipboard initialized successfully");
 
             // Store state
-            app.manage(AppState { db, monitor });
+            app.manage(AppState { db, monitor, queue, packs, opened_from });
 
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             get_history,
             search,
+            get_item,
+            get_item_content_chunk,
+            get_frequent_items,
+            get_usage_events,
+            find_similar_images,
+            get_stats,
             copy_to_clipboard,
             set_favorite,
+            set_annotation,
+            update_item_content,
+            get_item_revisions,
+            revert_item_revision,
             delete_item,
+            restore_item,
+            get_trash,
+            bulk_update,
+            clear_history,
             get_settings,
             update_settings,
             get_exclusions,
             add_exclusion,
             remove_exclusion,
-            get_image_data,
+            enqueue,
+            dequeue,
+            peek,
+            clear_queue,
+            get_queue,
+            set_queue_mode,
+            copy_transformed,
+            copy_joined,
+            get_derived_from,
+            list_transforms,
+            get_transform_chains,
+            save_transform_chain,
+            delete_transform_chain,
+            get_snippets,
+            get_snippet,
+            create_snippet,
+            update_snippet,
+            delete_snippet,
+            find_snippets,
+            promote_to_snippet,
+            expand_snippet,
+            get_snippet_pack_diagnostics,
+            reload_snippet_packs,
+            get_collections,
+            create_collection,
+            rename_collection,
+            delete_collection,
+            reorder_collections,
+            get_collection_items,
+            add_to_collection,
+            remove_from_collection,
+            reorder_collection_items,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -1,3 +1,4 @@
+use crate::error::{AppError, Result};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,16 +15,279 @@ pub struct ClipboardItem {
     pub is_favorite: bool,
     pub is_sensitive: bool,
     pub hash: String,
+    pub title: Option<String>,      // user-provided label
+    pub note: Option<String>,       // user-provided free text, searchable
+    pub deleted_at: Option<i64>,    // set while the item is in the trash
+    pub use_count: i64,             // re-captures plus paste-backs
+    pub last_used_at: Option<i64>,  // last capture or paste-back
 }
 
+/// An item as shown in lists: everything but the content, which can be
+/// megabytes. The detail view loads it with `get_item`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+pub struct ClipboardListItem {
+    pub id: i64,
+    pub content_type: String,
+    pub image_path: Option<String>,
+    pub category: String,
+    pub source_app: String,
+    pub preview: String,
+    pub content_length: usize,      // characters
+    pub line_count: usize,
+    pub copied_at: i64,
+    pub is_favorite: bool,
+    pub is_sensitive: bool,
+    pub hash: String,
+    pub title: Option<String>,
+    pub note: Option<String>,
+    pub deleted_at: Option<i64>,
+    pub use_count: i64,
+    pub last_used_at: Option<i64>,
+}
+
+impl From<ClipboardItem> for ClipboardListItem {
+    fn from(item: ClipboardItem) -> Self {
+        ClipboardListItem {
+            id: item.id,
+            content_length: item.content.chars().count(),
+            line_count: item.content.split('\n').count(),
+            content_type: item.content_type,
+            image_path: item.image_path,
+            category: item.category,
+            source_app: item.source_app,
+            preview: item.preview,
+            copied_at: item.copied_at,
+            is_favorite: item.is_favorite,
+            is_sensitive: item.is_sensitive,
+            hash: item.hash,
+            title: item.title,
+            note: item.note,
+            deleted_at: item.deleted_at,
+            use_count: item.use_count,
+            last_used_at: item.last_used_at,
+        }
+    }
+}
+
+/// A slice of an item's content. Offsets are UTF-8 byte offsets and always
+/// fall on character boundaries.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentChunk {
+    pub text: String,
+    pub offset: usize,
+    pub next_offset: Option<usize>, // None once the content is complete
+    pub total_bytes: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemDetail {
+    pub item: ClipboardListItem,
+    pub content: ContentChunk,      // first chunk; fetch the rest with get_item_content_chunk
+    pub image: Option<ImageMetadata>, // image items captured since metadata was recorded
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct SearchFilters {
     pub category: Option<String>,
     pub date_from: Option<i64>,     // unix timestamp
     pub date_to: Option<i64>,
     pub source_app: Option<String>,
     pub content_type: Option<String>,
+    pub collection_id: Option<i64>,
+    pub is_favorite: Option<bool>,
+    pub sort: Option<SearchSort>,   // not a filter; defaults to relevance
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SearchSort {
+    #[default]
+    Relevance,
+    Newest,
+    Oldest,
+    MostUsed,
+}
+
+impl SearchSort {
+    /// Sorts that are paged with a `PageCursor`; the others return a single page
+    pub fn is_chronological(self) -> bool {
+        matches!(self, SearchSort::Newest | SearchSort::Oldest)
+    }
+}
+
+/// Weights for the relevance score. Each signal is scaled to roughly 0..1
+/// except text, which is the (positive) FTS5 bm25 score.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RankingWeights {
+    pub text: f64,
+    pub recency: f64,
+    pub recency_half_life_days: f64,    // age at which the recency signal halves
+    pub favorite: f64,
+    pub usage: f64,
+}
+
+impl Default for RankingWeights {
+    fn default() -> Self {
+        Self {
+            text: 1.0,
+            recency: 2.0,
+            recency_half_life_days: 7.0,
+            favorite: 1.0,
+            usage: 1.0,
+        }
+    }
+}
+
+/// What the monitor does with an image whose PNG exceeds `max_image_size_mb`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OversizedImageAction {
+    Skip,
+    Downscale,  // fit within max_dimension, still PNG
+    Recompress, // lossy WebP or JPEG at quality
+}
+
+/// Lossy formats an oversized image can be recompressed to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecompressFormat {
+    Webp,
+    Jpeg,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OversizedImagePolicy {
+    pub action: OversizedImageAction,
+    pub max_dimension: u32,     // longest side after downscaling, in pixels
+    pub format: RecompressFormat,
+    pub quality: u8,            // 1-100
+}
+
+impl Default for OversizedImagePolicy {
+    fn default() -> Self {
+        Self {
+            action: OversizedImageAction::Downscale,
+            max_dimension: 1920,
+            format: RecompressFormat::Webp,
+            quality: 80,
+        }
+    }
+}
+
+/// Encoding of a stored image file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StoredImageFormat {
+    Png,
+    Webp,
+    Jpeg,
+}
+
+impl StoredImageFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            StoredImageFormat::Png => "png",
+            StoredImageFormat::Webp => "webp",
+            StoredImageFormat::Jpeg => "jpeg",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "png" => Some(StoredImageFormat::Png),
+            "webp" => Some(StoredImageFormat::Webp),
+            "jpeg" => Some(StoredImageFormat::Jpeg),
+            _ => None,
+        }
+    }
+
+    /// File extension for stored images
+    pub fn extension(self) -> &'static str {
+        match self {
+            StoredImageFormat::Jpeg => "jpg",
+            other => other.as_str(),
+        }
+    }
+}
+
+impl From<RecompressFormat> for StoredImageFormat {
+    fn from(format: RecompressFormat) -> Self {
+        match format {
+            RecompressFormat::Webp => StoredImageFormat::Webp,
+            RecompressFormat::Jpeg => StoredImageFormat::Jpeg,
+        }
+    }
+}
+
+/// How an image item was stored, next to the dimensions it was copied at
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageMetadata {
+    pub original_width: u32,
+    pub original_height: u32,
+    pub width: u32,
+    pub height: u32,
+    pub format: StoredImageFormat,
+    pub byte_size: u64,
+}
+
+/// What happens to a new image whose perceptual hash is within
+/// `max_distance` bits of an existing image
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NearDuplicateAction {
+    Merge,  // count it as a re-copy of the existing item; the new file is dropped
+    Link,   // keep it as its own item, linked to the existing one
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NearDuplicatePolicy {
+    pub action: NearDuplicateAction,
+    pub max_distance: u32,      // differing bits of the 64-bit dHash, 0-16
+}
+
+impl Default for NearDuplicatePolicy {
+    fn default() -> Self {
+        Self { action: NearDuplicateAction::Link, max_distance: 5 }
+    }
+}
+
+/// An image item that looks like another one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimilarImage {
+    pub item: ClipboardListItem,
+    pub distance: u32,          // differing dHash bits; 0 is visually identical
+    pub linked: bool,           // recorded as a near-duplicate when captured
+}
+
+impl SearchFilters {
+    /// True when no filter is set
+    pub fn is_empty(&self) -> bool {
+        self.category.is_none()
+            && self.date_from.is_none()
+            && self.date_to.is_none()
+            && self.source_app.is_none()
+            && self.content_type.is_none()
+            && self.collection_id.is_none()
+            && self.is_favorite.is_none()
+    }
+}
+
+/// How search text is matched against items
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchMode {
+    Exact,      // whole-token matches only
+    #[default]
+    Fuzzy,      // token matches, then substrings, then small typos for single words
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,6 +298,20 @@ pub struct Settings {
     pub keyboard_shortcut: String,
     pub auto_exclude_sensitive: bool,
     pub max_image_size_mb: u32,
+    #[serde(default)]
+    pub snippet_pack_dir: String,   // empty = no team snippet packs
+    #[serde(default = "default_trash_retention_days")]
+    pub trash_retention_days: u32,
+    #[serde(default)]
+    pub ranking_weights: RankingWeights,
+    #[serde(default)]
+    pub oversized_images: OversizedImagePolicy,
+    #[serde(default)]
+    pub near_duplicate_images: NearDuplicatePolicy,
+}
+
+fn default_trash_retention_days() -> u32 {
+    7
 }
 
 impl Default for Settings {
@@ -44,6 +322,394 @@ impl Default for Settings {
             keyboard_shortcut: "CmdOrCtrl+Shift+V".to_string(),
             auto_exclude_sensitive: true,
             max_image_size_mb: 5,
+            snippet_pack_dir: String::new(),
+            trash_retention_days: default_trash_retention_days(),
+            ranking_weights: RankingWeights::default(),
+            oversized_images: OversizedImagePolicy::default(),
+            near_duplicate_images: NearDuplicatePolicy::default(),
         }
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QueueOrder {
+    Fifo,
+    Lifo,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueState {
+    pub order: QueueOrder,
+    pub capture_new: bool,     // append every new capture to the queue
+    pub item_ids: Vec<i64>,    // stored front-to-back, regardless of order
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransformInfo {
+    pub name: String,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransformChain {
+    pub name: String,
+    pub transforms: Vec<String>,   // transform names, applied in order
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JoinResult {
+    pub text: String,
+    pub item_id: Option<i64>,      // set when the joined text was saved to history
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snippet {
+    pub id: i64,
+    pub name: String,
+    pub abbreviation: Option<String>,
+    pub folder: Option<String>,
+    pub content: String,
+    pub tags: Vec<String>,
+    pub pack: Option<String>,       // source pack file; pack snippets are read-only
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnippetInput {
+    pub name: String,
+    pub abbreviation: Option<String>,
+    pub folder: Option<String>,
+    pub content: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl SnippetInput {
+    /// Trim fields, turn blanks into `None` and reject unusable values.
+    pub fn normalize(self) -> Result<Self> {
+        let name = self.name.trim().to_string();
+        if name.is_empty() {
+            return Err(AppError::InvalidInput("Snippet name cannot be empty".to_string()));
+        }
+
+        let abbreviation = self
+            .abbreviation
+            .map(|a| a.trim().to_string())
+            .filter(|a| !a.is_empty());
+        if abbreviation.as_ref().is_some_and(|a| a.chars().any(char::is_whitespace)) {
+            return Err(AppError::InvalidInput(
+                "Snippet abbreviation cannot contain whitespace".to_string(),
+            ));
+        }
+
+        let folder = self
+            .folder
+            .map(|f| f.trim().to_string())
+            .filter(|f| !f.is_empty());
+
+        let mut tags: Vec<String> = Vec::new();
+        for tag in self.tags {
+            let tag = tag.trim().to_string();
+            if !tag.is_empty() && !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+
+        Ok(SnippetInput {
+            name,
+            abbreviation,
+            folder,
+            content: self.content,
+            tags,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackDiagnostic {
+    pub path: String,
+    pub snippet_count: usize,
+    pub error: Option<String>,      // parse error; the file's previous snippets are kept
+    pub warnings: Vec<String>,
+}
+
+/// Byte range of a match within `ClipboardItem::content`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchRange {
+    pub start: usize,
+    pub end: usize,             // exclusive
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit<T = ClipboardItem> {
+    pub item: T,
+    pub snippet: Option<String>,    // FTS5 snippet(); matches wrapped in U+0002 ... U+0003
+    pub matches: Vec<MatchRange>,
+}
+
+impl SearchHit {
+    pub fn into_list_hit(self) -> SearchHit<ClipboardListItem> {
+        SearchHit { item: self.item.into(), snippet: self.snippet, matches: self.matches }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResults {
+    pub items: Vec<SearchHit<ClipboardListItem>>,
+    pub snippets: Vec<Snippet>,
+    pub next_cursor: Option<String>, // `PageCursor` for newest and oldest, always `None` for ranked sorts
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum TemplateExpansion {
+    Complete { text: String },
+    NeedsInput { prompts: Vec<String> },   // `{input:...}` labels the UI must ask for
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Collection {
+    pub id: i64,
+    pub name: String,
+    pub position: i64,
+    pub item_count: i64,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemRevision {
+    pub id: i64,
+    pub item_id: i64,
+    pub content: String,            // content before the edit
+    pub replaced_at: i64,
+}
+
+/// Which items a bulk operation applies to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "by", rename_all = "camelCase")]
+pub enum BulkSelection {
+    Ids { ids: Vec<i64> },
+    Search { query: String, filters: SearchFilters },   // empty query = filters only
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum BulkAction {
+    Delete,                                 // moves items to the trash
+    Favorite,
+    Unfavorite,
+    AddToCollection { collection_id: i64 },
+    Retag { category: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkResult {
+    pub affected: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageEvent {
+    pub kind: String,               // "capture" | "paste"
+    pub app: Option<String>,        // source app for captures, target app for pastes
+    pub occurred_at: i64,
+}
+
+/// Why an item was moved up in a history list ordered for the app the window was opened from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ContextReason {
+    PastedIntoApp,                  // pasted into this app before
+    FrequentCategory,               // its category is common in this app
+    CopiedFromApp,                  // captured from this app
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    pub item: ClipboardListItem,
+    pub reason: Option<ContextReason>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryPage {
+    pub context_app: Option<String>, // frontmost app when the window was shown
+    pub items: Vec<HistoryEntry>,
+    pub next_cursor: Option<String>, // None on the last page
+}
+
+/// Time bucket for statistics over time
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StatsBucket {
+    #[default]
+    Day,
+    Hour,
+}
+
+impl StatsBucket {
+    pub fn seconds(self) -> i64 {
+        match self {
+            StatsBucket::Day => 86_400,
+            StatsBucket::Hour => 3_600,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyCount {
+    pub key: String,
+    pub count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeCount {
+    pub start: i64,                 // bucket start, unix timestamp aligned to local midnight/hour
+    pub count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClipboardStats {
+    pub total_items: i64,
+    pub favorite_count: i64,
+    pub sensitive_skipped: i64,     // sensitive copies never stored (auto-exclusion)
+    pub text_bytes: i64,            // UTF-8 size of text content
+    pub image_bytes: i64,           // size of stored image files
+    pub by_category: Vec<KeyCount>,
+    pub by_source_app: Vec<KeyCount>,
+    pub by_content_type: Vec<KeyCount>,
+    pub by_time: Vec<TimeCount>,    // items by the time they were last copied
+    pub capture_rate: Vec<TimeCount>, // every capture, re-copies included
+}
+
+/// Position after the last row of a page, in keyset order. Sent to the UI as
+/// an opaque string so the encoding can change without breaking callers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageCursor {
+    pub is_favorite: bool,
+    pub copied_at: i64,
+    pub id: i64,
+}
+
+impl PageCursor {
+    pub fn after(item: &ClipboardListItem) -> Self {
+        PageCursor { is_favorite: item.is_favorite, copied_at: item.copied_at, id: item.id }
+    }
+
+    pub fn encode(&self) -> String {
+        use base64::Engine;
+        let raw = format!("{}:{}:{}", self.is_favorite as u8, self.copied_at, self.id);
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(raw)
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self> {
+        use base64::Engine;
+        let invalid = || AppError::InvalidInput("Invalid page cursor".to_string());
+
+        let raw = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(cursor)
+            .map_err(|_| invalid())?;
+        let raw = String::from_utf8(raw).map_err(|_| invalid())?;
+        let mut parts = raw.split(':');
+        let (Some(favorite), Some(copied_at), Some(id), None) = (parts.next(), parts.next(), parts.next(), parts.next()) else {
+            return Err(invalid());
+        };
+
+        Ok(PageCursor {
+            is_favorite: match favorite {
+                "0" => false,
+                "1" => true,
+                _ => return Err(invalid()),
+            },
+            copied_at: copied_at.parse().map_err(|_| invalid())?,
+            id: id.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// Position in history ordered for an app: the boosted items shown so far,
+/// which later pages skip, and the position in plain history after them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextCursor {
+    pub after: Option<PageCursor>,
+    pub boosted: Vec<i64>,
+}
+
+impl ContextCursor {
+    /// Cursor for the page after `page`, which followed `previous`
+    pub fn after_page(previous: Option<&ContextCursor>, page: &[HistoryEntry]) -> Self {
+        let mut boosted = previous.map(|cursor| cursor.boosted.clone()).unwrap_or_default();
+        boosted.extend(page.iter().filter(|entry| entry.reason.is_some()).map(|entry| entry.item.id));
+        let after = page
+            .iter()
+            .rev()
+            .find(|entry| entry.reason.is_none())
+            .map(|entry| PageCursor::after(&entry.item))
+            .or(previous.and_then(|cursor| cursor.after));
+        ContextCursor { after, boosted }
+    }
+
+    pub fn encode(&self) -> String {
+        use base64::Engine;
+        let after = self.after.map(|cursor| cursor.encode()).unwrap_or_default();
+        let boosted: Vec<String> = self.boosted.iter().map(|id| id.to_string()).collect();
+        let raw = format!("ctx;{};{}", after, boosted.join(","));
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(raw)
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self> {
+        use base64::Engine;
+        let invalid = || AppError::InvalidInput("Invalid page cursor".to_string());
+
+        let raw = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(cursor)
+            .map_err(|_| invalid())?;
+        let raw = String::from_utf8(raw).map_err(|_| invalid())?;
+        let mut parts = raw.split(';');
+        let (Some("ctx"), Some(after), Some(boosted), None) = (parts.next(), parts.next(), parts.next(), parts.next()) else {
+            return Err(invalid());
+        };
+
+        Ok(ContextCursor {
+            after: (!after.is_empty()).then(|| PageCursor::decode(after)).transpose()?,
+            boosted: boosted
+                .split(',')
+                .filter(|id| !id.is_empty())
+                .map(|id| id.parse().map_err(|_| invalid()))
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+/// Which rows of a list to return: up to `limit` rows after `after`
+#[derive(Debug, Clone, Copy)]
+pub struct PageRequest {
+    pub after: Option<PageCursor>,
+    pub limit: u32,
+}
+
+impl PageRequest {
+    pub fn first(limit: u32) -> Self {
+        PageRequest { after: None, limit }
+    }
+}
@@ -0,0 +1,133 @@
+use crate::models::{QueueOrder, QueueState};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+struct QueueInner {
+    items: VecDeque<i64>,
+    order: QueueOrder,
+    capture_new: bool,
+}
+
+/// In-memory paste queue shared between commands, the monitor consumer and the
+/// paste-next shortcut. Lives in `AppState`, so it outlives the window.
+pub struct PasteQueue {
+    inner: Mutex<QueueInner>,
+}
+
+impl Default for PasteQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PasteQueue {
+    pub fn new() -> Self {
+        PasteQueue {
+            inner: Mutex::new(QueueInner {
+                items: VecDeque::new(),
+                order: QueueOrder::Fifo,
+                capture_new: false,
+            }),
+        }
+    }
+
+    /// Append an item id; returns the new queue length
+    pub fn push(&self, id: i64) -> usize {
+        let mut inner = self.inner.lock().unwrap();
+        inner.items.push_back(id);
+        inner.items.len()
+    }
+
+    /// Remove and return the next id according to the current order
+    pub fn pop(&self) -> Option<i64> {
+        let mut inner = self.inner.lock().unwrap();
+        match inner.order {
+            QueueOrder::Fifo => inner.items.pop_front(),
+            QueueOrder::Lifo => inner.items.pop_back(),
+        }
+    }
+
+    /// Return the next id without removing it
+    pub fn peek(&self) -> Option<i64> {
+        let inner = self.inner.lock().unwrap();
+        match inner.order {
+            QueueOrder::Fifo => inner.items.front().copied(),
+            QueueOrder::Lifo => inner.items.back().copied(),
+        }
+    }
+
+    pub fn clear(&self) {
+        self.inner.lock().unwrap().items.clear();
+    }
+
+    /// Drop every queued occurrence of an id (e.g. after the item was deleted)
+    pub fn remove(&self, id: i64) {
+        self.inner.lock().unwrap().items.retain(|&queued| queued != id);
+    }
+
+    pub fn set_mode(&self, order: QueueOrder, capture_new: bool) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.order = order;
+        inner.capture_new = capture_new;
+    }
+
+    /// Whether newly captured items should be appended automatically
+    pub fn capture_new(&self) -> bool {
+        self.inner.lock().unwrap().capture_new
+    }
+
+    pub fn state(&self) -> QueueState {
+        let inner = self.inner.lock().unwrap();
+        QueueState {
+            order: inner.order,
+            capture_new: inner.capture_new,
+            item_ids: inner.items.iter().copied().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PasteQueue;
+    use crate::models::QueueOrder;
+
+    #[test]
+    fn test_fifo_pops_in_insertion_order() {
+        let queue = PasteQueue::new();
+        queue.push(1);
+        queue.push(2);
+        queue.push(3);
+
+        assert_eq!(queue.peek(), Some(1));
+        assert_eq!(queue.pop(), Some(1));
+        assert_eq!(queue.pop(), Some(2));
+        assert_eq!(queue.pop(), Some(3));
+        assert_eq!(queue.pop(), None);
+    }
+
+    #[test]
+    fn test_lifo_pops_most_recent_first() {
+        let queue = PasteQueue::new();
+        queue.set_mode(QueueOrder::Lifo, false);
+        queue.push(1);
+        queue.push(2);
+
+        assert_eq!(queue.peek(), Some(2));
+        assert_eq!(queue.pop(), Some(2));
+        assert_eq!(queue.pop(), Some(1));
+    }
+
+    #[test]
+    fn test_remove_and_clear() {
+        let queue = PasteQueue::new();
+        queue.push(1);
+        queue.push(2);
+        queue.push(1);
+
+        queue.remove(1);
+        assert_eq!(queue.state().item_ids, vec![2]);
+
+        queue.clear();
+        assert!(queue.state().item_ids.is_empty());
+    }
+}
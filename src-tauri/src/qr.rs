@@ -0,0 +1,110 @@
+//! QR code decoding for captured images, so text hidden in a screenshot
+//! (2FA setup, Wi-Fi config, links) becomes searchable and is checked for
+//! sensitive content like any copied text.
+
+use image::imageops::FilterType;
+use image::GrayImage;
+
+/// Longest side of the copy that codes are first looked for in. Screenshots
+/// of large displays are several times this; codes in them stay decodable.
+const MAX_SCAN_DIMENSION: u32 = 1600;
+
+/// Text of every QR code found in the image, in detection order, without
+/// duplicates. Codes that fail to decode are skipped. Large images are
+/// scanned downscaled first, and again at full size only when that finds a
+/// code it cannot read.
+pub fn decode_qr_codes(image: &GrayImage) -> Vec<String> {
+    if image.width().max(image.height()) <= MAX_SCAN_DIMENSION {
+        return scan(image).0;
+    }
+
+    let scaled = image::imageops::resize(
+        image,
+        scaled_dimension(image.width(), image.height()),
+        scaled_dimension(image.height(), image.width()),
+        FilterType::Nearest,    // smoothing blurs module edges below what the detector reads
+    );
+    match scan(&scaled) {
+        (_, undecoded) if undecoded > 0 => scan(image).0,
+        (decoded, _) => decoded,
+    }
+}
+
+/// `side` scaled so the longer of `side` and `other` is `MAX_SCAN_DIMENSION`
+fn scaled_dimension(side: u32, other: u32) -> u32 {
+    let longest = side.max(other) as u64;
+    ((side as u64 * MAX_SCAN_DIMENSION as u64 / longest) as u32).max(1)
+}
+
+/// Decoded texts and the number of detected codes that could not be decoded
+fn scan(image: &GrayImage) -> (Vec<String>, usize) {
+    let mut prepared = rqrr::PreparedImage::prepare_from_greyscale(
+        image.width() as usize,
+        image.height() as usize,
+        |x, y| image.get_pixel(x as u32, y as u32)[0],
+    );
+
+    let mut decoded: Vec<String> = Vec::new();
+    let mut undecoded = 0;
+    for grid in prepared.detect_grids() {
+        match grid.decode() {
+            Ok((_, text)) => {
+                let text = text.trim().to_string();
+                if !text.is_empty() && !decoded.contains(&text) {
+                    decoded.push(text);
+                }
+            }
+            Err(e) => {
+                log::debug!("Skipping undecodable QR code: {}", e);
+                undecoded += 1;
+            }
+        }
+    }
+    (decoded, undecoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_qr_codes, scaled_dimension, MAX_SCAN_DIMENSION};
+    use image::{GrayImage, Luma};
+
+    /// Render `data` as a QR code with `scale` pixels per module at (`left`, `top`)
+    fn draw_qr(canvas: &mut GrayImage, data: &str, left: u32, top: u32, scale: u32) {
+        let code = qrcode::QrCode::new(data).expect("qr");
+        let width = code.width() as u32;
+        for (i, color) in code.to_colors().into_iter().enumerate() {
+            if color == qrcode::Color::Dark {
+                let (mx, my) = (i as u32 % width, i as u32 / width);
+                for dy in 0..scale {
+                    for dx in 0..scale {
+                        canvas.put_pixel(left + mx * scale + dx, top + my * scale + dy, Luma([0]));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_decodes_qr_codes_in_a_screenshot() {
+        let secret = "otpauth://totp/Example:alice?secret=JBSWY3DPEHPK3PXP&issuer=Example";
+        let mut screenshot = GrayImage::from_pixel(640, 360, Luma([255]));
+        draw_qr(&mut screenshot, secret, 40, 40, 4);
+        draw_qr(&mut screenshot, "WIFI:T:WPA;S:Office;P:hunter22;;", 360, 40, 4);
+
+        let decoded = decode_qr_codes(&screenshot);
+        assert_eq!(decoded, vec![secret.to_string(), "WIFI:T:WPA;S:Office;P:hunter22;;".to_string()]);
+
+        assert!(decode_qr_codes(&GrayImage::from_pixel(200, 200, Luma([255]))).is_empty());
+    }
+
+    #[test]
+    fn test_large_screenshots_are_scanned_downscaled() {
+        assert_eq!((scaled_dimension(4800, 2700), scaled_dimension(2700, 4800)), (MAX_SCAN_DIMENSION, 900));
+        assert_eq!(scaled_dimension(10, 100_000), 1);
+
+        let secret = "otpauth://totp/Example:alice?secret=JBSWY3DPEHPK3PXP&issuer=Example";
+        let mut screenshot = GrayImage::from_pixel(4800, 2700, Luma([255]));
+        draw_qr(&mut screenshot, secret, 101, 50, 12);
+        assert_eq!(decode_qr_codes(&screenshot), vec![secret.to_string()]);
+    }
+}
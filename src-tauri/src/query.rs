@@ -0,0 +1,444 @@
+use crate::categorizer::CATEGORIES;
+use crate::error::{AppError, Result};
+use crate::models::SearchFilters;
+use crate::fuzzy::within_edit_distance;
+use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone};
+use regex::{Regex, RegexBuilder};
+use std::sync::LazyLock;
+
+static WORD_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"[\p{L}\p{N}]+").unwrap());
+
+/// Upper bound on highlighted ranges returned for one item
+const MAX_MATCH_RANGES: usize = 100;
+
+/// A search string compiled into FTS5 expressions and item filters.
+///
+/// Supported syntax: bare words, `"exact phrase"`, `-exclude`, `OR` between
+/// terms, trailing `*` for prefix matches, and the operators `app:`, `cat:`,
+/// `type:`, `is:fav`, `before:` and `after:`. Operators and exclusions apply
+/// to the whole query regardless of where they appear.
+#[derive(Debug, Clone, Default)]
+pub struct ParsedQuery {
+    pub match_expr: Option<String>,     // FTS5 expression rows must match
+    pub exclude_expr: Option<String>,   // FTS5 expression rows must not match
+    pub filters: SearchFilters,         // filters set by operators
+    pub typo_term: Option<String>,      // lowercased lone word eligible for typo matching
+    pub highlight_terms: Vec<String>,   // positive words and phrases as typed
+}
+
+impl ParsedQuery {
+    /// Combine with filters chosen in the UI; operators typed in the query win
+    pub fn merge_filters(&self, base: SearchFilters) -> SearchFilters {
+        let own = self.filters.clone();
+        SearchFilters {
+            category: own.category.or(base.category),
+            date_from: own.date_from.or(base.date_from),
+            date_to: own.date_to.or(base.date_to),
+            source_app: own.source_app.or(base.source_app),
+            content_type: own.content_type.or(base.content_type),
+            collection_id: own.collection_id.or(base.collection_id),
+            is_favorite: own.is_favorite.or(base.is_favorite),
+            sort: base.sort,
+        }
+    }
+
+    /// Trigram OR-expression that finds candidate rows for typo matching.
+    /// Trigrams of every one-character deletion are included so that swapped
+    /// or mistyped letters still share a trigram with the intended word.
+    pub fn typo_candidates_expr(&self) -> Option<String> {
+        let chars: Vec<char> = self.typo_term.as_ref()?.chars().collect();
+
+        let mut variants = vec![chars.clone()];
+        for skip in 0..chars.len() {
+            let mut variant = chars.clone();
+            variant.remove(skip);
+            variants.push(variant);
+        }
+
+        let mut trigrams: Vec<String> = Vec::new();
+        for variant in &variants {
+            for window in variant.windows(3) {
+                let trigram = fts_string(&window.iter().collect::<String>());
+                if !trigrams.contains(&trigram) {
+                    trigrams.push(trigram);
+                }
+            }
+        }
+        Some(trigrams.join(" OR "))
+    }
+
+    /// Edits allowed between `typo_term` and a word in the item
+    pub fn max_typo_edits(&self) -> usize {
+        match &self.typo_term {
+            Some(term) if term.chars().count() > 6 => 2,
+            _ => 1,
+        }
+    }
+
+    /// True if any word of `text` is a typo-tolerant match for `typo_term`
+    pub fn has_typo_match(&self, text: &str) -> bool {
+        self.typo_term.as_deref().is_some_and(|term| {
+            WORD_REGEX
+                .find_iter(text)
+                .any(|word| within_edit_distance(&word.as_str().to_lowercase(), term, self.max_typo_edits()))
+        })
+    }
+
+    /// Sorted, non-overlapping byte ranges in `content` to highlight:
+    /// case-insensitive occurrences of each term plus typo-matched words.
+    pub fn match_ranges(&self, content: &str) -> Vec<(usize, usize)> {
+        let mut ranges: Vec<(usize, usize)> = Vec::new();
+
+        for term in &self.highlight_terms {
+            let Ok(regex) = RegexBuilder::new(&regex::escape(term)).case_insensitive(true).build() else {
+                continue;
+            };
+            ranges.extend(regex.find_iter(content).take(MAX_MATCH_RANGES).map(|m| (m.start(), m.end())));
+        }
+
+        if let Some(term) = &self.typo_term {
+            ranges.extend(
+                WORD_REGEX
+                    .find_iter(content)
+                    .filter(|word| within_edit_distance(&word.as_str().to_lowercase(), term, self.max_typo_edits()))
+                    .take(MAX_MATCH_RANGES)
+                    .map(|m| (m.start(), m.end())),
+            );
+        }
+
+        ranges.sort_unstable();
+        let mut merged: Vec<(usize, usize)> = Vec::with_capacity(ranges.len());
+        for (start, end) in ranges {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        merged.truncate(MAX_MATCH_RANGES);
+        merged
+    }
+
+    /// Single FTS5 expression for tables without the SQL filters (snippets).
+    /// `None` when the query has no positive terms.
+    pub fn fts_expression(&self) -> Option<String> {
+        let positive = self.match_expr.as_ref()?;
+        Some(match &self.exclude_expr {
+            Some(exclude) => format!("({}) NOT ({})", positive, exclude),
+            None => positive.clone(),
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Term { atom: String, text: String, word: Option<String>, negated: bool },
+    Field { name: String, value: String },
+    Or,
+}
+
+const FIELDS: &[&str] = &["app", "cat", "category", "type", "is", "before", "after"];
+
+/// Quote text as an FTS5 string so punctuation and keywords are matched literally
+fn fts_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "\"\""))
+}
+
+fn word_atom(word: &str) -> String {
+    match word.strip_suffix('*') {
+        Some(prefix) if !prefix.is_empty() => format!("{}*", fts_string(prefix)),
+        _ => fts_string(word),
+    }
+}
+
+fn read_quoted(chars: &[char], pos: &mut usize) -> Result<String> {
+    // `pos` points at the opening quote
+    let start = *pos + 1;
+    match chars[start..].iter().position(|c| *c == '"') {
+        Some(len) => {
+            *pos = start + len + 1;
+            Ok(chars[start..start + len].iter().collect())
+        }
+        None => Err(AppError::InvalidInput("Unclosed quote in search".to_string())),
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+
+    while pos < chars.len() {
+        if chars[pos].is_whitespace() {
+            pos += 1;
+            continue;
+        }
+
+        let negated = chars[pos] == '-';
+        if negated {
+            pos += 1;
+            if pos >= chars.len() || chars[pos].is_whitespace() {
+                return Err(AppError::InvalidInput("Expected a term after '-'".to_string()));
+            }
+        }
+
+        if chars[pos] == '"' {
+            let phrase = read_quoted(&chars, &mut pos)?;
+            tokens.push(Token::Term { atom: fts_string(&phrase), text: phrase, word: None, negated });
+            continue;
+        }
+
+        let start = pos;
+        while pos < chars.len() && !chars[pos].is_whitespace() && chars[pos] != '"' {
+            pos += 1;
+        }
+        let word: String = chars[start..pos].iter().collect();
+
+        if word == "OR" && !negated {
+            tokens.push(Token::Or);
+            continue;
+        }
+
+        let field = word
+            .split_once(':')
+            .map(|(name, value)| (name.to_ascii_lowercase(), value.to_string()))
+            .filter(|(name, _)| FIELDS.contains(&name.as_str()));
+
+        match field {
+            Some((name, mut value)) => {
+                if negated {
+                    return Err(AppError::InvalidInput(format!("'{}:' cannot be negated", name)));
+                }
+                if value.is_empty() && pos < chars.len() && chars[pos] == '"' {
+                    value = read_quoted(&chars, &mut pos)?;
+                }
+                if value.trim().is_empty() {
+                    return Err(AppError::InvalidInput(format!("'{}:' needs a value", name)));
+                }
+                tokens.push(Token::Field { name, value: value.trim().to_string() });
+            }
+            None => {
+                let bare = (!word.ends_with('*')).then(|| word.clone());
+                let text = word.trim_end_matches('*').to_string();
+                tokens.push(Token::Term { atom: word_atom(&word), text, word: bare, negated });
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn parse_date(value: &str, now: &DateTime<Local>) -> Result<NaiveDate> {
+    let today = now.date_naive();
+    match value.to_ascii_lowercase().as_str() {
+        "today" => Ok(today),
+        "yesterday" => Ok(today - Duration::days(1)),
+        other => NaiveDate::parse_from_str(other, "%Y-%m-%d").map_err(|_| {
+            AppError::InvalidInput(format!(
+                "Invalid date '{}' (use YYYY-MM-DD, today or yesterday)",
+                value
+            ))
+        }),
+    }
+}
+
+/// Unix timestamp of local midnight at the start of `date`
+fn start_of_day(date: NaiveDate) -> i64 {
+    let midnight = date.and_hms_opt(0, 0, 0).unwrap();
+    Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .map(|dt| dt.timestamp())
+        .unwrap_or_else(|| midnight.and_utc().timestamp())
+}
+
+fn apply_field(filters: &mut SearchFilters, name: &str, value: String, now: &DateTime<Local>) -> Result<()> {
+    match name {
+        "app" => filters.source_app = Some(value),
+        "cat" | "category" => {
+            let category = value.to_lowercase();
+            if !CATEGORIES.contains(&category.as_str()) {
+                return Err(AppError::InvalidInput(format!(
+                    "Unknown category '{}' (expected one of: {})",
+                    value,
+                    CATEGORIES.join(", ")
+                )));
+            }
+            filters.category = Some(category);
+        }
+        "type" => {
+            let content_type = value.to_lowercase();
+            if content_type != "text" && content_type != "image" {
+                return Err(AppError::InvalidInput(format!(
+                    "Unknown type '{}' (expected text or image)",
+                    value
+                )));
+            }
+            filters.content_type = Some(content_type);
+        }
+        "is" => match value.to_lowercase().as_str() {
+            "fav" | "favorite" | "pinned" => filters.is_favorite = Some(true),
+            _ => {
+                return Err(AppError::InvalidInput(format!(
+                    "Unknown 'is:{}' (expected is:fav)",
+                    value
+                )))
+            }
+        },
+        // `before:` excludes the given day, `after:` includes it
+        "before" => filters.date_to = Some(start_of_day(parse_date(&value, now)?) - 1),
+        "after" => filters.date_from = Some(start_of_day(parse_date(&value, now)?)),
+        _ => unreachable!("field names are checked by the tokenizer"),
+    }
+    Ok(())
+}
+
+/// Lone words of this many characters get typo-tolerant matching; longer
+/// queries rely on the token index alone
+const TYPO_WORD_LEN: std::ops::RangeInclusive<usize> = 4..=12;
+
+/// Compile a user search string. `now` resolves relative dates like `yesterday`.
+pub fn parse(input: &str, now: DateTime<Local>) -> Result<ParsedQuery> {
+    let mut groups: Vec<Vec<String>> = vec![Vec::new()];
+    let mut excluded: Vec<String> = Vec::new();
+    let mut filters = SearchFilters::default();
+    let mut words: Vec<Option<String>> = Vec::new();
+    let mut highlight_terms: Vec<String> = Vec::new();
+
+    let or_error = || AppError::InvalidInput("OR must be placed between two search terms".to_string());
+
+    for token in tokenize(input)? {
+        match token {
+            Token::Term { atom, negated: true, .. } => excluded.push(atom),
+            Token::Term { atom, text, word, negated: false } => {
+                groups.last_mut().unwrap().push(atom);
+                words.push(word);
+                if !text.is_empty() {
+                    highlight_terms.push(text);
+                }
+            }
+            Token::Field { name, value } => apply_field(&mut filters, &name, value, &now)?,
+            Token::Or => {
+                if groups.last().unwrap().is_empty() {
+                    return Err(or_error());
+                }
+                groups.push(Vec::new());
+            }
+        }
+    }
+
+    if groups.len() > 1 && groups.last().unwrap().is_empty() {
+        return Err(or_error());
+    }
+
+    let match_expr = match groups.as_slice() {
+        [only] if only.is_empty() => None,
+        [only] => Some(only.join(" AND ")),
+        _ => Some(
+            groups
+                .iter()
+                .map(|group| format!("({})", group.join(" AND ")))
+                .collect::<Vec<_>>()
+                .join(" OR "),
+        ),
+    };
+
+    let exclude_expr = if excluded.is_empty() { None } else { Some(excluded.join(" OR ")) };
+
+    let typo_term = match words.as_slice() {
+        [Some(word)] if TYPO_WORD_LEN.contains(&word.chars().count()) => Some(word.to_lowercase()),
+        _ => None,
+    };
+
+    Ok(ParsedQuery { match_expr, exclude_expr, filters, typo_term, highlight_terms })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 3, 14, 9, 26, 53).unwrap()
+    }
+
+    #[test]
+    fn test_compiles_terms_phrases_and_or() {
+        let parsed = parse(r#"foo-bar "exact phrase" cargo* OR ssh -draft"#, now()).expect("parse");
+        assert_eq!(
+            parsed.match_expr.as_deref(),
+            Some(r#"("foo-bar" AND "exact phrase" AND "cargo"*) OR ("ssh")"#)
+        );
+        assert_eq!(parsed.exclude_expr.as_deref(), Some(r#""draft""#));
+        assert_eq!(parsed.typo_term, None, "only a lone word is typo-matched");
+        assert_eq!(
+            parsed.fts_expression().as_deref(),
+            Some(r#"(("foo-bar" AND "exact phrase" AND "cargo"*) OR ("ssh")) NOT ("draft")"#)
+        );
+    }
+
+    #[test]
+    fn test_operators_become_filters() {
+        let parsed = parse(r#"app:"Google Chrome" cat:URL type:text is:fav after:yesterday before:2026-03-14"#, now())
+            .expect("parse");
+        assert_eq!(parsed.match_expr, None);
+        assert_eq!(parsed.filters.source_app.as_deref(), Some("Google Chrome"));
+        assert_eq!(parsed.filters.category.as_deref(), Some("url"));
+        assert_eq!(parsed.filters.content_type.as_deref(), Some("text"));
+        assert_eq!(parsed.filters.is_favorite, Some(true));
+
+        let yesterday = start_of_day(NaiveDate::from_ymd_opt(2026, 3, 13).unwrap());
+        assert_eq!(parsed.filters.date_from, Some(yesterday));
+        assert_eq!(parsed.filters.date_to, Some(start_of_day(NaiveDate::from_ymd_opt(2026, 3, 14).unwrap()) - 1));
+
+        assert_eq!(parsed.typo_term, None);
+
+        // Unknown prefixes are plain text, so URLs still search normally
+        let url = parse("https://example.com", now()).expect("parse");
+        assert_eq!(url.match_expr.as_deref(), Some(r#""https://example.com""#));
+    }
+
+    #[test]
+    fn test_typo_term_for_lone_short_words() {
+        let parsed = parse("Dokcer -draft app:Terminal", now()).expect("parse");
+        assert_eq!(parsed.typo_term.as_deref(), Some("dokcer"));
+
+        let expr = parsed.typo_candidates_expr().expect("candidates");
+        assert!(expr.starts_with(r#""dok" OR "okc" OR "kce" OR "cer""#));
+        assert!(expr.contains(r#""doc""#), "deletion variants reach the intended word");
+
+        for no_typo in ["ls", "kube*", "\"kubectl\"", "kubectl get", "averyveryverylongword"] {
+            assert_eq!(parse(no_typo, now()).expect("parse").typo_term, None, "{}", no_typo);
+        }
+    }
+
+    #[test]
+    fn test_match_ranges_are_byte_offsets() {
+        let content = "Ünïcode ERROR: kubectl failed; error again";
+        let parsed = parse(r#"error "kubectl failed""#, now()).expect("parse");
+        let ranges = parsed.match_ranges(content);
+
+        let matched: Vec<&str> = ranges.iter().map(|(s, e)| &content[*s..*e]).collect();
+        assert_eq!(matched, vec!["ERROR", "kubectl failed", "error"]);
+
+        let typo = parse("dokcer", now()).expect("parse");
+        assert_eq!(typo.match_ranges("run docker now"), vec![(4, 10)]);
+    }
+
+    #[test]
+    fn test_rejects_malformed_queries() {
+        for bad in [
+            r#"say "hello"#,
+            "OR foo",
+            "foo OR",
+            "foo OR OR bar",
+            "foo - bar",
+            "cat:banana",
+            "type:video",
+            "is:old",
+            "before:last-week",
+            "app:",
+            "-app:Slack",
+        ] {
+            assert!(parse(bad, now()).is_err(), "expected error for {:?}", bad);
+        }
+    }
+}
@@ -9,6 +9,11 @@ static PHONE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(r"\b\d{3}-\d{3}-\d{4}\b|\(\d{3}\)\s*\d{3}-\d{4}").unwrap()
 });
 
+static SECRET_URI_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    // One-time password seeds, and Wi-Fi configs that carry a password
+    Regex::new(r"(?i)\botpauth(-migration)?://|\bWIFI:(?:[^;]*;)*?P:[^;]").unwrap()
+});
+
 /// Check if a string is a valid credit card using Luhn algorithm
 pub fn is_credit_card(content: &str) -> bool {
     // Extract all digit sequences of 13-19 digits
@@ -49,9 +54,14 @@ pub fn is_phone(content: &str) -> bool {
     PHONE_REGEX.is_match(content)
 }
 
-/// Check if content is sensitive (credit card, SSN, or phone)
+/// Check if content contains a 2FA secret or Wi-Fi password URI, as found in QR codes
+pub fn is_secret_uri(content: &str) -> bool {
+    SECRET_URI_REGEX.is_match(content)
+}
+
+/// Check if content is sensitive (credit card, SSN, phone, or secret URI)
 pub fn is_sensitive(content: &str) -> bool {
-    is_credit_card(content) || is_ssn(content) || is_phone(content)
+    is_credit_card(content) || is_ssn(content) || is_phone(content) || is_secret_uri(content)
 }
 
 #[cfg(test)]
@@ -86,6 +96,15 @@ mod tests {
         assert!(!is_phone("5551234")); // Too short
     }
 
+    #[test]
+    fn test_secret_uri_detection() {
+        assert!(is_secret_uri("otpauth://totp/Example:alice?secret=JBSWY3DPEHPK3PXP"));
+        assert!(is_secret_uri("otpauth-migration://offline?data=CjEKCkhlbGxv"));
+        assert!(is_secret_uri("WIFI:T:WPA;S:Office;P:hunter22;;"));
+        assert!(!is_secret_uri("WIFI:T:nopass;S:Guest;;")); // Open network
+        assert!(!is_secret_uri("https://example.com/otpauth"));
+    }
+
     #[test]
     fn test_is_sensitive() {
         assert!(is_sensitive("4532015112830366")); // CC
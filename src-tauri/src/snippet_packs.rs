@@ -0,0 +1,308 @@
+use crate::db::Database;
+use crate::models::{PackDiagnostic, SnippetInput};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Deserialize)]
+struct PackFile {
+    #[serde(default)]
+    folder: Option<String>,          // default folder for every snippet in the file
+    #[serde(default)]
+    tags: Vec<String>,               // added to every snippet in the file
+    #[serde(default)]
+    snippets: Vec<PackSnippet>,
+    #[serde(default)]
+    folders: BTreeMap<String, Vec<PackSnippet>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PackSnippet {
+    name: String,
+    content: String,
+    #[serde(default)]
+    abbreviation: Option<String>,
+    #[serde(default)]
+    folder: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+/// (modified time, size) per pack file; a change in any entry triggers a reload
+type Fingerprint = BTreeMap<PathBuf, (Option<SystemTime>, u64)>;
+
+fn is_pack_file(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase()).as_deref(),
+        Some("yaml" | "yml" | "toml")
+    )
+}
+
+/// Collect pack files below `dir`, skipping hidden entries such as `.git`
+fn collect_pack_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            log::warn!("Failed to read snippet pack directory {}: {}", dir.display(), e);
+            return;
+        }
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        if path.is_dir() {
+            collect_pack_files(&path, files);
+        } else if is_pack_file(&path) {
+            files.push(path);
+        }
+    }
+}
+
+fn fingerprint(files: &[PathBuf]) -> Fingerprint {
+    files
+        .iter()
+        .map(|path| {
+            let meta = std::fs::metadata(path).ok();
+            let modified = meta.as_ref().and_then(|m| m.modified().ok());
+            let len = meta.map(|m| m.len()).unwrap_or(0);
+            (path.clone(), (modified, len))
+        })
+        .collect()
+}
+
+/// Parse one pack file into snippets. Returns a per-file error message on failure.
+pub fn parse_pack(path: &Path, source: &str) -> std::result::Result<(Vec<SnippetInput>, Vec<String>), String> {
+    let is_toml = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("toml"));
+
+    let pack: PackFile = if is_toml {
+        toml::from_str(source).map_err(|e| e.to_string())?
+    } else if source.trim().is_empty() {
+        return Ok((Vec::new(), Vec::new()));
+    } else {
+        serde_yaml::from_str(source).map_err(|e| e.to_string())?
+    };
+
+    let mut snippets = Vec::new();
+    let mut warnings = Vec::new();
+
+    let grouped = pack
+        .snippets
+        .into_iter()
+        .map(|snippet| (None, snippet))
+        .chain(
+            pack.folders
+                .into_iter()
+                .flat_map(|(folder, list)| list.into_iter().map(move |s| (Some(folder.clone()), s))),
+        );
+
+    for (group_folder, snippet) in grouped {
+        let name = snippet.name.clone();
+        let input = SnippetInput {
+            name: snippet.name,
+            abbreviation: snippet.abbreviation,
+            folder: snippet.folder.or(group_folder).or_else(|| pack.folder.clone()),
+            content: snippet.content,
+            tags: pack.tags.iter().cloned().chain(snippet.tags).collect(),
+        };
+
+        match input.normalize() {
+            Ok(input) => snippets.push(input),
+            Err(e) => warnings.push(format!("Skipped snippet '{}': {}", name, e)),
+        }
+    }
+
+    Ok((snippets, warnings))
+}
+
+/// Polls a configured directory for YAML/TOML snippet packs and mirrors them
+/// into the `snippets` table as read-only rows.
+pub struct SnippetPackWatcher {
+    db: Arc<Database>,
+    dir: Mutex<Option<PathBuf>>,
+    fingerprint: Mutex<Option<Fingerprint>>,
+    diagnostics: Mutex<Vec<PackDiagnostic>>,
+}
+
+impl SnippetPackWatcher {
+    pub fn new(db: Arc<Database>) -> Self {
+        SnippetPackWatcher {
+            db,
+            dir: Mutex::new(None),
+            fingerprint: Mutex::new(None),
+            diagnostics: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Point the watcher at a new directory (empty string disables packs)
+    pub fn set_dir(&self, dir: &str) {
+        let dir = dir.trim();
+        *self.dir.lock().unwrap() = if dir.is_empty() { None } else { Some(PathBuf::from(dir)) };
+        // Force a reload on the next poll
+        *self.fingerprint.lock().unwrap() = None;
+    }
+
+    /// Per-file results of the most recent load
+    pub fn diagnostics(&self) -> Vec<PackDiagnostic> {
+        self.diagnostics.lock().unwrap().clone()
+    }
+
+    /// Reload packs if any file was added, removed or modified since the last check
+    pub fn reload_if_changed(&self) {
+        let dir = self.dir.lock().unwrap().clone();
+
+        let mut files = Vec::new();
+        if let Some(dir) = &dir {
+            collect_pack_files(dir, &mut files);
+        }
+        files.sort();
+
+        let current = fingerprint(&files);
+        if self.fingerprint.lock().unwrap().as_ref() == Some(&current) {
+            return;
+        }
+
+        self.load(&files);
+        *self.fingerprint.lock().unwrap() = Some(current);
+    }
+
+    fn load(&self, files: &[PathBuf]) {
+        let mut diagnostics = Vec::with_capacity(files.len());
+        let mut pack_paths = Vec::with_capacity(files.len());
+
+        for path in files {
+            let path_str = path.to_string_lossy().to_string();
+            pack_paths.push(path_str.clone());
+
+            let parsed = std::fs::read_to_string(path)
+                .map_err(|e| e.to_string())
+                .and_then(|source| parse_pack(path, &source));
+
+            let diagnostic = match parsed {
+                Ok((snippets, mut warnings)) => {
+                    match self.db.sync_pack_snippets(&path_str, snippets) {
+                        Ok((snippet_count, sync_warnings)) => {
+                            warnings.extend(sync_warnings);
+                            PackDiagnostic { path: path_str, snippet_count, error: None, warnings }
+                        }
+                        Err(e) => PackDiagnostic {
+                            path: path_str,
+                            snippet_count: 0,
+                            error: Some(e.to_string()),
+                            warnings,
+                        },
+                    }
+                }
+                Err(error) => {
+                    log::warn!("Failed to parse snippet pack {}: {}", path_str, error);
+                    PackDiagnostic { path: path_str, snippet_count: 0, error: Some(error), warnings: Vec::new() }
+                }
+            };
+            diagnostics.push(diagnostic);
+        }
+
+        if let Err(e) = self.db.remove_pack_snippets_except(&pack_paths) {
+            log::error!("Failed to remove snippets from deleted packs: {}", e);
+        }
+
+        log::info!("Loaded {} snippet pack file(s)", diagnostics.len());
+        *self.diagnostics.lock().unwrap() = diagnostics;
+    }
+
+    /// Start polling in a background thread
+    pub fn start(self: &Arc<Self>) {
+        let watcher = self.clone();
+
+        thread::spawn(move || loop {
+            watcher.reload_if_changed();
+            thread::sleep(POLL_INTERVAL);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_yaml_pack_with_folders_and_tags() {
+        let source = r#"
+folder: Support
+tags: [team]
+snippets:
+  - name: Greeting
+    abbreviation: hi
+    content: "Hello {input:Customer name},"
+folders:
+  Billing:
+    - name: Refund
+      tags: [money]
+      content: Your refund is on its way.
+"#;
+        let (snippets, warnings) = parse_pack(Path::new("support.yaml"), source).expect("parse");
+        assert!(warnings.is_empty());
+        assert_eq!(snippets.len(), 2);
+        assert_eq!(snippets[0].folder.as_deref(), Some("Support"));
+        assert_eq!(snippets[1].folder.as_deref(), Some("Billing"));
+        assert_eq!(snippets[1].tags, vec!["team".to_string(), "money".to_string()]);
+    }
+
+    #[test]
+    fn test_parse_toml_pack_and_report_errors() {
+        let source = r#"
+[[snippets]]
+name = "Signature"
+abbreviation = "sig"
+content = "Best regards"
+
+[[snippets]]
+name = "  "
+content = "nameless"
+"#;
+        let (snippets, warnings) = parse_pack(Path::new("team.toml"), source).expect("parse");
+        assert_eq!(snippets.len(), 1);
+        assert_eq!(warnings.len(), 1);
+
+        assert!(parse_pack(Path::new("broken.yaml"), "snippets: [unclosed").is_err());
+        assert!(parse_pack(Path::new("broken.toml"), "snippets = ").is_err());
+    }
+
+    #[test]
+    fn test_watcher_loads_and_reports_per_file() {
+        let data_dir = tempfile::tempdir().expect("tempdir");
+        let pack_dir = tempfile::tempdir().expect("tempdir");
+        let db = Arc::new(Database::new(data_dir.path()).expect("db init"));
+
+        std::fs::write(
+            pack_dir.path().join("good.yml"),
+            "snippets:\n  - name: Ping\n    content: pong\n",
+        )
+        .expect("write good pack");
+        std::fs::write(pack_dir.path().join("bad.toml"), "not = [valid").expect("write bad pack");
+        std::fs::write(pack_dir.path().join("notes.txt"), "ignored").expect("write other file");
+
+        let watcher = SnippetPackWatcher::new(db.clone());
+        watcher.set_dir(&pack_dir.path().to_string_lossy());
+        watcher.reload_if_changed();
+
+        let diagnostics = watcher.diagnostics();
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics.iter().any(|d| d.path.ends_with("bad.toml") && d.error.is_some()));
+        assert!(diagnostics.iter().any(|d| d.path.ends_with("good.yml") && d.snippet_count == 1));
+        assert_eq!(db.search_snippets("pong".to_string(), 10).expect("search").len(), 1);
+
+        watcher.set_dir("");
+        watcher.reload_if_changed();
+        assert!(db.get_snippets(None).expect("snippets").is_empty());
+    }
+}
@@ -0,0 +1,224 @@
+use crate::error::{AppError, Result};
+use crate::models::TemplateExpansion;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local};
+use regex::{Captures, Regex};
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+// Only known placeholder names are expanded, so code snippets full of braces
+// (JSON, Rust, shell `${VAR}`) pass through untouched.
+static PLACEHOLDER_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\{(date|time|clipboard|history|uuid|env|input)(?::([^{}]*))?\}").unwrap()
+});
+
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+const DEFAULT_TIME_FORMAT: &str = "%H:%M:%S";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Placeholder {
+    Date(Option<String>),
+    Time(Option<String>),
+    Clipboard,
+    History(usize),
+    Uuid,
+    Env(String),
+    Input(String),
+}
+
+/// Values the template can pull from outside the snippet itself
+pub struct TemplateContext {
+    pub now: DateTime<Local>,
+    pub clipboard: Option<String>,
+    pub history: Vec<String>, // most recent first; `{history:1}` is index 0
+    pub inputs: HashMap<String, String>,
+    pub allow_env: bool,      // only user-authored snippets may read environment variables
+}
+
+fn parse_placeholder(caps: &Captures) -> Result<Placeholder> {
+    let arg = caps.get(2).map(|m| m.as_str().to_string());
+
+    match &caps[1] {
+        "date" => Ok(Placeholder::Date(arg)),
+        "time" => Ok(Placeholder::Time(arg)),
+        "clipboard" => Ok(Placeholder::Clipboard),
+        "uuid" => Ok(Placeholder::Uuid),
+        "history" => {
+            let n = arg
+                .as_deref()
+                .unwrap_or("1")
+                .trim()
+                .parse::<usize>()
+                .ok()
+                .filter(|n| *n >= 1)
+                .ok_or_else(|| {
+                    AppError::InvalidInput(format!("Invalid history placeholder: {}", &caps[0]))
+                })?;
+            Ok(Placeholder::History(n))
+        }
+        "env" => match arg.filter(|a| !a.trim().is_empty()) {
+            Some(var) => Ok(Placeholder::Env(var.trim().to_string())),
+            None => Err(AppError::InvalidInput("{env} needs a variable name".to_string())),
+        },
+        "input" => match arg.filter(|a| !a.trim().is_empty()) {
+            Some(label) => Ok(Placeholder::Input(label.trim().to_string())),
+            None => Err(AppError::InvalidInput("{input} needs a prompt label".to_string())),
+        },
+        other => Err(AppError::InvalidInput(format!("Unknown placeholder: {}", other))),
+    }
+}
+
+/// List the placeholders used by a template, in order of appearance
+pub fn placeholders(template: &str) -> Result<Vec<Placeholder>> {
+    PLACEHOLDER_REGEX
+        .captures_iter(template)
+        .map(|caps| parse_placeholder(&caps))
+        .collect()
+}
+
+fn format_timestamp(now: &DateTime<Local>, format: Option<&str>, default: &str) -> Result<String> {
+    let format = format.filter(|f| !f.is_empty()).unwrap_or(default);
+
+    // chrono panics when displaying an invalid format, so validate up front
+    if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+        return Err(AppError::InvalidInput(format!("Invalid date/time format: {}", format)));
+    }
+
+    Ok(now.format(format).to_string())
+}
+
+/// Expand a template. Returns the prompts still needing answers if any
+/// `{input:...}` placeholder is missing from `ctx.inputs`.
+pub fn expand(template: &str, ctx: &TemplateContext) -> Result<TemplateExpansion> {
+    let used = placeholders(template)?;
+
+    // Pack snippets come from a shared directory; they must not be able to
+    // paste secrets like AWS_SECRET_ACCESS_KEY out of the user's environment
+    if !ctx.allow_env {
+        if let Some(Placeholder::Env(var)) = used.iter().find(|p| matches!(p, Placeholder::Env(_))) {
+            return Err(AppError::InvalidInput(format!(
+                "{{env:{}}} is only available in your own snippets, not in snippet packs",
+                var
+            )));
+        }
+    }
+
+    let mut prompts: Vec<String> = Vec::new();
+    for placeholder in &used {
+        if let Placeholder::Input(label) = placeholder {
+            if !ctx.inputs.contains_key(label) && !prompts.contains(label) {
+                prompts.push(label.clone());
+            }
+        }
+    }
+
+    if !prompts.is_empty() {
+        return Ok(TemplateExpansion::NeedsInput { prompts });
+    }
+
+    let mut text = String::with_capacity(template.len());
+    let mut last_end = 0;
+
+    for (caps, placeholder) in PLACEHOLDER_REGEX.captures_iter(template).zip(used) {
+        let whole = caps.get(0).unwrap();
+        text.push_str(&template[last_end..whole.start()]);
+        last_end = whole.end();
+
+        let value = match placeholder {
+            Placeholder::Date(format) => format_timestamp(&ctx.now, format.as_deref(), DEFAULT_DATE_FORMAT)?,
+            Placeholder::Time(format) => format_timestamp(&ctx.now, format.as_deref(), DEFAULT_TIME_FORMAT)?,
+            Placeholder::Clipboard => ctx.clipboard.clone().unwrap_or_default(),
+            Placeholder::History(n) => ctx.history.get(n - 1).cloned().unwrap_or_default(),
+            Placeholder::Uuid => uuid::Uuid::new_v4().to_string(),
+            Placeholder::Env(var) => std::env::var(&var).unwrap_or_else(|_| {
+                log::debug!("Template env var {} is not set", var);
+                String::new()
+            }),
+            Placeholder::Input(label) => ctx.inputs.get(&label).cloned().unwrap_or_default(),
+        };
+        text.push_str(&value);
+    }
+
+    text.push_str(&template[last_end..]);
+    Ok(TemplateExpansion::Complete { text })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn context(inputs: &[(&str, &str)]) -> TemplateContext {
+        TemplateContext {
+            now: Local.with_ymd_and_hms(2026, 3, 14, 9, 26, 53).unwrap(),
+            clipboard: Some("from clipboard".to_string()),
+            history: vec!["latest".to_string(), "older".to_string()],
+            inputs: inputs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            allow_env: true,
+        }
+    }
+
+    fn expand_text(template: &str, ctx: &TemplateContext) -> String {
+        match expand(template, ctx).expect("expand") {
+            TemplateExpansion::Complete { text } => text,
+            other => panic!("expected complete expansion, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_expands_builtin_placeholders() {
+        let ctx = context(&[]);
+        assert_eq!(
+            expand_text("{date} {time} {date:%d/%m}", &ctx),
+            "2026-03-14 09:26:53 14/03"
+        );
+        assert_eq!(expand_text("[{clipboard}] {history:2} {history:9}.", &ctx), "[from clipboard] older .");
+        assert_eq!(expand_text("{uuid}", &ctx).len(), 36);
+    }
+
+    #[test]
+    fn test_reports_missing_inputs_once() {
+        let template = "Hi {input:Customer name}, re {input:Ticket}. Thanks {input:Customer name}";
+
+        match expand(template, &context(&[("Ticket", "#42")])).expect("expand") {
+            TemplateExpansion::NeedsInput { prompts } => {
+                assert_eq!(prompts, vec!["Customer name".to_string()]);
+            }
+            other => panic!("expected prompts, got {:?}", other),
+        }
+
+        let ctx = context(&[("Ticket", "#42"), ("Customer name", "Ada")]);
+        assert_eq!(expand_text(template, &ctx), "Hi Ada, re #42. Thanks Ada");
+    }
+
+    #[test]
+    fn test_leaves_unknown_braces_untouched() {
+        let ctx = context(&[]);
+        assert_eq!(
+            expand_text(r#"{"id": 1} ${HOME} {name}"#, &ctx),
+            r#"{"id": 1} ${HOME} {name}"#
+        );
+    }
+
+    #[test]
+    fn test_env_is_only_expanded_when_allowed() {
+        std::env::set_var("SMARTCLIPBOARD_TEMPLATE_TEST", "from env");
+        let mut ctx = context(&[]);
+        assert_eq!(expand_text("{env:SMARTCLIPBOARD_TEMPLATE_TEST}", &ctx), "from env");
+
+        ctx.allow_env = false;
+        assert!(expand("key={env:SMARTCLIPBOARD_TEMPLATE_TEST}", &ctx).is_err());
+        assert_eq!(expand_text("{date}", &ctx), "2026-03-14");
+    }
+
+    #[test]
+    fn test_rejects_malformed_placeholders() {
+        let ctx = context(&[]);
+        assert!(expand("{history:0}", &ctx).is_err());
+        assert!(expand("{env:}", &ctx).is_err());
+        assert!(expand("{date:%Q}", &ctx).is_err());
+    }
+}
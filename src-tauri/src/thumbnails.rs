@@ -0,0 +1,124 @@
+//! Cached thumbnails for image items, stored in `images/thumbs` so list
+//! previews never decode the full-size PNG.
+
+use crate::db::Database;
+use crate::error::{AppError, Result};
+use image::DynamicImage;
+use std::path::{Path, PathBuf};
+
+/// List previews
+pub const SMALL: u32 = 128;
+/// Detail previews
+pub const MEDIUM: u32 = 512;
+
+/// Items handled per database round trip while backfilling
+const BACKFILL_BATCH: u32 = 50;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThumbnailPaths {
+    pub small: String,
+    pub medium: String,
+}
+
+pub fn thumbs_dir(images_dir: &Path) -> PathBuf {
+    images_dir.join("thumbs")
+}
+
+/// Write small and medium thumbnails named after the original image file
+pub fn generate(image: &DynamicImage, image_path: &Path, thumbs_dir: &Path) -> Result<ThumbnailPaths> {
+    std::fs::create_dir_all(thumbs_dir)?;
+    let stem = image_path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .ok_or_else(|| AppError::InvalidInput("Image path has no file name".to_string()))?;
+
+    let write = |size: u32, suffix: &str| -> Result<String> {
+        let path = thumbs_dir.join(format!("{}_{}.png", stem, suffix));
+        let scaled = if image.width() > size || image.height() > size {
+            image.thumbnail(size, size)
+        } else {
+            image.clone()
+        };
+        scaled
+            .save_with_format(&path, image::ImageFormat::Png)
+            .map_err(|e| AppError::InvalidInput(format!("Failed to write thumbnail: {}", e)))?;
+        Ok(path.canonicalize().unwrap_or(path).to_string_lossy().to_string())
+    };
+
+    Ok(ThumbnailPaths { small: write(SMALL, "s")?, medium: write(MEDIUM, "m")? })
+}
+
+/// Create thumbnails for image items stored before thumbnails existed.
+/// Returns how many items got thumbnails; unreadable images are skipped.
+pub fn backfill(db: &Database, images_dir: &Path) -> Result<usize> {
+    let thumbs_dir = thumbs_dir(images_dir);
+    let mut after_id = 0;
+    let mut created = 0;
+
+    loop {
+        let batch = db.get_items_missing_thumbnails(after_id, BACKFILL_BATCH)?;
+        let Some(&(last_id, _)) = batch.last() else {
+            break;
+        };
+        after_id = last_id;
+
+        for (id, image_path) in batch {
+            let thumbnails = image::open(&image_path)
+                .map_err(|e| AppError::InvalidInput(format!("Failed to decode image: {}", e)))
+                .and_then(|image| generate(&image, Path::new(&image_path), &thumbs_dir));
+            match thumbnails {
+                Ok(thumbnails) => {
+                    db.set_thumbnails(id, &thumbnails)?;
+                    created += 1;
+                }
+                Err(e) => log::warn!("Skipping thumbnails for item {}: {}", id, e),
+            }
+        }
+    }
+
+    if created > 0 {
+        log::info!("Created thumbnails for {} existing images", created);
+    }
+    Ok(created)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{backfill, thumbs_dir, MEDIUM, SMALL};
+    use crate::db::{insert_image, Database};
+    use crate::models::PageRequest;
+
+    #[test]
+    fn test_backfill_creates_thumbnails_and_cleanup_removes_them() {
+        let temp_dir = tempfile::tempdir().expect("tempdir");
+        let db = Database::new(temp_dir.path()).expect("db init");
+        let images_dir = temp_dir.path().join("images");
+        std::fs::create_dir_all(&images_dir).expect("images dir");
+
+        let image_path = images_dir.join("100_abcdef.png");
+        image::RgbaImage::from_pixel(1200, 600, image::Rgba([1, 2, 3, 255]))
+            .save(&image_path)
+            .expect("write png");
+        let broken_path = images_dir.join("200_broken.png");
+        std::fs::write(&broken_path, b"not a png").expect("write broken");
+
+        let id = insert_image(&db, &image_path, "hash_thumb_ok");
+        insert_image(&db, &broken_path, "hash_thumb_broken");
+
+        assert_eq!(backfill(&db, &images_dir).expect("backfill"), 1);
+        assert_eq!(backfill(&db, &images_dir).expect("second backfill"), 0, "done items are skipped");
+
+        let (small, medium) = db.get_thumbnail_paths(id).expect("paths");
+        let (small, medium) = (small.expect("small"), medium.expect("medium"));
+        assert!(small.starts_with(&*thumbs_dir(&images_dir).canonicalize().expect("dir").to_string_lossy()));
+        let dims = |path: &str| image::image_dimensions(path).expect("dimensions");
+        assert_eq!(dims(&small), (SMALL, SMALL / 2));
+        assert_eq!(dims(&medium), (MEDIUM, MEDIUM / 2));
+
+        db.cleanup_expired(1).expect("cleanup");
+        assert!(db.get_history(PageRequest::first(10)).expect("history").is_empty());
+        assert!(!image_path.exists());
+        assert!(!std::path::Path::new(&small).exists());
+        assert!(!std::path::Path::new(&medium).exists());
+    }
+}
@@ -0,0 +1,365 @@
+use crate::error::{AppError, Result};
+use crate::models::TransformInfo;
+use base64::Engine;
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use regex::Regex;
+use std::sync::LazyLock;
+
+// RFC 3986 unreserved characters stay as-is, everything else is percent-encoded
+const URL_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+// CSI sequences (colors, cursor movement) and OSC sequences (titles, hyperlinks)
+static ANSI_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\x1b\[[0-?]*[ -/]*[@-~]|\x1b\][^\x07\x1b]*(?:\x07|\x1b\\)").unwrap()
+});
+
+pub struct Transform {
+    pub name: &'static str,
+    pub description: &'static str,
+    apply: fn(&str) -> Result<String>,
+}
+
+static REGISTRY: &[Transform] = &[
+    Transform { name: "trim", description: "Trim leading and trailing whitespace", apply: trim },
+    Transform { name: "collapse_whitespace", description: "Collapse runs of whitespace into single spaces", apply: collapse_whitespace },
+    Transform { name: "lowercase", description: "Convert to lowercase", apply: lowercase },
+    Transform { name: "uppercase", description: "Convert to UPPERCASE", apply: uppercase },
+    Transform { name: "title_case", description: "Capitalize The First Letter Of Each Word", apply: title_case },
+    Transform { name: "snake_case", description: "Convert each line to snake_case", apply: snake_case },
+    Transform { name: "camel_case", description: "Convert each line to camelCase", apply: camel_case },
+    Transform { name: "kebab_case", description: "Convert each line to kebab-case", apply: kebab_case },
+    Transform { name: "url_encode", description: "Percent-encode for use in a URL", apply: url_encode },
+    Transform { name: "url_decode", description: "Decode percent-encoded text", apply: url_decode },
+    Transform { name: "base64_encode", description: "Encode as Base64", apply: base64_encode },
+    Transform { name: "base64_decode", description: "Decode Base64 to UTF-8 text", apply: base64_decode },
+    Transform { name: "json_pretty", description: "Pretty-print JSON", apply: json_pretty },
+    Transform { name: "json_minify", description: "Minify JSON", apply: json_minify },
+    Transform { name: "strip_ansi", description: "Remove ANSI color and escape codes", apply: strip_ansi },
+    Transform { name: "sort_lines", description: "Sort lines alphabetically", apply: sort_lines },
+    Transform { name: "dedupe_lines", description: "Remove duplicate lines, keeping the first occurrence", apply: dedupe_lines },
+    Transform { name: "shell_escape", description: "Quote as a single POSIX shell argument", apply: shell_escape },
+    Transform { name: "sql_escape", description: "Quote as a SQL string literal", apply: sql_escape },
+];
+
+/// List every registered transform (for the UI picker)
+pub fn list_transforms() -> Vec<TransformInfo> {
+    REGISTRY
+        .iter()
+        .map(|t| TransformInfo {
+            name: t.name.to_string(),
+            description: t.description.to_string(),
+        })
+        .collect()
+}
+
+fn find_transform(name: &str) -> Result<&'static Transform> {
+    REGISTRY
+        .iter()
+        .find(|t| t.name == name)
+        .ok_or_else(|| AppError::InvalidInput(format!("Unknown transform: {}", name)))
+}
+
+/// Check that every name in a chain is a registered transform
+pub fn validate_chain(names: &[String]) -> Result<()> {
+    for name in names {
+        find_transform(name)?;
+    }
+    Ok(())
+}
+
+/// Apply a chain of named transforms in order
+pub fn apply_chain(input: &str, names: &[String]) -> Result<String> {
+    let mut output = input.to_string();
+    for name in names {
+        output = (find_transform(name)?.apply)(&output)?;
+    }
+    Ok(output)
+}
+
+/// Join item contents with a separator, optionally wrapping each one in a template.
+/// Templates substitute `{content}` and the 1-based `{index}`.
+pub fn join_contents(contents: &[String], separator: &str, template: Option<&str>) -> Result<String> {
+    let template = template.filter(|t| !t.is_empty());
+
+    if let Some(template) = template {
+        if !template.contains("{content}") {
+            return Err(AppError::InvalidInput(
+                "Join template must contain {content}".to_string(),
+            ));
+        }
+    }
+
+    let parts: Vec<String> = contents
+        .iter()
+        .enumerate()
+        .map(|(i, content)| match template {
+            Some(template) => template
+                .replace("{index}", &(i + 1).to_string())
+                .replace("{content}", content),
+            None => content.clone(),
+        })
+        .collect();
+
+    Ok(parts.join(separator))
+}
+
+/// Run a line-based operation while keeping a trailing newline if there was one
+fn map_lines(input: &str, f: impl FnOnce(Vec<&str>) -> Vec<String>) -> String {
+    let mut output = f(input.lines().collect()).join("\n");
+    if input.ends_with('\n') {
+        output.push('\n');
+    }
+    output
+}
+
+/// Split an identifier-ish line into lowercase words on separators and case boundaries
+fn split_words(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let chars: Vec<char> = line.chars().collect();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+
+        if c.is_uppercase() && !current.is_empty() {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            // "fooBar" -> foo|Bar, "HTTPServer" -> HTTP|Server
+            if prev.is_lowercase() || prev.is_numeric() || (prev.is_uppercase() && next_is_lower) {
+                words.push(std::mem::take(&mut current));
+            }
+        }
+
+        current.extend(c.to_lowercase());
+    }
+
+    if !current.is_empty() {
+        words.push(current);
+    }
+
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn trim(input: &str) -> Result<String> {
+    Ok(input.trim().to_string())
+}
+
+fn collapse_whitespace(input: &str) -> Result<String> {
+    Ok(input.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+fn lowercase(input: &str) -> Result<String> {
+    Ok(input.to_lowercase())
+}
+
+fn uppercase(input: &str) -> Result<String> {
+    Ok(input.to_uppercase())
+}
+
+fn title_case(input: &str) -> Result<String> {
+    let mut output = String::with_capacity(input.len());
+    let mut at_word_start = true;
+
+    for c in input.chars() {
+        if c.is_alphanumeric() {
+            if at_word_start {
+                output.extend(c.to_uppercase());
+            } else {
+                output.extend(c.to_lowercase());
+            }
+            at_word_start = false;
+        } else {
+            output.push(c);
+            at_word_start = c.is_whitespace() || c == '-' || c == '_';
+        }
+    }
+
+    Ok(output)
+}
+
+fn snake_case(input: &str) -> Result<String> {
+    Ok(map_lines(input, |lines| {
+        lines.into_iter().map(|line| split_words(line).join("_")).collect()
+    }))
+}
+
+fn kebab_case(input: &str) -> Result<String> {
+    Ok(map_lines(input, |lines| {
+        lines.into_iter().map(|line| split_words(line).join("-")).collect()
+    }))
+}
+
+fn camel_case(input: &str) -> Result<String> {
+    Ok(map_lines(input, |lines| {
+        lines
+            .into_iter()
+            .map(|line| {
+                split_words(line)
+                    .iter()
+                    .enumerate()
+                    .map(|(i, word)| if i == 0 { word.clone() } else { capitalize(word) })
+                    .collect()
+            })
+            .collect()
+    }))
+}
+
+fn url_encode(input: &str) -> Result<String> {
+    Ok(utf8_percent_encode(input, URL_ENCODE_SET).to_string())
+}
+
+fn url_decode(input: &str) -> Result<String> {
+    percent_decode_str(input)
+        .decode_utf8()
+        .map(|decoded| decoded.into_owned())
+        .map_err(|_| AppError::InvalidInput("URL-decoded text is not valid UTF-8".to_string()))
+}
+
+fn base64_encode(input: &str) -> Result<String> {
+    Ok(base64::engine::general_purpose::STANDARD.encode(input.as_bytes()))
+}
+
+fn base64_decode(input: &str) -> Result<String> {
+    let compact: String = input.chars().filter(|c| !c.is_whitespace()).collect();
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(compact.as_bytes())
+        .map_err(|e| AppError::InvalidInput(format!("Invalid Base64: {}", e)))?;
+
+    String::from_utf8(bytes)
+        .map_err(|_| AppError::InvalidInput("Base64-decoded data is not valid UTF-8".to_string()))
+}
+
+fn parse_json(input: &str) -> Result<serde_json::Value> {
+    serde_json::from_str(input).map_err(|e| AppError::InvalidInput(format!("Invalid JSON: {}", e)))
+}
+
+fn json_pretty(input: &str) -> Result<String> {
+    serde_json::to_string_pretty(&parse_json(input)?)
+        .map_err(|e| AppError::InvalidInput(format!("Failed to format JSON: {}", e)))
+}
+
+fn json_minify(input: &str) -> Result<String> {
+    serde_json::to_string(&parse_json(input)?)
+        .map_err(|e| AppError::InvalidInput(format!("Failed to format JSON: {}", e)))
+}
+
+fn strip_ansi(input: &str) -> Result<String> {
+    Ok(ANSI_REGEX.replace_all(input, "").into_owned())
+}
+
+fn sort_lines(input: &str) -> Result<String> {
+    Ok(map_lines(input, |mut lines| {
+        lines.sort_unstable();
+        lines.into_iter().map(str::to_string).collect()
+    }))
+}
+
+fn dedupe_lines(input: &str) -> Result<String> {
+    Ok(map_lines(input, |lines| {
+        let mut seen = std::collections::HashSet::new();
+        lines
+            .into_iter()
+            .filter(|line| seen.insert(*line))
+            .map(str::to_string)
+            .collect()
+    }))
+}
+
+fn shell_escape(input: &str) -> Result<String> {
+    Ok(format!("'{}'", input.replace('\'', r"'\''")))
+}
+
+fn sql_escape(input: &str) -> Result<String> {
+    Ok(format!("'{}'", input.replace('\'', "''")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_apply_chain_runs_in_order() {
+        let output = apply_chain("  Hello   World  ", &chain(&["trim", "collapse_whitespace", "uppercase"]))
+            .expect("chain");
+        assert_eq!(output, "HELLO WORLD");
+    }
+
+    #[test]
+    fn test_unknown_transform_is_rejected() {
+        assert!(apply_chain("x", &chain(&["reverse"])).is_err());
+        assert!(validate_chain(&chain(&["trim", "nope"])).is_err());
+        assert!(validate_chain(&chain(&["trim", "sql_escape"])).is_ok());
+    }
+
+    #[test]
+    fn test_join_contents_with_template() {
+        let contents = chain(&["alpha", "beta"]);
+        assert_eq!(join_contents(&contents, ", ", None).unwrap(), "alpha, beta");
+        assert_eq!(
+            join_contents(&contents, "\n", Some("{index}. {content}")).unwrap(),
+            "1. alpha\n2. beta"
+        );
+        assert!(join_contents(&contents, "\n", Some("- item")).is_err());
+    }
+
+    #[test]
+    fn test_case_conversions() {
+        assert_eq!(snake_case("fooBar baz-qux").unwrap(), "foo_bar_baz_qux");
+        assert_eq!(snake_case("HTTPServer").unwrap(), "http_server");
+        assert_eq!(camel_case("user_id\nfirst-name").unwrap(), "userId\nfirstName");
+        assert_eq!(kebab_case("SomeValue2").unwrap(), "some-value2");
+        assert_eq!(title_case("hello wORLD").unwrap(), "Hello World");
+    }
+
+    #[test]
+    fn test_encoding_roundtrips() {
+        let text = "a b/c?d=é";
+        assert_eq!(url_encode(text).unwrap(), "a%20b%2Fc%3Fd%3D%C3%A9");
+        assert_eq!(url_decode(&url_encode(text).unwrap()).unwrap(), text);
+        assert_eq!(base64_encode("hello").unwrap(), "aGVsbG8=");
+        assert_eq!(base64_decode("aGVs\nbG8=").unwrap(), "hello");
+        assert!(base64_decode("not base64!").is_err());
+    }
+
+    #[test]
+    fn test_json_transforms_preserve_key_order() {
+        let input = r#"{"b": 1, "a": [1, 2]}"#;
+        assert_eq!(json_minify(input).unwrap(), r#"{"b":1,"a":[1,2]}"#);
+        assert!(json_pretty(input).unwrap().contains("\n  \"b\": 1"));
+        assert!(json_pretty("{broken").is_err());
+    }
+
+    #[test]
+    fn test_line_transforms() {
+        assert_eq!(sort_lines("b\na\nc\n").unwrap(), "a\nb\nc\n");
+        assert_eq!(dedupe_lines("x\ny\nx\nz").unwrap(), "x\ny\nz");
+    }
+
+    #[test]
+    fn test_strip_ansi_and_escaping() {
+        assert_eq!(strip_ansi("\x1b[31mred\x1b[0m text").unwrap(), "red text");
+        assert_eq!(shell_escape("it's").unwrap(), r"'it'\''s'");
+        assert_eq!(sql_escape("O'Brien").unwrap(), "'O''Brien'");
+    }
+}
@@ -1,10 +1,10 @@
 import { useState, useEffect } from "react";
-import type { ClipboardItem } from "../types";
-import { getImageData } from "../lib/ipc";
+import type { ClipboardListItem, ImageMetadata, SimilarImage } from "../types";
+import { findSimilarImages, getItem, getItemContentChunk, imageUrl, THUMB_SMALL } from "../lib/ipc";
 import { CodeBlock } from "./CodeBlock";
 
 interface DetailViewProps {
-  item: ClipboardItem;
+  item: ClipboardListItem;
   onClose: () => void;
   onCopy: (id: number) => void;
   onToggleFavorite: (id: number, isFavorite: boolean) => void;
@@ -16,33 +16,64 @@ function formatDate(timestamp: number): string {
 }
 
 export function DetailView({ item, onClose, onCopy, onToggleFavorite, onDelete }: DetailViewProps) {
-  const [imageSrc, setImageSrc] = useState<string | null>(null);
+  const [content, setContent] = useState("");
+  const [contentLoading, setContentLoading] = useState(item.contentType === "text");
+  const [imageMetadata, setImageMetadata] = useState<ImageMetadata | null>(null);
+  const [similarImages, setSimilarImages] = useState<SimilarImage[]>([]);
 
   useEffect(() => {
-    // Load full image if this is an image item
-    if (item.contentType === "image" && item.imagePath) {
-      let isMounted = true;
-      getImageData(item.imagePath)
-        .then((bytes) => {
-          if (isMounted) {
-            const blob = new Blob([new Uint8Array(bytes)], { type: "image/png" });
-            const url = URL.createObjectURL(blob);
-            setImageSrc(url);
-          }
-        })
-        .catch((err) => console.error("Failed to load image:", err));
-
-      return () => {
-        isMounted = false;
-        setImageSrc((currentSrc) => {
-          if (currentSrc) {
-            URL.revokeObjectURL(currentSrc);
-          }
-          return null;
-        });
-      };
-    }
-  }, [item.contentType, item.imagePath]);
+    if (item.contentType !== "image") return;
+    let cancelled = false;
+    setImageMetadata(null);
+    setSimilarImages([]);
+    getItem(item.id)
+      .then((detail) => {
+        if (!cancelled) setImageMetadata(detail.image);
+      })
+      .catch((err) => console.error("Failed to load image metadata:", err));
+    findSimilarImages(item.id)
+      .then((similar) => {
+        if (!cancelled) setSimilarImages(similar);
+      })
+      .catch((err) => console.error("Failed to load similar images:", err));
+    return () => {
+      cancelled = true;
+    };
+  }, [item.id, item.contentType]);
+
+  useEffect(() => {
+    // List items carry no content; stream it in chunks so large items render progressively
+    if (item.contentType !== "text") return;
+    let cancelled = false;
+    setContent("");
+    setContentLoading(true);
+
+    const load = async () => {
+      try {
+        const detail = await getItem(item.id);
+        let text = detail.content.text;
+        let nextOffset = detail.content.nextOffset;
+        if (!cancelled) setContent(text);
+        while (nextOffset !== null && !cancelled) {
+          const chunk = await getItemContentChunk(item.id, nextOffset);
+          text += chunk.text;
+          nextOffset = chunk.nextOffset;
+          if (!cancelled) setContent(text);
+        }
+      } catch (err) {
+        console.error("Failed to load item content:", err);
+      } finally {
+        if (!cancelled) setContentLoading(false);
+      }
+    };
+    void load();
+
+    return () => {
+      cancelled = true;
+    };
+  }, [item.id, item.contentType]);
+
+  const imageSrc = item.contentType === "image" && item.imagePath ? imageUrl(item.id) : null;
 
   const handleCopy = () => {
     onCopy(item.id);
@@ -101,6 +132,17 @@ export function DetailView({ item, onClose, onCopy, onToggleFavorite, onDelete }
             <span className="text-[var(--text-secondary)]">Type:</span>
             <span className="text-[var(--text-primary)] capitalize">{item.contentType}</span>
           </div>
+          {imageMetadata && (
+            <div className="flex items-center justify-between text-sm">
+              <span className="text-[var(--text-secondary)]">Stored as:</span>
+              <span className="text-[var(--text-primary)]">
+                {imageMetadata.format.toUpperCase()} {imageMetadata.width}×{imageMetadata.height}
+                {(imageMetadata.width !== imageMetadata.originalWidth ||
+                  imageMetadata.height !== imageMetadata.originalHeight) &&
+                  ` (copied at ${imageMetadata.originalWidth}×${imageMetadata.originalHeight})`}
+              </span>
+            </div>
+          )}
         </div>
 
         {/* Full Content */}
@@ -118,31 +160,61 @@ export function DetailView({ item, onClose, onCopy, onToggleFavorite, onDelete }
             </div>
           ) : item.contentType === "image" ? (
             <div className="bg-[var(--bg-secondary)] border border-[var(--border)] rounded-lg p-4 flex items-center justify-center text-[var(--text-secondary)]">
-              Loading image...
+              Image unavailable
             </div>
-          ) : item.category === "code" || item.category === "command" ? (
+          ) : !contentLoading && (item.category === "code" || item.category === "command") ? (
             <div className="bg-[var(--bg-secondary)] border border-[var(--border)] rounded-lg p-4 overflow-hidden">
-              <CodeBlock code={item.content} />
+              <CodeBlock code={content} />
             </div>
           ) : (
             <div className="bg-[var(--bg-secondary)] border border-[var(--border)] rounded-lg p-4">
               <pre className="text-sm text-[var(--text-primary)] whitespace-pre-wrap break-words font-mono">
-                {item.content}
+                {content}
               </pre>
+              {contentLoading && (
+                <p className="text-xs text-[var(--text-secondary)] mt-2">Loading content...</p>
+              )}
             </div>
           )}
         </div>
 
+        {/* Similar Images */}
+        {similarImages.length > 0 && (
+          <div>
+            <label className="block text-sm font-medium text-[var(--text-secondary)] mb-2">
+              Similar Images:
+            </label>
+            <div className="flex flex-wrap gap-2">
+              {similarImages.map((similar) => (
+                <button
+                  key={similar.item.id}
+                  onClick={() => onCopy(similar.item.id)}
+                  className="bg-[var(--bg-secondary)] border border-[var(--border)] rounded-lg p-1 hover:bg-[var(--bg-hover)]"
+                  title={`${similar.item.preview} · ${formatDate(similar.item.copiedAt)}${
+                    similar.linked ? " · near-duplicate" : ""
+                  } · click to copy`}
+                >
+                  <img
+                    src={imageUrl(similar.item.id, THUMB_SMALL)}
+                    alt="Similar clipboard image"
+                    className="w-16 h-16 object-contain rounded"
+                  />
+                </button>
+              ))}
+            </div>
+          </div>
+        )}
+
         {/* Stats */}
         {item.contentType === "text" && (
           <div className="grid grid-cols-2 gap-4 text-sm">
             <div className="bg-[var(--bg-secondary)] border border-[var(--border)] rounded-lg p-3">
               <div className="text-[var(--text-secondary)] mb-1">Length</div>
-              <div className="text-[var(--text-primary)] font-semibold">{item.content.length} chars</div>
+              <div className="text-[var(--text-primary)] font-semibold">{item.contentLength} chars</div>
             </div>
             <div className="bg-[var(--bg-secondary)] border border-[var(--border)] rounded-lg p-3">
               <div className="text-[var(--text-secondary)] mb-1">Lines</div>
-              <div className="text-[var(--text-primary)] font-semibold">{item.content.split('\n').length}</div>
+              <div className="text-[var(--text-primary)] font-semibold">{item.lineCount}</div>
             </div>
           </div>
         )}
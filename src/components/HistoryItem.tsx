@@ -1,14 +1,18 @@
-import type { ClipboardItem, Category } from "../types";
-import { useState, useEffect } from "react";
-import { getImageData } from "../lib/ipc";
+import type { ClipboardListItem, Category, ContextReason } from "../types";
+import { HIGHLIGHT_END, HIGHLIGHT_START } from "../types";
+import { useState } from "react";
+import { imageUrl, THUMB_SMALL } from "../lib/ipc";
 
 interface HistoryItemProps {
-  item: ClipboardItem;
+  item: ClipboardListItem;
   isSelected: boolean;
+  snippet?: string;
+  reason?: ContextReason;
+  contextApp?: string | null;
   onCopy: (id: number) => void;
   onToggleFavorite: (id: number, isFavorite: boolean) => void;
   onDelete: (id: number) => void;
-  onShowDetails?: (item: ClipboardItem) => void;
+  onShowDetails?: (item: ClipboardListItem) => void;
 }
 
 const categoryIcons: Record<Category, string> = {
@@ -22,6 +26,35 @@ const categoryIcons: Record<Category, string> = {
   misc: "📝",
 };
 
+function describeReason(reason: ContextReason, category: Category, app: string) {
+  switch (reason) {
+    case "pastedIntoApp":
+      return `Pasted into ${app} before`;
+    case "frequentCategory":
+      return `Often used in ${app} (${category})`;
+    case "copiedFromApp":
+      return `Copied from ${app}`;
+  }
+}
+
+// Split a search snippet into plain and highlighted segments
+function renderSnippet(snippet: string) {
+  return snippet.split(HIGHLIGHT_START).map((part, i) => {
+    const end = part.indexOf(HIGHLIGHT_END);
+    if (i === 0 || end === -1) {
+      return <span key={i}>{part}</span>;
+    }
+    return (
+      <span key={i}>
+        <mark className="bg-[var(--accent)] text-[var(--text-primary)] rounded-sm">
+          {part.slice(0, end)}
+        </mark>
+        {part.slice(end + 1)}
+      </span>
+    );
+  });
+}
+
 function formatRelativeTime(timestamp: number): string {
   const now = Date.now() / 1000;
   const diff = now - timestamp;
@@ -35,39 +68,17 @@ function formatRelativeTime(timestamp: number): string {
 export function HistoryItem({
   item,
   isSelected,
+  snippet,
+  reason,
+  contextApp,
   onCopy,
   onToggleFavorite,
   onDelete,
   onShowDetails,
 }: HistoryItemProps) {
   const [isHovered, setIsHovered] = useState(false);
-  const [imageSrc, setImageSrc] = useState<string | null>(null);
-
-  useEffect(() => {
-    // Load image thumbnail if this is an image item
-    if (item.contentType === "image" && item.imagePath) {
-      let isMounted = true;
-      getImageData(item.imagePath)
-        .then((bytes) => {
-          if (isMounted) {
-            const blob = new Blob([new Uint8Array(bytes)], { type: "image/png" });
-            const url = URL.createObjectURL(blob);
-            setImageSrc(url);
-          }
-        })
-        .catch((err) => console.error("Failed to load image:", err));
-
-      return () => {
-        isMounted = false;
-        setImageSrc((currentSrc) => {
-          if (currentSrc) {
-            URL.revokeObjectURL(currentSrc);
-          }
-          return null;
-        });
-      };
-    }
-  }, [item.contentType, item.imagePath]);
+  // 48px tile, rendered at 2x on Retina displays
+  const imageSrc = item.contentType === "image" && item.imagePath ? imageUrl(item.id, THUMB_SMALL) : null;
 
   const handleClick = (e: React.MouseEvent) => {
     // Cmd+click or double-click to show details
@@ -114,10 +125,18 @@ export function HistoryItem({
 
       <div className="flex-1 min-w-0">
         <p className="text-sm text-[var(--text-primary)] truncate">
-          {item.preview}
+          {item.title ?? item.preview}
         </p>
+        {snippet && (
+          <p className="text-xs text-[var(--text-secondary)] mt-1 truncate">{renderSnippet(snippet)}</p>
+        )}
         <p className="text-xs text-[var(--text-secondary)] mt-1">
           {item.sourceApp} · {formatRelativeTime(item.copiedAt)}
+          {reason && contextApp && (
+            <span className="ml-1 text-[var(--accent)]">
+              · {describeReason(reason, item.category, contextApp)}
+            </span>
+          )}
         </p>
       </div>
 
@@ -1,7 +1,7 @@
 /* @vitest-environment jsdom */
 import { afterEach, beforeEach, describe, expect, it, vi } from "vitest";
 import { cleanup, fireEvent, render, screen, waitFor } from "@testing-library/react";
-import type { ClipboardItem } from "../types";
+import type { ClipboardListItem, HistoryPage, SearchHit, Snippet } from "../types";
 import { HistoryList } from "./HistoryList";
 
 const mockGetHistory = vi.fn();
@@ -9,6 +9,8 @@ const mockSearch = vi.fn();
 const mockCopyToClipboard = vi.fn();
 const mockSetFavorite = vi.fn();
 const mockDeleteItem = vi.fn();
+const mockRestoreItem = vi.fn();
+const mockExpandSnippet = vi.fn();
 const mockHide = vi.fn();
 
 vi.mock("../lib/ipc", () => ({
@@ -17,6 +19,8 @@ vi.mock("../lib/ipc", () => ({
   copyToClipboard: (...args: unknown[]) => mockCopyToClipboard(...args),
   setFavorite: (...args: unknown[]) => mockSetFavorite(...args),
   deleteItem: (...args: unknown[]) => mockDeleteItem(...args),
+  restoreItem: (...args: unknown[]) => mockRestoreItem(...args),
+  expandSnippet: (...args: unknown[]) => mockExpandSnippet(...args),
 }));
 
 vi.mock("@tauri-apps/api/window", () => ({
@@ -44,7 +48,7 @@ vi.mock("./HistoryItem", () => ({
     isSelected,
     onToggleFavorite,
   }: {
-    item: ClipboardItem;
+    item: ClipboardListItem;
     isSelected: boolean;
     onToggleFavorite: (id: number, isFavorite: boolean) => void;
   }) => (
@@ -65,22 +69,50 @@ vi.mock("./DetailView", () => ({
   DetailView: () => <div>detail</div>,
 }));
 
-function makeItem(id: number, preview: string): ClipboardItem {
+function makeItem(id: number, preview: string): ClipboardListItem {
   return {
     id,
-    content: preview,
     contentType: "text",
     imagePath: null,
     category: "code",
     sourceApp: "Tests",
     preview,
+    contentLength: preview.length,
+    lineCount: 1,
     copiedAt: 1_700_000_000,
     isFavorite: false,
     isSensitive: false,
     hash: `hash-${id}`,
+    title: null,
+    note: null,
+    deletedAt: null,
+    useCount: 0,
+    lastUsedAt: null,
   };
 }
 
+function toHit(item: ClipboardListItem): SearchHit {
+  return { item, snippet: null, matches: [] };
+}
+
+function makeSnippet(id: number, name: string, content: string): Snippet {
+  return {
+    id,
+    name,
+    abbreviation: null,
+    folder: null,
+    content,
+    tags: [],
+    pack: "/packs/team.yaml",
+    createdAt: 1_700_000_000,
+    updatedAt: 1_700_000_000,
+  };
+}
+
+function toPage(items: ClipboardListItem[]): HistoryPage {
+  return { contextApp: null, items: items.map((item) => ({ item, reason: null })), nextCursor: null };
+}
+
 describe("HistoryList", () => {
   afterEach(() => {
     cleanup();
@@ -96,8 +128,8 @@ describe("HistoryList", () => {
 
   it("keeps active filters when refreshing after favorite toggle", async () => {
     const items = [makeItem(1, "first")];
-    mockGetHistory.mockResolvedValue(items);
-    mockSearch.mockResolvedValue(items);
+    mockGetHistory.mockResolvedValue(toPage(items));
+    mockSearch.mockResolvedValue({ items: items.map(toHit), snippets: [], nextCursor: null });
 
     render(<HistoryList />);
 
@@ -120,8 +152,8 @@ describe("HistoryList", () => {
     const initialItems = [makeItem(1, "only")];
     const searchedItems = [makeItem(1, "only"), makeItem(2, "second")];
 
-    mockGetHistory.mockResolvedValue(initialItems);
-    mockSearch.mockResolvedValue(searchedItems);
+    mockGetHistory.mockResolvedValue(toPage(initialItems));
+    mockSearch.mockResolvedValue({ items: searchedItems.map(toHit), snippets: [], nextCursor: null });
 
     render(<HistoryList />);
 
@@ -137,4 +169,28 @@ describe("HistoryList", () => {
       expect(mockCopyToClipboard).toHaveBeenCalledWith(2);
     });
   });
+
+  it("shows snippet matches from search and expands them on click", async () => {
+    mockGetHistory.mockResolvedValue(toPage([makeItem(1, "first")]));
+    mockSearch.mockResolvedValue({
+      items: [],
+      snippets: [makeSnippet(7, "Thank you", "Thanks for reaching out")],
+      nextCursor: null,
+    });
+    mockExpandSnippet.mockResolvedValue({ status: "complete", text: "Thanks for reaching out" });
+
+    render(<HistoryList />);
+    await waitFor(() => expect(screen.getByTestId("item-1")).toBeTruthy());
+
+    fireEvent.click(screen.getByText("trigger-search"));
+    await waitFor(() => expect(screen.getByTestId("snippet-7")).toBeTruthy());
+    expect(screen.getByText("Thanks for reaching out")).toBeTruthy();
+    expect(screen.queryByText("No results found")).toBeNull();
+
+    fireEvent.click(screen.getByTestId("snippet-7"));
+    await waitFor(() => {
+      expect(mockExpandSnippet).toHaveBeenCalledWith(7);
+      expect(mockHide).toHaveBeenCalled();
+    });
+  });
 });
@@ -1,6 +1,14 @@
 import { useCallback, useEffect, useRef, useState } from "react";
-import type { ClipboardItem, SearchFilters } from "../types";
-import { getHistory, search, copyToClipboard, setFavorite, deleteItem } from "../lib/ipc";
+import type { ClipboardListItem, ContextReason, SearchFilters, Snippet } from "../types";
+import {
+  getHistory,
+  search,
+  copyToClipboard,
+  expandSnippet,
+  setFavorite,
+  deleteItem,
+  restoreItem,
+} from "../lib/ipc";
 import { HistoryItem as HistoryItemComponent } from "./HistoryItem";
 import { EmptyState } from "./EmptyState";
 import { SearchBar } from "./SearchBar";
@@ -8,15 +16,20 @@ import { DetailView } from "./DetailView";
 import { getCurrentWindow } from "@tauri-apps/api/window";
 
 export function HistoryList() {
-  const [items, setItems] = useState<ClipboardItem[]>([]);
+  const [items, setItems] = useState<ClipboardListItem[]>([]);
   const [selectedIndex, setSelectedIndex] = useState(0);
   const [loading, setLoading] = useState(true);
   const [isSearching, setIsSearching] = useState(false);
   const [searchQuery, setSearchQuery] = useState("");
   const [searchFilters, setSearchFilters] = useState<SearchFilters>({});
-  const [detailItem, setDetailItem] = useState<ClipboardItem | null>(null);
+  const [detailItem, setDetailItem] = useState<ClipboardListItem | null>(null);
+  const [trashedId, setTrashedId] = useState<number | null>(null);
+  const [snippets, setSnippets] = useState<Record<number, string>>({});
+  const [contextApp, setContextApp] = useState<string | null>(null);
+  const [reasons, setReasons] = useState<Record<number, ContextReason>>({});
+  const [snippetResults, setSnippetResults] = useState<Snippet[]>([]);
 
-  const itemsRef = useRef<ClipboardItem[]>([]);
+  const itemsRef = useRef<ClipboardListItem[]>([]);
   const selectedIndexRef = useRef(0);
   const isSearchingRef = useRef(false);
   const searchQueryRef = useRef("");
@@ -44,8 +57,18 @@ export function HistoryList() {
 
   const loadHistory = useCallback(async () => {
     try {
-      const history = await getHistory(100, 0);
-      setItems(history);
+      const page = await getHistory(100);
+      setItems(page.items.map((entry) => entry.item));
+      setContextApp(page.contextApp);
+      setReasons(
+        Object.fromEntries(
+          page.items
+            .filter((entry) => entry.reason !== null)
+            .map((entry) => [entry.item.id, entry.reason as ContextReason])
+        )
+      );
+      setSnippets({});
+      setSnippetResults([]);
     } catch (error) {
       console.error("Failed to load history:", error);
     } finally {
@@ -63,13 +86,42 @@ export function HistoryList() {
     }
   }, []);
 
+  const handleExpandSnippet = useCallback(async (snippet: Snippet) => {
+    try {
+      let expansion = await expandSnippet(snippet.id);
+      if (expansion.status === "needsInput") {
+        const inputs: Record<string, string> = {};
+        for (const prompt of expansion.prompts) {
+          const value = window.prompt(prompt);
+          if (value === null) return;
+          inputs[prompt] = value;
+        }
+        expansion = await expandSnippet(snippet.id, inputs);
+      }
+      if (expansion.status === "complete") {
+        await getCurrentWindow().hide();
+      }
+    } catch (error) {
+      console.error("Failed to expand snippet:", error);
+    }
+  }, []);
+
   const handleSearch = useCallback(async (query: string, filters: SearchFilters) => {
     setIsSearching(true);
     setSearchQuery(query);
     setSearchFilters(filters);
     try {
       const results = await search(query, filters, 100);
-      setItems(results);
+      setItems(results.items.map((hit) => hit.item));
+      setSnippetResults(results.snippets);
+      setReasons({});
+      setSnippets(
+        Object.fromEntries(
+          results.items
+            .filter((hit) => hit.snippet !== null)
+            .map((hit) => [hit.item.id, hit.snippet as string])
+        )
+      );
     } catch (error) {
       console.error("Failed to search:", error);
     }
@@ -161,12 +213,24 @@ export function HistoryList() {
   const handleDelete = async (id: number) => {
     try {
       await deleteItem(id);
+      setTrashedId(id);
       await refreshCurrentView();
     } catch (error) {
       console.error("Failed to delete item:", error);
     }
   };
 
+  const handleUndoDelete = async () => {
+    if (trashedId === null) return;
+    try {
+      await restoreItem(trashedId);
+      setTrashedId(null);
+      await refreshCurrentView();
+    } catch (error) {
+      console.error("Failed to restore item:", error);
+    }
+  };
+
   if (loading) {
     return (
       <div className="flex items-center justify-center h-full">
@@ -192,7 +256,7 @@ export function HistoryList() {
     <div className="h-full flex flex-col">
       <SearchBar onSearch={handleSearch} onClear={handleClearSearch} />
 
-      {items.length === 0 ? (
+      {items.length === 0 && snippetResults.length === 0 ? (
         <div className="flex-1 flex items-center justify-center">
           {isSearching ? (
             <div className="text-center px-8">
@@ -210,13 +274,39 @@ export function HistoryList() {
           {isSearching && (
             <div className="px-4 py-2 text-xs text-[var(--text-secondary)] bg-[var(--bg-secondary)] border-b border-[var(--border)]">
               Found {items.length} result{items.length !== 1 ? "s" : ""}
+              {snippetResults.length > 0 &&
+                ` and ${snippetResults.length} snippet${snippetResults.length !== 1 ? "s" : ""}`}
             </div>
           )}
+          {snippetResults.map((snippet) => (
+            <button
+              key={`snippet-${snippet.id}`}
+              data-testid={`snippet-${snippet.id}`}
+              onClick={() => void handleExpandSnippet(snippet)}
+              className="w-full text-left px-4 py-2 border-b border-[var(--border)] hover:bg-[var(--bg-hover)]"
+            >
+              <div className="flex items-center gap-2 text-sm text-[var(--text-primary)]">
+                <span>📝 {snippet.name}</span>
+                {snippet.abbreviation && (
+                  <span className="text-xs text-[var(--text-secondary)]">:{snippet.abbreviation}</span>
+                )}
+                {snippet.pack && (
+                  <span className="text-xs px-1.5 rounded bg-[var(--bg-secondary)] text-[var(--text-secondary)]">
+                    pack
+                  </span>
+                )}
+              </div>
+              <p className="text-xs text-[var(--text-secondary)] truncate">{snippet.content}</p>
+            </button>
+          ))}
           {items.map((item, index) => (
             <HistoryItemComponent
               key={item.id}
               item={item}
               isSelected={index === selectedIndex}
+              snippet={snippets[item.id]}
+              reason={reasons[item.id]}
+              contextApp={contextApp}
               onCopy={handleCopy}
               onToggleFavorite={handleToggleFavorite}
               onDelete={handleDelete}
@@ -225,6 +315,15 @@ export function HistoryList() {
           ))}
         </div>
       )}
+
+      {trashedId !== null && (
+        <div className="flex items-center justify-between px-4 py-2 text-xs text-[var(--text-secondary)] bg-[var(--bg-secondary)] border-t border-[var(--border)]">
+          <span>Item moved to trash</span>
+          <button onClick={handleUndoDelete} className="text-[var(--accent)] hover:underline">
+            Undo
+          </button>
+        </div>
+      )}
     </div>
   );
 }
@@ -1,5 +1,5 @@
 import { useState, useEffect } from "react";
-import type { Category, SearchFilters } from "../types";
+import type { Category, SearchFilters, SearchSort } from "../types";
 
 interface SearchBarProps {
   onSearch: (query: string, filters: SearchFilters) => void;
@@ -19,9 +19,17 @@ const categoryLabels: Record<Category, string> = {
   misc: "📝 Misc",
 };
 
+const sortLabels: Record<SearchSort, string> = {
+  relevance: "Best match",
+  newest: "Newest",
+  oldest: "Oldest",
+  mostUsed: "Most used",
+};
+
 export function SearchBar({ onSearch, onClear }: SearchBarProps) {
   const [query, setQuery] = useState("");
   const [selectedCategory, setSelectedCategory] = useState<Category | "">("");
+  const [sort, setSort] = useState<SearchSort>("relevance");
   const [showFilters, setShowFilters] = useState(false);
 
   // Debounce search
@@ -32,16 +40,20 @@ export function SearchBar({ onSearch, onClear }: SearchBarProps) {
         if (selectedCategory) {
           filters.category = selectedCategory;
         }
+        if (sort !== "relevance") {
+          filters.sort = sort;
+        }
         onSearch(query.trim() || "*", filters); // Use "*" for match-all when no query
       }
     }, 300);
 
     return () => clearTimeout(timer);
-  }, [query, selectedCategory]); // eslint-disable-line react-hooks/exhaustive-deps
+  }, [query, selectedCategory, sort]); // eslint-disable-line react-hooks/exhaustive-deps
 
   const handleClear = () => {
     setQuery("");
     setSelectedCategory("");
+    setSort("relevance");
     setShowFilters(false);
     onClear();
   };
@@ -109,6 +121,18 @@ export function SearchBar({ onSearch, onClear }: SearchBarProps) {
               </button>
             ))}
           </div>
+          <label className="block text-xs text-[var(--text-secondary)] mt-3 mb-2">Sort by:</label>
+          <select
+            value={sort}
+            onChange={(e) => setSort(e.target.value as SearchSort)}
+            className="bg-[var(--bg-primary)] text-[var(--text-primary)] px-3 py-1.5 rounded-lg text-xs outline-none"
+          >
+            {(Object.keys(sortLabels) as SearchSort[]).map((value) => (
+              <option key={value} value={value}>
+                {sortLabels[value]}
+              </option>
+            ))}
+          </select>
         </div>
       )}
     </div>
@@ -1,5 +1,5 @@
 import { useEffect, useState } from "react";
-import type { Settings } from "../types";
+import type { OversizedImageAction, Settings } from "../types";
 import { getSettings, updateSettings, getExclusions, addExclusion, removeExclusion } from "../lib/ipc";
 
 interface SettingsPanelProps {
@@ -55,6 +55,22 @@ export function SettingsPanel({ onClose }: SettingsPanelProps) {
       return;
     }
 
+    const oversized = settings.oversizedImages;
+    if (oversized.maxDimension < 256 || oversized.maxDimension > 8192) {
+      alert("Max image dimension must be between 256 and 8192 pixels");
+      return;
+    }
+
+    if (oversized.quality < 1 || oversized.quality > 100) {
+      alert("Image quality must be between 1 and 100");
+      return;
+    }
+
+    if (settings.nearDuplicateImages.maxDistance < 0 || settings.nearDuplicateImages.maxDistance > 16) {
+      alert("Near-duplicate threshold must be between 0 and 16");
+      return;
+    }
+
     setIsSaving(true);
     try {
       await updateSettings(settings);
@@ -160,6 +176,24 @@ export function SettingsPanel({ onClose }: SettingsPanelProps) {
               </p>
             </div>
 
+            <div>
+              <label className="block text-sm font-medium text-[var(--text-primary)] mb-2">
+                Keep Deleted Items
+              </label>
+              <select
+                value={settings.trashRetentionDays}
+                onChange={(e) => setSettings({ ...settings, trashRetentionDays: Number(e.target.value) })}
+                className="w-full bg-[var(--bg-secondary)] text-[var(--text-primary)] px-3 py-2 rounded-lg border border-[var(--border)] outline-none focus:ring-2 focus:ring-[var(--accent)]"
+              >
+                <option value={1}>1 day</option>
+                <option value={7}>7 days</option>
+                <option value={30}>30 days</option>
+              </select>
+              <p className="text-xs text-[var(--text-secondary)] mt-1">
+                Deleted items stay in the trash for this long before they are removed for good
+              </p>
+            </div>
+
             <div>
               <label className="block text-sm font-medium text-[var(--text-primary)] mb-2">
                 Max Items
@@ -178,6 +212,119 @@ export function SettingsPanel({ onClose }: SettingsPanelProps) {
               </p>
             </div>
 
+            <div>
+              <label className="block text-sm font-medium text-[var(--text-primary)] mb-2">
+                Images Over {settings.maxImageSizeMb} MB
+              </label>
+              <select
+                value={settings.oversizedImages.action}
+                onChange={(e) =>
+                  setSettings({
+                    ...settings,
+                    oversizedImages: { ...settings.oversizedImages, action: e.target.value as OversizedImageAction },
+                  })
+                }
+                className="w-full bg-[var(--bg-secondary)] text-[var(--text-primary)] px-3 py-2 rounded-lg border border-[var(--border)] outline-none focus:ring-2 focus:ring-[var(--accent)]"
+              >
+                <option value="skip">Skip</option>
+                <option value="downscale">Downscale</option>
+                <option value="recompress">Recompress</option>
+              </select>
+              {settings.oversizedImages.action === "downscale" && (
+                <input
+                  type="number"
+                  min={256}
+                  max={8192}
+                  step={64}
+                  value={settings.oversizedImages.maxDimension}
+                  onChange={(e) =>
+                    setSettings({
+                      ...settings,
+                      oversizedImages: { ...settings.oversizedImages, maxDimension: Number(e.target.value) },
+                    })
+                  }
+                  className="mt-2 w-full bg-[var(--bg-secondary)] text-[var(--text-primary)] px-3 py-2 rounded-lg border border-[var(--border)] outline-none focus:ring-2 focus:ring-[var(--accent)]"
+                />
+              )}
+              {settings.oversizedImages.action === "recompress" && (
+                <div className="flex gap-2 mt-2">
+                  <select
+                    value={settings.oversizedImages.format}
+                    onChange={(e) =>
+                      setSettings({
+                        ...settings,
+                        oversizedImages: { ...settings.oversizedImages, format: e.target.value as "webp" | "jpeg" },
+                      })
+                    }
+                    className="w-full bg-[var(--bg-secondary)] text-[var(--text-primary)] px-3 py-2 rounded-lg border border-[var(--border)] outline-none focus:ring-2 focus:ring-[var(--accent)]"
+                  >
+                    <option value="webp">WebP</option>
+                    <option value="jpeg">JPEG</option>
+                  </select>
+                  <input
+                    type="number"
+                    min={1}
+                    max={100}
+                    value={settings.oversizedImages.quality}
+                    onChange={(e) =>
+                      setSettings({
+                        ...settings,
+                        oversizedImages: { ...settings.oversizedImages, quality: Number(e.target.value) },
+                      })
+                    }
+                    className="w-full bg-[var(--bg-secondary)] text-[var(--text-primary)] px-3 py-2 rounded-lg border border-[var(--border)] outline-none focus:ring-2 focus:ring-[var(--accent)]"
+                  />
+                </div>
+              )}
+              <p className="text-xs text-[var(--text-secondary)] mt-1">
+                {settings.oversizedImages.action === "skip"
+                  ? "Images larger than this are not saved"
+                  : settings.oversizedImages.action === "downscale"
+                    ? "Scale images down so the longest side fits this many pixels"
+                    : "Re-encode images in a lossy format at this quality (1-100)"}
+              </p>
+            </div>
+
+            <div>
+              <label className="block text-sm font-medium text-[var(--text-primary)] mb-2">
+                Near-Duplicate Images
+              </label>
+              <div className="flex gap-2">
+                <select
+                  value={settings.nearDuplicateImages.action}
+                  onChange={(e) =>
+                    setSettings({
+                      ...settings,
+                      nearDuplicateImages: {
+                        ...settings.nearDuplicateImages,
+                        action: e.target.value as "merge" | "link",
+                      },
+                    })
+                  }
+                  className="w-full bg-[var(--bg-secondary)] text-[var(--text-primary)] px-3 py-2 rounded-lg border border-[var(--border)] outline-none focus:ring-2 focus:ring-[var(--accent)]"
+                >
+                  <option value="link">Keep and link</option>
+                  <option value="merge">Merge into existing</option>
+                </select>
+                <input
+                  type="number"
+                  min={0}
+                  max={16}
+                  value={settings.nearDuplicateImages.maxDistance}
+                  onChange={(e) =>
+                    setSettings({
+                      ...settings,
+                      nearDuplicateImages: { ...settings.nearDuplicateImages, maxDistance: Number(e.target.value) },
+                    })
+                  }
+                  className="w-full bg-[var(--bg-secondary)] text-[var(--text-primary)] px-3 py-2 rounded-lg border border-[var(--border)] outline-none focus:ring-2 focus:ring-[var(--accent)]"
+                />
+              </div>
+              <p className="text-xs text-[var(--text-secondary)] mt-1">
+                Images whose perceptual hash differs by at most this many bits (0-16) count as the same picture
+              </p>
+            </div>
+
             <div>
               <label className="block text-sm font-medium text-[var(--text-primary)] mb-2">
                 Keyboard Shortcut
@@ -192,6 +339,22 @@ export function SettingsPanel({ onClose }: SettingsPanelProps) {
                 Custom shortcuts coming in Phase 3
               </p>
             </div>
+
+            <div>
+              <label className="block text-sm font-medium text-[var(--text-primary)] mb-2">
+                Team Snippet Packs
+              </label>
+              <input
+                type="text"
+                placeholder="/path/to/snippet-packs"
+                value={settings.snippetPackDir}
+                onChange={(e) => setSettings({ ...settings, snippetPackDir: e.target.value })}
+                className="w-full bg-[var(--bg-secondary)] text-[var(--text-primary)] px-3 py-2 rounded-lg border border-[var(--border)] outline-none focus:ring-2 focus:ring-[var(--accent)]"
+              />
+              <p className="text-xs text-[var(--text-secondary)] mt-1">
+                Directory of YAML or TOML snippet packs, reloaded when files change
+              </p>
+            </div>
           </div>
         )}
 
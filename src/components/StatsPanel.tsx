@@ -1,56 +1,47 @@
 import { useEffect, useState } from "react";
-import type { ClipboardItem, Category } from "../types";
-import { getHistory } from "../lib/ipc";
+import type { Category, ClipboardStats, StatsBucket } from "../types";
+import { getStats } from "../lib/ipc";
 
 interface StatsPanelProps {
   onClose: () => void;
 }
 
-interface CategoryStats {
-  category: Category;
-  count: number;
-  percentage: number;
+type StatsRange = "all" | "week" | "day";
+
+const rangeLabels: Record<StatsRange, string> = {
+  all: "All time",
+  week: "Last 7 days",
+  day: "Last 24 hours",
+};
+
+function formatBytes(bytes: number) {
+  if (bytes < 1024) return `${bytes} B`;
+  if (bytes < 1024 * 1024) return `${(bytes / 1024).toFixed(1)} KB`;
+  return `${(bytes / (1024 * 1024)).toFixed(1)} MB`;
 }
 
 export function StatsPanel({ onClose }: StatsPanelProps) {
-  const [items, setItems] = useState<ClipboardItem[]>([]);
+  const [range, setRange] = useState<StatsRange>("all");
   const [loading, setLoading] = useState(true);
-  const [stats, setStats] = useState<CategoryStats[]>([]);
+  const [stats, setStats] = useState<ClipboardStats | null>(null);
 
   useEffect(() => {
-    loadData();
-  }, []);
+    void loadData(range);
+  }, [range]);
 
-  const loadData = async () => {
+  const loadData = async (range: StatsRange) => {
+    const now = Math.floor(Date.now() / 1000);
+    const dateFrom = range === "week" ? now - 7 * 86400 : range === "day" ? now - 86400 : null;
+    const bucket: StatsBucket = range === "day" ? "hour" : "day";
     try {
-      const history = await getHistory(10000, 0); // Get all items for stats
-      setItems(history);
-      calculateStats(history);
+      setStats(await getStats(dateFrom, null, bucket));
     } catch (error) {
-      console.error("Failed to load history:", error);
+      console.error("Failed to load statistics:", error);
     } finally {
       setLoading(false);
     }
   };
 
-  const calculateStats = (items: ClipboardItem[]) => {
-    const categoryCounts: Record<string, number> = {};
-
-    items.forEach((item) => {
-      categoryCounts[item.category] = (categoryCounts[item.category] || 0) + 1;
-    });
-
-    const categoryStats: CategoryStats[] = Object.entries(categoryCounts)
-      .map(([category, count]) => ({
-        category: category as Category,
-        count,
-        percentage: (count / items.length) * 100,
-      }))
-      .sort((a, b) => b.count - a.count);
-
-    setStats(categoryStats);
-  };
-
   const categoryIcons: Record<Category, string> = {
     url: "🔗",
     email: "📧",
@@ -73,10 +64,6 @@ export function StatsPanel({ onClose }: StatsPanelProps) {
     misc: "Miscellaneous",
   };
 
-  const totalFavorites = items.filter((item) => item.isFavorite).length;
-  const totalSize = items.reduce((acc, item) => acc + item.content.length, 0);
-  const avgSize = items.length > 0 ? Math.round(totalSize / items.length) : 0;
-
   if (loading) {
     return (
       <div className="h-full flex items-center justify-center">
@@ -85,17 +72,44 @@ export function StatsPanel({ onClose }: StatsPanelProps) {
     );
   }
 
+  if (!stats) {
+    return (
+      <div className="h-full flex items-center justify-center">
+        <p className="text-[var(--text-secondary)]">Statistics are unavailable</p>
+      </div>
+    );
+  }
+
+  const peakCaptures = Math.max(1, ...stats.captureRate.map((bucket) => bucket.count));
+  const formatBucket = (start: number) =>
+    range === "day"
+      ? new Date(start * 1000).toLocaleTimeString([], { hour: "2-digit", minute: "2-digit" })
+      : new Date(start * 1000).toLocaleDateString();
+
   return (
     <div className="h-full flex flex-col bg-[var(--bg-primary)]">
       {/* Header */}
       <div className="flex items-center justify-between px-4 py-3 border-b border-[var(--border)] bg-[var(--bg-secondary)]">
         <h2 className="text-lg font-semibold text-[var(--text-primary)]">Statistics</h2>
-        <button
-          onClick={onClose}
-          className="text-[var(--text-secondary)] hover:text-[var(--text-primary)] text-xl"
-        >
-          ✕
-        </button>
+        <div className="flex items-center gap-3">
+          <select
+            value={range}
+            onChange={(e) => setRange(e.target.value as StatsRange)}
+            className="bg-[var(--bg-primary)] text-[var(--text-primary)] px-2 py-1 rounded-lg text-xs outline-none"
+          >
+            {(Object.keys(rangeLabels) as StatsRange[]).map((value) => (
+              <option key={value} value={value}>
+                {rangeLabels[value]}
+              </option>
+            ))}
+          </select>
+          <button
+            onClick={onClose}
+            className="text-[var(--text-secondary)] hover:text-[var(--text-primary)] text-xl"
+          >
+            ✕
+          </button>
+        </div>
       </div>
 
       {/* Content */}
@@ -104,21 +118,21 @@ export function StatsPanel({ onClose }: StatsPanelProps) {
         <div className="grid grid-cols-2 gap-4">
           <div className="bg-[var(--bg-secondary)] border border-[var(--border)] rounded-lg p-4">
             <div className="text-[var(--text-secondary)] text-sm mb-1">Total Items</div>
-            <div className="text-[var(--text-primary)] text-3xl font-bold">{items.length}</div>
+            <div className="text-[var(--text-primary)] text-3xl font-bold">{stats.totalItems}</div>
           </div>
           <div className="bg-[var(--bg-secondary)] border border-[var(--border)] rounded-lg p-4">
             <div className="text-[var(--text-secondary)] text-sm mb-1">Favorites</div>
-            <div className="text-[var(--text-primary)] text-3xl font-bold">{totalFavorites}</div>
+            <div className="text-[var(--text-primary)] text-3xl font-bold">{stats.favoriteCount}</div>
           </div>
           <div className="bg-[var(--bg-secondary)] border border-[var(--border)] rounded-lg p-4">
-            <div className="text-[var(--text-secondary)] text-sm mb-1">Total Size</div>
-            <div className="text-[var(--text-primary)] text-2xl font-bold">
-              {(totalSize / 1024).toFixed(1)} KB
+            <div className="text-[var(--text-secondary)] text-sm mb-1">Text / Images</div>
+            <div className="text-[var(--text-primary)] text-xl font-bold">
+              {formatBytes(stats.textBytes)} / {formatBytes(stats.imageBytes)}
             </div>
           </div>
           <div className="bg-[var(--bg-secondary)] border border-[var(--border)] rounded-lg p-4">
-            <div className="text-[var(--text-secondary)] text-sm mb-1">Avg Size</div>
-            <div className="text-[var(--text-primary)] text-2xl font-bold">{avgSize} chars</div>
+            <div className="text-[var(--text-secondary)] text-sm mb-1">Sensitive Skipped</div>
+            <div className="text-[var(--text-primary)] text-3xl font-bold">{stats.sensitiveSkipped}</div>
           </div>
         </div>
 
@@ -128,65 +142,82 @@ export function StatsPanel({ onClose }: StatsPanelProps) {
             Items by Category
           </h3>
           <div className="space-y-3">
-            {stats.map((stat) => (
-              <div
-                key={stat.category}
-                className="bg-[var(--bg-secondary)] border border-[var(--border)] rounded-lg p-3"
-              >
-                <div className="flex items-center justify-between mb-2">
-                  <div className="flex items-center gap-2">
-                    <span className="text-xl">{categoryIcons[stat.category]}</span>
-                    <span className="text-sm text-[var(--text-primary)]">
-                      {categoryLabels[stat.category]}
+            {stats.byCategory.map((stat) => {
+              const category = stat.key as Category;
+              const percentage = stats.totalItems > 0 ? (stat.count / stats.totalItems) * 100 : 0;
+              return (
+                <div
+                  key={stat.key}
+                  className="bg-[var(--bg-secondary)] border border-[var(--border)] rounded-lg p-3"
+                >
+                  <div className="flex items-center justify-between mb-2">
+                    <div className="flex items-center gap-2">
+                      <span className="text-xl">{categoryIcons[category]}</span>
+                      <span className="text-sm text-[var(--text-primary)]">
+                        {categoryLabels[category] ?? stat.key}
+                      </span>
+                    </div>
+                    <span className="text-sm font-semibold text-[var(--text-primary)]">
+                      {stat.count}
                     </span>
                   </div>
-                  <span className="text-sm font-semibold text-[var(--text-primary)]">
-                    {stat.count}
-                  </span>
-                </div>
-                <div className="w-full bg-[var(--bg-hover)] rounded-full h-2">
-                  <div
-                    className="bg-[var(--accent)] h-2 rounded-full transition-all"
-                    style={{ width: `${stat.percentage}%` }}
-                  />
-                </div>
-                <div className="text-xs text-[var(--text-secondary)] mt-1">
-                  {stat.percentage.toFixed(1)}%
+                  <div className="w-full bg-[var(--bg-hover)] rounded-full h-2">
+                    <div
+                      className="bg-[var(--accent)] h-2 rounded-full transition-all"
+                      style={{ width: `${percentage}%` }}
+                    />
+                  </div>
+                  <div className="text-xs text-[var(--text-secondary)] mt-1">
+                    {percentage.toFixed(1)}%
+                  </div>
                 </div>
-              </div>
-            ))}
+              );
+            })}
           </div>
         </div>
 
-        {/* Recent Activity */}
+        {/* Sources and Types */}
+        <div className="grid grid-cols-2 gap-4">
+          {[
+            { title: "Top Sources", counts: stats.bySourceApp.slice(0, 5) },
+            { title: "Content Types", counts: stats.byContentType },
+          ].map(({ title, counts }) => (
+            <div key={title}>
+              <h3 className="text-sm font-medium text-[var(--text-primary)] mb-3">{title}</h3>
+              <div className="bg-[var(--bg-secondary)] border border-[var(--border)] rounded-lg p-3 space-y-1 text-sm">
+                {counts.length === 0 && <div className="text-[var(--text-secondary)]">N/A</div>}
+                {counts.map((entry) => (
+                  <div key={entry.key} className="flex justify-between">
+                    <span className="text-[var(--text-secondary)] truncate">{entry.key}</span>
+                    <span className="text-[var(--text-primary)]">{entry.count}</span>
+                  </div>
+                ))}
+              </div>
+            </div>
+          ))}
+        </div>
+
+        {/* Capture Rate */}
         <div>
           <h3 className="text-sm font-medium text-[var(--text-primary)] mb-3">
-            Recent Activity
+            Captures per {range === "day" ? "Hour" : "Day"}
           </h3>
-          <div className="bg-[var(--bg-secondary)] border border-[var(--border)] rounded-lg p-4">
-            <div className="space-y-2 text-sm">
-              <div className="flex justify-between">
-                <span className="text-[var(--text-secondary)]">Last copied:</span>
-                <span className="text-[var(--text-primary)]">
-                  {items.length > 0
-                    ? new Date(items[0].copiedAt * 1000).toLocaleString()
-                    : "Never"}
-                </span>
-              </div>
-              <div className="flex justify-between">
-                <span className="text-[var(--text-secondary)]">Most common source:</span>
-                <span className="text-[var(--text-primary)]">
-                  {items.length > 0
-                    ? Object.entries(
-                        items.reduce((acc, item) => {
-                          acc[item.sourceApp] = (acc[item.sourceApp] || 0) + 1;
-                          return acc;
-                        }, {} as Record<string, number>)
-                      ).sort((a, b) => b[1] - a[1])[0][0]
-                    : "N/A"}
-                </span>
+          <div className="bg-[var(--bg-secondary)] border border-[var(--border)] rounded-lg p-3 space-y-1">
+            {stats.captureRate.length === 0 && (
+              <div className="text-sm text-[var(--text-secondary)]">No captures yet</div>
+            )}
+            {stats.captureRate.slice(-14).map((bucket) => (
+              <div key={bucket.start} className="flex items-center gap-2 text-xs">
+                <span className="w-20 text-[var(--text-secondary)]">{formatBucket(bucket.start)}</span>
+                <div className="flex-1 bg-[var(--bg-hover)] rounded-full h-2">
+                  <div
+                    className="bg-[var(--accent)] h-2 rounded-full"
+                    style={{ width: `${(bucket.count / peakCaptures) * 100}%` }}
+                  />
+                </div>
+                <span className="w-8 text-right text-[var(--text-primary)]">{bucket.count}</span>
               </div>
-            </div>
+            ))}
           </div>
         </div>
       </div>
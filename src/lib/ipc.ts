@@ -1,16 +1,55 @@
-import { invoke } from "@tauri-apps/api/core";
-import type { ClipboardItem, SearchFilters, Settings } from "../types";
+import { convertFileSrc, invoke } from "@tauri-apps/api/core";
+import type {
+  BulkAction,
+  BulkResult,
+  BulkSelection,
+  ClipboardItem,
+  ClipboardListItem,
+  ClipboardStats,
+  Collection,
+  ContentChunk,
+  HistoryPage,
+  ItemDetail,
+  ItemRevision,
+  JoinResult,
+  PackDiagnostic,
+  QueueOrder,
+  QueueState,
+  SearchFilters,
+  SearchMode,
+  SearchResults,
+  Settings,
+  SimilarImage,
+  Snippet,
+  SnippetInput,
+  StatsBucket,
+  TemplateExpansion,
+  TransformChain,
+  TransformInfo,
+  UsageEvent,
+} from "../types";
 
-export async function getHistory(limit = 100, offset = 0): Promise<ClipboardItem[]> {
-  return invoke("get_history", { limit, offset });
+/** Pass the previous page's `nextCursor` to continue where it ended */
+export async function getHistory(limit = 100, cursor: string | null = null): Promise<HistoryPage> {
+  return invoke("get_history", { limit, cursor });
 }
 
 export async function search(
   query: string,
   filters: SearchFilters,
-  limit = 100
-): Promise<ClipboardItem[]> {
-  return invoke("search", { query, filters, limit });
+  limit = 100,
+  mode: SearchMode = "fuzzy",
+  cursor: string | null = null
+): Promise<SearchResults> {
+  return invoke("search", { query, filters, limit, mode, cursor });
+}
+
+export async function getItem(id: number): Promise<ItemDetail> {
+  return invoke("get_item", { id });
+}
+
+export async function getItemContentChunk(id: number, offset: number): Promise<ContentChunk> {
+  return invoke("get_item_content_chunk", { id, offset });
 }
 
 export async function copyToClipboard(id: number): Promise<void> {
@@ -21,10 +60,76 @@ export async function setFavorite(id: number, isFavorite: boolean): Promise<void
   return invoke("set_favorite", { id, isFavorite });
 }
 
+export async function setAnnotation(
+  id: number,
+  title: string | null,
+  note: string | null
+): Promise<void> {
+  return invoke("set_annotation", { id, title, note });
+}
+
+export async function updateItemContent(
+  id: number,
+  content: string
+): Promise<ClipboardItem> {
+  return invoke("update_item_content", { id, content });
+}
+
+export async function getItemRevisions(id: number): Promise<ItemRevision[]> {
+  return invoke("get_item_revisions", { id });
+}
+
+export async function revertItemRevision(
+  id: number,
+  revisionId: number
+): Promise<ClipboardItem> {
+  return invoke("revert_item_revision", { id, revisionId });
+}
+
 export async function deleteItem(id: number): Promise<void> {
   return invoke("delete_item", { id });
 }
 
+export async function restoreItem(id: number): Promise<ClipboardItem> {
+  return invoke("restore_item", { id });
+}
+
+export async function getFrequentItems(limit = 50): Promise<ClipboardListItem[]> {
+  return invoke("get_frequent_items", { limit });
+}
+
+export async function getStats(
+  dateFrom: number | null = null,
+  dateTo: number | null = null,
+  bucket: StatsBucket = "day"
+): Promise<ClipboardStats> {
+  return invoke("get_stats", { dateFrom, dateTo, bucket });
+}
+
+export async function getUsageEvents(id: number): Promise<UsageEvent[]> {
+  return invoke("get_usage_events", { id });
+}
+
+/** Image items that look like `id`, closest first */
+export async function findSimilarImages(id: number): Promise<SimilarImage[]> {
+  return invoke("find_similar_images", { id });
+}
+
+export async function getTrash(): Promise<ClipboardListItem[]> {
+  return invoke("get_trash");
+}
+
+export async function bulkUpdate(
+  selection: BulkSelection,
+  action: BulkAction
+): Promise<BulkResult> {
+  return invoke("bulk_update", { selection, action });
+}
+
+export async function clearHistory(): Promise<BulkResult> {
+  return invoke("clear_history");
+}
+
 export async function getSettings(): Promise<Settings> {
   return invoke("get_settings");
 }
@@ -45,6 +150,152 @@ export async function removeExclusion(appName: string): Promise<void> {
   return invoke("remove_exclusion", { appName });
 }
 
-export async function getImageData(imagePath: string): Promise<number[]> {
-  return invoke("get_image_data", { imagePath });
+/** Size of the small thumbnail cached for every image (`thumbnails::SMALL`), served without re-encoding */
+export const THUMB_SMALL = 128;
+
+/** URL of an item's image on the `clip-image` scheme; `thumb` fits it within thumb×thumb pixels */
+export function imageUrl(id: number, thumb?: number): string {
+  const url = convertFileSrc(String(id), "clip-image");
+  return thumb ? `${url}?thumb=${thumb}` : url;
+}
+
+export async function enqueue(id: number): Promise<QueueState> {
+  return invoke("enqueue", { id });
+}
+
+export async function dequeue(): Promise<ClipboardItem | null> {
+  return invoke("dequeue");
+}
+
+export async function peek(): Promise<ClipboardItem | null> {
+  return invoke("peek");
+}
+
+export async function clearQueue(): Promise<void> {
+  return invoke("clear_queue");
+}
+
+export async function getQueue(): Promise<QueueState> {
+  return invoke("get_queue");
+}
+
+export async function setQueueMode(order: QueueOrder, captureNew: boolean): Promise<QueueState> {
+  return invoke("set_queue_mode", { order, captureNew });
+}
+
+export async function copyTransformed(id: number, transforms: string[]): Promise<string> {
+  return invoke("copy_transformed", { id, transforms });
+}
+
+export async function copyJoined(
+  ids: number[],
+  separator: string,
+  template: string | null = null,
+  save = false
+): Promise<JoinResult> {
+  return invoke("copy_joined", { ids, separator, template, save });
+}
+
+export async function getDerivedFrom(id: number): Promise<number[]> {
+  return invoke("get_derived_from", { id });
+}
+
+export async function listTransforms(): Promise<TransformInfo[]> {
+  return invoke("list_transforms");
+}
+
+export async function getTransformChains(): Promise<TransformChain[]> {
+  return invoke("get_transform_chains");
+}
+
+export async function saveTransformChain(name: string, transforms: string[]): Promise<void> {
+  return invoke("save_transform_chain", { name, transforms });
+}
+
+export async function deleteTransformChain(name: string): Promise<void> {
+  return invoke("delete_transform_chain", { name });
+}
+
+export async function getSnippets(folder: string | null = null): Promise<Snippet[]> {
+  return invoke("get_snippets", { folder });
+}
+
+export async function getSnippet(id: number): Promise<Snippet> {
+  return invoke("get_snippet", { id });
+}
+
+export async function createSnippet(snippet: SnippetInput): Promise<Snippet> {
+  return invoke("create_snippet", { snippet });
+}
+
+export async function updateSnippet(id: number, snippet: SnippetInput): Promise<Snippet> {
+  return invoke("update_snippet", { id, snippet });
+}
+
+export async function deleteSnippet(id: number): Promise<void> {
+  return invoke("delete_snippet", { id });
+}
+
+export async function findSnippets(query: string, limit = 20): Promise<Snippet[]> {
+  return invoke("find_snippets", { query, limit });
+}
+
+export async function promoteToSnippet(
+  id: number,
+  name: string,
+  abbreviation: string | null = null,
+  folder: string | null = null
+): Promise<Snippet> {
+  return invoke("promote_to_snippet", { id, name, abbreviation, folder });
+}
+
+export async function expandSnippet(
+  id: number,
+  inputs: Record<string, string> | null = null
+): Promise<TemplateExpansion> {
+  return invoke("expand_snippet", { id, inputs });
+}
+
+export async function getSnippetPackDiagnostics(): Promise<PackDiagnostic[]> {
+  return invoke("get_snippet_pack_diagnostics");
+}
+
+export async function reloadSnippetPacks(): Promise<PackDiagnostic[]> {
+  return invoke("reload_snippet_packs");
+}
+
+export async function getCollections(): Promise<Collection[]> {
+  return invoke("get_collections");
+}
+
+export async function createCollection(name: string): Promise<number> {
+  return invoke("create_collection", { name });
+}
+
+export async function renameCollection(id: number, name: string): Promise<void> {
+  return invoke("rename_collection", { id, name });
+}
+
+export async function deleteCollection(id: number): Promise<void> {
+  return invoke("delete_collection", { id });
+}
+
+export async function reorderCollections(ids: number[]): Promise<void> {
+  return invoke("reorder_collections", { ids });
+}
+
+export async function getCollectionItems(collectionId: number): Promise<ClipboardListItem[]> {
+  return invoke("get_collection_items", { collectionId });
+}
+
+export async function addToCollection(collectionId: number, itemId: number): Promise<void> {
+  return invoke("add_to_collection", { collectionId, itemId });
+}
+
+export async function removeFromCollection(collectionId: number, itemId: number): Promise<void> {
+  return invoke("remove_from_collection", { collectionId, itemId });
+}
+
+export async function reorderCollectionItems(collectionId: number, itemIds: number[]): Promise<void> {
+  return invoke("reorder_collection_items", { collectionId, itemIds });
 }
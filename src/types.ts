@@ -10,6 +10,44 @@ export interface ClipboardItem {
   isFavorite: boolean;
   isSensitive: boolean;
   hash: string;
+  title: string | null;
+  note: string | null;
+  deletedAt: number | null;
+  useCount: number;
+  lastUsedAt: number | null;
+}
+
+/** An item as listed by history and search: no content, see `getItem` */
+export type ClipboardListItem = Omit<ClipboardItem, "content"> & {
+  /** Content length in characters */
+  contentLength: number;
+  lineCount: number;
+};
+
+/** A slice of an item's content; offsets are UTF-8 byte offsets */
+export interface ContentChunk {
+  text: string;
+  offset: number;
+  nextOffset: number | null;
+  totalBytes: number;
+}
+
+export type StoredImageFormat = "png" | "webp" | "jpeg";
+
+export interface ImageMetadata {
+  originalWidth: number;
+  originalHeight: number;
+  width: number;
+  height: number;
+  format: StoredImageFormat;
+  byteSize: number;
+}
+
+export interface ItemDetail {
+  item: ClipboardListItem;
+  /** First chunk; load the rest with `getItemContentChunk` */
+  content: ContentChunk;
+  image: ImageMetadata | null;
 }
 
 export type Category = "url" | "email" | "error" | "code" | "command" | "ip" | "path" | "misc";
@@ -20,6 +58,38 @@ export interface SearchFilters {
   dateTo?: number;
   sourceApp?: string;
   contentType?: "text" | "image";
+  collectionId?: number;
+  isFavorite?: boolean;
+  sort?: SearchSort;
+}
+
+export type SearchSort = "relevance" | "newest" | "oldest" | "mostUsed";
+
+export interface RankingWeights {
+  text: number;
+  recency: number;
+  recencyHalfLifeDays: number;
+  favorite: number;
+  usage: number;
+}
+
+export type SearchMode = "exact" | "fuzzy";
+
+export type OversizedImageAction = "skip" | "downscale" | "recompress";
+
+export interface OversizedImagePolicy {
+  action: OversizedImageAction;
+  /** Longest side after downscaling, in pixels */
+  maxDimension: number;
+  format: "webp" | "jpeg";
+  /** 1-100 */
+  quality: number;
+}
+
+export interface NearDuplicatePolicy {
+  action: "merge" | "link";
+  /** Differing perceptual-hash bits still counted as the same image, 0-16 */
+  maxDistance: number;
 }
 
 export interface Settings {
@@ -28,4 +98,171 @@ export interface Settings {
   keyboardShortcut: string;
   autoExcludeSensitive: boolean;
   maxImageSizeMb: number;
+  snippetPackDir: string;
+  trashRetentionDays: number;
+  rankingWeights: RankingWeights;
+  oversizedImages: OversizedImagePolicy;
+  nearDuplicateImages: NearDuplicatePolicy;
+}
+
+export type QueueOrder = "fifo" | "lifo";
+
+export interface QueueState {
+  order: QueueOrder;
+  captureNew: boolean;
+  itemIds: number[];
+}
+
+export interface TransformInfo {
+  name: string;
+  description: string;
+}
+
+export interface TransformChain {
+  name: string;
+  transforms: string[];
+  updatedAt: number;
+}
+
+export interface JoinResult {
+  text: string;
+  itemId: number | null;
+}
+
+export interface Snippet {
+  id: number;
+  name: string;
+  abbreviation: string | null;
+  folder: string | null;
+  content: string;
+  tags: string[];
+  pack: string | null;
+  createdAt: number;
+  updatedAt: number;
+}
+
+export interface SnippetInput {
+  name: string;
+  abbreviation?: string | null;
+  folder?: string | null;
+  content: string;
+  tags?: string[];
+}
+
+export interface PackDiagnostic {
+  path: string;
+  snippetCount: number;
+  error: string | null;
+  warnings: string[];
+}
+
+export interface MatchRange {
+  start: number; // byte offset into content (UTF-8)
+  end: number;
+}
+
+// Highlighted terms in `snippet` are wrapped in these markers
+export const HIGHLIGHT_START = "\u0002";
+export const HIGHLIGHT_END = "\u0003";
+
+export interface SearchHit {
+  item: ClipboardListItem;
+  snippet: string | null;
+  matches: MatchRange[];
+}
+
+/** Why an item moved up in history ordered for the app the window was opened from */
+export type ContextReason = "pastedIntoApp" | "frequentCategory" | "copiedFromApp";
+
+export interface HistoryEntry {
+  item: ClipboardListItem;
+  reason: ContextReason | null;
+}
+
+export interface HistoryPage {
+  contextApp: string | null;
+  items: HistoryEntry[];
+  /** Opaque cursor for the next page; null on the last page */
+  nextCursor: string | null;
+}
+
+export interface SearchResults {
+  items: SearchHit[];
+  snippets: Snippet[];
+  /** Opaque cursor for the next page; null on the last page and for ranked sorts */
+  nextCursor: string | null;
+}
+
+export type TemplateExpansion =
+  | { status: "complete"; text: string }
+  | { status: "needsInput"; prompts: string[] };
+
+export interface Collection {
+  id: number;
+  name: string;
+  position: number;
+  itemCount: number;
+  createdAt: number;
+}
+
+export interface SimilarImage {
+  item: ClipboardListItem;
+  /** Differing bits of the 64-bit perceptual hash; 0 looks identical */
+  distance: number;
+  /** Recorded as a near-duplicate when it was captured */
+  linked: boolean;
+}
+
+export interface UsageEvent {
+  kind: "capture" | "paste";
+  app: string | null;
+  occurredAt: number;
+}
+
+export type StatsBucket = "day" | "hour";
+
+export interface KeyCount {
+  key: string;
+  count: number;
+}
+
+export interface TimeCount {
+  /** Bucket start (unix seconds), aligned to local midnight or hour */
+  start: number;
+  count: number;
+}
+
+export interface ClipboardStats {
+  totalItems: number;
+  favoriteCount: number;
+  sensitiveSkipped: number;
+  textBytes: number;
+  imageBytes: number;
+  byCategory: KeyCount[];
+  bySourceApp: KeyCount[];
+  byContentType: KeyCount[];
+  byTime: TimeCount[];
+  captureRate: TimeCount[];
+}
+
+export interface ItemRevision {
+  id: number;
+  itemId: number;
+  content: string;
+  replacedAt: number;
+}
+
+export type BulkSelection =
+  | { by: "ids"; ids: number[] }
+  | { by: "search"; query: string; filters: SearchFilters };
+
+export type BulkAction =
+  | { action: "delete" }
+  | { action: "favorite" }
+  | { action: "unfavorite" }
+  | { action: "addToCollection"; collectionId: number }
+  | { action: "retag"; category: Category };
+
+export interface BulkResult {
+  affected: number;
 }